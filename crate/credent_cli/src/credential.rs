@@ -0,0 +1,18 @@
+use std::future::Future;
+
+use credent_cli_model::Error;
+
+/// Credentials type that can be read from the command line.
+///
+/// This is usually implemented with `#[derive(Credential)]`, behind the
+/// `"derive"` feature, which prompts for each field in order, hides input for
+/// fields marked `#[credent(secret)]`, and masks them in `Debug` and
+/// `Display`. Each field's type must implement `FromStr` to parse the entered
+/// text.
+pub trait Credential: Sized {
+    /// Reads each field from the terminal, validating it once entered.
+    fn prompt_from_tty() -> impl Future<Output = Result<Self, Error>> + Send;
+
+    /// Checks each field's value, such as after loading it from file.
+    fn validate(&self) -> Result<(), Error>;
+}
@@ -1,7 +1,10 @@
-use std::{fmt::Display, io};
+use std::{
+    fmt::Display,
+    io::{self, BufRead},
+};
 
 use credent_cli_model::Error;
-use credent_model::{Credentials, Password, Username};
+use credent_model::{Credentials, Password, Username, UsernamePolicy, ValidatedUsername};
 
 const CREDENTIALS_CLI_READER_PLAIN: CredentialsCliReader<&str, &str> = CredentialsCliReader {
     username_prompt: "Username: ",
@@ -26,15 +29,89 @@ impl CredentialsCliReader<(), ()> {
         Ok(Credentials { username, password })
     }
 
+    /// Reads the username and password from the terminal, normalizing the
+    /// username with the given policy.
+    pub async fn read_from_tty_with<P>() -> Result<Credentials<ValidatedUsername<P>>, Error>
+    where
+        P: UsernamePolicy,
+    {
+        let username = Self::read_username_with::<P>().await?;
+        let password = Self::read_password().await?;
+
+        Ok(Credentials { username, password })
+    }
+
     /// Reads the username from the terminal.
     pub async fn read_username() -> Result<Username, Error> {
         CREDENTIALS_CLI_READER_PLAIN.prompt_username().await
     }
 
+    /// Reads the username from the terminal, normalizing it with the given
+    /// policy.
+    pub async fn read_username_with<P>() -> Result<ValidatedUsername<P>, Error>
+    where
+        P: UsernamePolicy,
+    {
+        CREDENTIALS_CLI_READER_PLAIN
+            .prompt_username_with::<P>()
+            .await
+    }
+
     /// Reads the password from the terminal.
     pub async fn read_password() -> Result<Password, Error> {
         CREDENTIALS_CLI_READER_PLAIN.prompt_password().await
     }
+
+    /// Writes a message to the terminal on its own line, such as why an
+    /// entered value was rejected.
+    pub async fn write_message(message: &str) -> Result<(), Error> {
+        CredentialsCliReader::<&str, &str>::message_write(message).await
+    }
+
+    /// Reads any deserializable credentials type from the terminal, prompting
+    /// for each field.
+    ///
+    /// Fields whose names match the default secret patterns are read with
+    /// hidden input. Use [`CredentialsFieldReader`] to configure the
+    /// patterns.
+    ///
+    /// [`CredentialsFieldReader`]: crate::CredentialsFieldReader
+    #[cfg(feature = "serde")]
+    pub async fn read_fields_from_tty<C>() -> Result<C, Error>
+    where
+        C: serde::de::DeserializeOwned + Send + 'static,
+    {
+        crate::CredentialsFieldReader::new().read_from_tty().await
+    }
+}
+
+/// Reads a line without its line ending.
+///
+/// Returns an [`io::ErrorKind::UnexpectedEof`] error when the input has
+/// ended, so that callers that ask again do not loop forever.
+fn line_read_from<R>(mut reader: R) -> io::Result<String>
+where
+    R: BufRead,
+{
+    let mut line = String::new();
+    match reader.read_line(&mut line)? {
+        0 => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Input ended before a line was entered.",
+        )),
+        _ => Ok(line_ending_trim(line)),
+    }
+}
+
+/// Returns the line without its trailing `"\n"` or `"\r\n"`.
+fn line_ending_trim(mut line: String) -> String {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    line
 }
 
 #[cfg(feature = "smol")]
@@ -47,17 +124,30 @@ where
     PasswordPrompt: Display,
 {
     /// Reads the username and password from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
     pub async fn prompt_from_tty(&self) -> Result<Credentials, Error> {
-        let username = self.prompt_username().await?;
-        let password = self.prompt_password().await?;
+        let username = self.username_read().await?;
+        let password = self.password_read().await?;
 
         Ok(Credentials { username, password })
     }
 
     /// Reads the username from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
     pub async fn prompt_username(&self) -> Result<Username, Error> {
+        self.username_read().await
+    }
+
+    /// Reads the username from the terminal without a span of its own.
+    async fn username_read(&self) -> Result<Username, Error> {
         let prompt = self.username_prompt.to_string();
-        let username = Self::prompt_plain_text(&prompt)
+        let username = Self::plain_text_read(&prompt)
             .await
             .map(Username)
             .map_err(|e| {
@@ -71,10 +161,41 @@ where
         Ok(username)
     }
 
+    /// Reads the username from the terminal, normalizing it with the given
+    /// policy.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn prompt_username_with<P>(&self) -> Result<ValidatedUsername<P>, Error>
+    where
+        P: UsernamePolicy,
+    {
+        let prompt = self.username_prompt.to_string();
+        let username = Self::plain_text_read(&prompt).await.map_err(|e| {
+            if let Error::PlainTextRead(error) = e {
+                Error::UsernameRead(error)
+            } else {
+                e
+            }
+        })?;
+
+        ValidatedUsername::new(username).map_err(Error::UsernameInvalid)
+    }
+
     /// Reads the password from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
     pub async fn prompt_password(&self) -> Result<Password, Error> {
+        self.password_read().await
+    }
+
+    /// Reads the password from the terminal without a span of its own.
+    async fn password_read(&self) -> Result<Password, Error> {
         let prompt = self.password_prompt.to_string();
-        let password = Self::prompt_secret(&prompt)
+        let password = Self::secret_read(&prompt)
             .await
             .map(Password::new)
             .map_err(|e| {
@@ -89,40 +210,81 @@ where
     }
 
     /// Reads a plain text value from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(prompt = %prompt), err(Display))
+    )]
     pub async fn prompt_plain_text(prompt: &str) -> Result<String, Error> {
-        let mut stderr = Unblock::new(io::stderr());
-        stderr.write_all(prompt.as_bytes()).await.map_err(|error| {
-            let prompt = prompt.to_string();
-            Error::PromptWrite { prompt, error }
-        })?;
-        stderr.flush().await.map_err(Error::StdErrFlush)?;
+        Self::plain_text_read(prompt).await
+    }
 
-        let value = smol::unblock(|| {
-            let mut value = String::new();
-            io::stdin()
-                .read_line(&mut value)
-                .map(|_| value.trim().to_string())
-        })
-        .await
-        .map_err(Error::PlainTextRead)?;
+    /// Reads a plain text value from the terminal without a span of its own.
+    ///
+    /// Used by prompts that record errors in their own span.
+    pub(crate) async fn plain_text_read(prompt: &str) -> Result<String, Error> {
+        Self::line_read(prompt)
+            .await
+            .map(|line| line.trim().to_string())
+    }
+
+    /// Reads a line from the terminal as it was entered, without its line
+    /// ending.
+    pub(crate) async fn line_read(prompt: &str) -> Result<String, Error> {
+        Self::stderr_write(prompt).await?;
+
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        let value = smol::unblock(|| line_read_from(io::stdin().lock()))
+            .await
+            .map_err(Error::PlainTextRead)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?started.elapsed(), "Read plain text value.");
 
         Ok(value)
     }
 
-    /// Reads a secret value from the terminal.
-    pub async fn prompt_secret(prompt: &str) -> Result<String, Error> {
+    /// Writes a message to the terminal on its own line.
+    pub(crate) async fn message_write(message: &str) -> Result<(), Error> {
+        Self::stderr_write(&format!("{message}\n")).await
+    }
+
+    /// Writes the text to stderr without blocking, and flushes it.
+    async fn stderr_write(text: &str) -> Result<(), Error> {
         let mut stderr = Unblock::new(io::stderr());
-        stderr.write_all(prompt.as_bytes()).await.map_err(|error| {
-            let prompt = prompt.to_string();
+        stderr.write_all(text.as_bytes()).await.map_err(|error| {
+            let prompt = text.to_string();
             Error::PromptWrite { prompt, error }
         })?;
-        stderr.flush().await.map_err(Error::StdErrFlush)?;
+        stderr.flush().await.map_err(Error::StdErrFlush)
+    }
+
+    /// Reads a secret value from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(prompt = %prompt), err(Display))
+    )]
+    pub async fn prompt_secret(prompt: &str) -> Result<String, Error> {
+        Self::secret_read(prompt).await
+    }
+
+    /// Reads a secret value from the terminal without a span of its own.
+    ///
+    /// Used by prompts that record errors in their own span.
+    pub(crate) async fn secret_read(prompt: &str) -> Result<String, Error> {
+        Self::stderr_write(prompt).await?;
 
         // Read secret value on a separate thread.
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
         let secret =
             smol::unblock(|| rpassword::read_password_from_tty(None).map_err(Error::SecretRead))
                 .await?;
 
+        // The secret itself is never recorded.
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?started.elapsed(), "Read secret value.");
+
         Ok(secret)
     }
 }
@@ -137,17 +299,30 @@ where
     PasswordPrompt: Display,
 {
     /// Reads the username and password from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
     pub async fn prompt_from_tty(&self) -> Result<Credentials, Error> {
-        let username = self.prompt_username().await?;
-        let password = self.prompt_password().await?;
+        let username = self.username_read().await?;
+        let password = self.password_read().await?;
 
         Ok(Credentials { username, password })
     }
 
     /// Reads the username from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
     pub async fn prompt_username(&self) -> Result<Username, Error> {
+        self.username_read().await
+    }
+
+    /// Reads the username from the terminal without a span of its own.
+    async fn username_read(&self) -> Result<Username, Error> {
         let prompt = self.username_prompt.to_string();
-        let username = Self::prompt_plain_text(&prompt)
+        let username = Self::plain_text_read(&prompt)
             .await
             .map(Username)
             .map_err(|e| {
@@ -161,10 +336,41 @@ where
         Ok(username)
     }
 
+    /// Reads the username from the terminal, normalizing it with the given
+    /// policy.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn prompt_username_with<P>(&self) -> Result<ValidatedUsername<P>, Error>
+    where
+        P: UsernamePolicy,
+    {
+        let prompt = self.username_prompt.to_string();
+        let username = Self::plain_text_read(&prompt).await.map_err(|e| {
+            if let Error::PlainTextRead(error) = e {
+                Error::UsernameRead(error)
+            } else {
+                e
+            }
+        })?;
+
+        ValidatedUsername::new(username).map_err(Error::UsernameInvalid)
+    }
+
     /// Reads the password from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
     pub async fn prompt_password(&self) -> Result<Password, Error> {
+        self.password_read().await
+    }
+
+    /// Reads the password from the terminal without a span of its own.
+    async fn password_read(&self) -> Result<Password, Error> {
         let prompt = self.password_prompt.to_string();
-        let password = Self::prompt_secret(&prompt)
+        let password = Self::secret_read(&prompt)
             .await
             .map(Password::new)
             .map_err(|e| {
@@ -179,43 +385,115 @@ where
     }
 
     /// Reads a plain text value from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(prompt = %prompt), err(Display))
+    )]
     pub async fn prompt_plain_text(prompt: &str) -> Result<String, Error> {
-        let mut stderr = tokio::io::stderr();
-        stderr.write_all(prompt.as_bytes()).await.map_err(|error| {
-            let prompt = prompt.to_string();
-            Error::PromptWrite { prompt, error }
-        })?;
-        stderr.flush().await.map_err(Error::StdErrFlush)?;
+        Self::plain_text_read(prompt).await
+    }
 
-        let value = tokio::task::spawn_blocking(|| {
-            let mut value = String::new();
-            io::stdin()
-                .read_line(&mut value)
-                .map(|_| value.trim().to_string())
-        })
-        .await
-        .map_err(Error::StdinReadJoin)?
-        .map_err(Error::PlainTextRead)?;
+    /// Reads a plain text value from the terminal without a span of its own.
+    ///
+    /// Used by prompts that record errors in their own span.
+    pub(crate) async fn plain_text_read(prompt: &str) -> Result<String, Error> {
+        Self::line_read(prompt)
+            .await
+            .map(|line| line.trim().to_string())
+    }
+
+    /// Reads a line from the terminal as it was entered, without its line
+    /// ending.
+    pub(crate) async fn line_read(prompt: &str) -> Result<String, Error> {
+        Self::stderr_write(prompt).await?;
+
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        let value = tokio::task::spawn_blocking(|| line_read_from(io::stdin().lock()))
+            .await
+            .map_err(Error::StdinReadJoin)?
+            .map_err(Error::PlainTextRead)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?started.elapsed(), "Read plain text value.");
 
         Ok(value)
     }
 
-    /// Reads a secret value from the terminal.
-    pub async fn prompt_secret(prompt: &str) -> Result<String, Error> {
+    /// Writes a message to the terminal on its own line.
+    pub(crate) async fn message_write(message: &str) -> Result<(), Error> {
+        Self::stderr_write(&format!("{message}\n")).await
+    }
+
+    /// Writes the text to stderr without blocking, and flushes it.
+    async fn stderr_write(text: &str) -> Result<(), Error> {
         let mut stderr = tokio::io::stderr();
-        stderr.write_all(prompt.as_bytes()).await.map_err(|error| {
-            let prompt = prompt.to_string();
+        stderr.write_all(text.as_bytes()).await.map_err(|error| {
+            let prompt = text.to_string();
             Error::PromptWrite { prompt, error }
         })?;
-        stderr.flush().await.map_err(Error::StdErrFlush)?;
+        stderr.flush().await.map_err(Error::StdErrFlush)
+    }
+
+    /// Reads a secret value from the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(prompt = %prompt), err(Display))
+    )]
+    pub async fn prompt_secret(prompt: &str) -> Result<String, Error> {
+        Self::secret_read(prompt).await
+    }
+
+    /// Reads a secret value from the terminal without a span of its own.
+    ///
+    /// Used by prompts that record errors in their own span.
+    pub(crate) async fn secret_read(prompt: &str) -> Result<String, Error> {
+        Self::stderr_write(prompt).await?;
 
         // Read secret value on a separate thread.
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
         let secret = tokio::task::spawn_blocking(|| {
             rpassword::read_password_from_tty(None).map_err(Error::SecretRead)
         })
         .await
         .map_err(Error::StdinReadJoin)??;
 
+        // The secret itself is never recorded.
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?started.elapsed(), "Read secret value.");
+
         Ok(secret)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{line_ending_trim, line_read_from};
+
+    #[test]
+    fn line_read_from_returns_err_at_end_of_input() -> Result<(), io::Error> {
+        assert_eq!("", line_read_from(&b"\n"[..])?);
+        assert_eq!("value", line_read_from(&b"value\r\n"[..])?);
+
+        let line_result = line_read_from(&b""[..]);
+        assert!(
+            matches!(&line_result, Err(error) if error.kind() == io::ErrorKind::UnexpectedEof),
+            "Expected `line_result` to return `UnexpectedEof` error, but got `{:?}`.",
+            line_result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn line_ending_trim_keeps_other_whitespace() {
+        assert_eq!(
+            "  indented ",
+            line_ending_trim(String::from("  indented \n"))
+        );
+        assert_eq!("\tline", line_ending_trim(String::from("\tline\r\n")));
+        assert_eq!("last", line_ending_trim(String::from("last")));
+    }
+}
@@ -0,0 +1,110 @@
+use credent_cli_model::Error;
+use serde::de::DeserializeOwned;
+
+use crate::field_deserializer::{FieldDeserializer, FieldSource};
+
+/// Reads any deserializable credentials type from the command line, prompting
+/// for each field.
+///
+/// Fields are prompted for in declaration order, using the field name in
+/// sentence case, e.g. `"Api key: "`. Nested structs are prompted for field by
+/// field, numbers and booleans are parsed from the entered text, and
+/// `Option`al fields are `None` when nothing is entered.
+///
+/// Fields whose name contains a secret pattern, ignoring case, are read with
+/// hidden input.
+///
+/// ```rust,ignore
+/// let credentials = CredentialsFieldReader::new()
+///     .with_secret_pattern("pin")
+///     .read_from_tty::<ApiCredentials>()
+///     .await?;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsFieldReader {
+    /// Field name patterns whose values are read with hidden input.
+    secret_patterns: Vec<String>,
+}
+
+impl CredentialsFieldReader {
+    /// Field name patterns read with hidden input by default.
+    pub const SECRET_PATTERNS_DEFAULT: &'static [&'static str] =
+        &["password", "secret", "token", "key"];
+
+    /// Returns a new `CredentialsFieldReader` with the default secret
+    /// patterns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field name pattern whose values are read with hidden input.
+    pub fn with_secret_pattern<P>(mut self, secret_pattern: P) -> Self
+    where
+        P: Into<String>,
+    {
+        self.secret_patterns.push(secret_pattern.into());
+        self
+    }
+
+    /// Sets the field name patterns whose values are read with hidden input,
+    /// replacing the defaults.
+    pub fn with_secret_patterns<I, P>(mut self, secret_patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        self.secret_patterns = secret_patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns the field name patterns whose values are read with hidden
+    /// input.
+    pub fn secret_patterns(&self) -> &[String] {
+        &self.secret_patterns
+    }
+
+    /// Prompts for each field of `C` on the terminal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn read_from_tty<C>(&self) -> Result<C, Error>
+    where
+        C: DeserializeOwned + Send + 'static,
+    {
+        let secret_patterns = self.secret_patterns.clone();
+        let read = move || {
+            let source = FieldSource::Prompt {
+                secret_patterns: &secret_patterns,
+            };
+            C::deserialize(FieldDeserializer::new(source)).map_err(Error::from)
+        };
+
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
+        #[cfg(feature = "smol")]
+        let credentials = smol::unblock(read).await?;
+        #[cfg(feature = "tokio")]
+        let credentials = tokio::task::spawn_blocking(read)
+            .await
+            .map_err(Error::StdinReadJoin)??;
+
+        // Values are never recorded, as they may be secrets.
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?started.elapsed(), "Read credentials fields.");
+
+        Ok(credentials)
+    }
+}
+
+impl Default for CredentialsFieldReader {
+    fn default() -> Self {
+        Self {
+            secret_patterns: Self::SECRET_PATTERNS_DEFAULT
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,631 @@
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use crate::{PromptAnswer, PromptAnswers};
+use credent_cli_model::Error;
+use credent_model::Password;
+use serde::de::{
+    self, value::StrDeserializer, DeserializeSeed, IntoDeserializer, MapAccess, Visitor,
+};
+
+/// Where a `FieldDeserializer` reads values from.
+#[derive(Clone, Copy)]
+pub(crate) enum FieldSource<'r> {
+    /// Prompts for each value on the terminal as it is requested.
+    ///
+    /// This blocks on stdin, so it is run on a blocking thread.
+    Prompt {
+        /// Field name patterns whose values are read with hidden input.
+        secret_patterns: &'r [String],
+    },
+    /// Reads each value from answers keyed by the field path.
+    Answers(&'r PromptAnswers),
+}
+
+/// Deserializer that reads each field value as it is requested, such as by
+/// prompting for it.
+pub(crate) struct FieldDeserializer<'r> {
+    /// Where values are read from.
+    source: FieldSource<'r>,
+    /// Dotted path of the field, empty for the top level value.
+    path: String,
+    /// Value already read for this field, such as for an `Option`.
+    value: Option<String>,
+}
+
+impl<'r> FieldDeserializer<'r> {
+    /// Returns a deserializer for the top level value.
+    pub(crate) fn new(source: FieldSource<'r>) -> Self {
+        Self {
+            source,
+            path: String::new(),
+            value: None,
+        }
+    }
+
+    /// Returns the deserializer for a field of the struct at this path.
+    fn field(&self, field: &str) -> Self {
+        let path = if self.path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", self.path, field)
+        };
+
+        Self {
+            source: self.source,
+            path,
+            value: None,
+        }
+    }
+
+    /// Returns whether the field name matches a secret pattern.
+    fn is_secret(&self, secret_patterns: &[String]) -> bool {
+        let field = self
+            .path
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        secret_patterns
+            .iter()
+            .any(|pattern| field.contains(&pattern.to_lowercase()))
+    }
+
+    /// Returns whether the field's value is secret, so that it must not be
+    /// shown in error messages.
+    fn is_value_secret(&self) -> bool {
+        match self.source {
+            FieldSource::Prompt { secret_patterns } => self.is_secret(secret_patterns),
+            FieldSource::Answers(answers) => {
+                matches!(answers.get(&self.path), Some(PromptAnswer::Secret(_)))
+            }
+        }
+    }
+
+    /// Returns the field path in sentence case as a prompt, e.g.
+    /// `"Api key: "`.
+    fn prompt(&self, secret: bool, optional: bool) -> String {
+        let label = if self.path.is_empty() {
+            String::from("value")
+        } else {
+            self.path.replace(['_', '.'], " ")
+        };
+        let mut chars = label.trim().chars();
+        let mut prompt = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default();
+
+        match (secret, optional) {
+            (true, true) => prompt.push_str(" (optional, input is hidden)"),
+            (true, false) => prompt.push_str(" (input is hidden)"),
+            (false, true) => prompt.push_str(" (optional)"),
+            (false, false) => {}
+        }
+        prompt.push_str(": ");
+        prompt
+    }
+
+    /// Returns the value already read, or reads it from the source.
+    ///
+    /// Optional values that are not given are returned as an empty string.
+    fn value(&mut self, optional: bool) -> Result<String, FieldError> {
+        if let Some(value) = self.value.take() {
+            return Ok(value);
+        }
+
+        match self.source {
+            FieldSource::Prompt { secret_patterns } => {
+                let secret = self.is_secret(secret_patterns);
+                self.value_prompt(secret, optional)
+            }
+            FieldSource::Answers(answers) => match answers.get(&self.path) {
+                Some(PromptAnswer::Text(value)) | Some(PromptAnswer::Secret(value)) => {
+                    Ok(value.clone())
+                }
+                Some(PromptAnswer::YesNo(value)) => Ok(value.to_string()),
+                None if optional => Ok(String::new()),
+                None => Err(FieldError::Message(String::from("No answer was given."))),
+            },
+        }
+    }
+
+    /// Prompts for the value on the terminal.
+    fn value_prompt(&self, secret: bool, optional: bool) -> Result<String, FieldError> {
+        let prompt = self.prompt(secret, optional);
+        let mut stderr = io::stderr();
+        stderr.write_all(prompt.as_bytes()).map_err(|error| {
+            let prompt = prompt.clone();
+            Error::PromptWrite { prompt, error }
+        })?;
+        stderr.flush().map_err(Error::StdErrFlush)?;
+
+        let value = if secret {
+            rpassword::read_password_from_tty(None).map_err(Error::SecretRead)?
+        } else {
+            let mut value = String::new();
+            io::stdin()
+                .read_line(&mut value)
+                .map_err(Error::PlainTextRead)?;
+            value.trim().to_string()
+        };
+
+        Ok(value)
+    }
+
+    /// Returns the value parsed as `T`.
+    fn parse<T>(&mut self) -> Result<T, FieldError>
+    where
+        T: std::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        self.value(false)?
+            .parse::<T>()
+            .map_err(|error| FieldError::Message(error.to_string()))
+    }
+}
+
+/// Generates `deserialize_*` methods that parse the entered value.
+macro_rules! deserialize_parsed {
+    ($($deserialize:ident => $visit:ident,)*) => {
+        $(
+            fn $deserialize<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de, 'r> de::Deserializer<'de> for FieldDeserializer<'r> {
+    type Error = FieldError;
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.value(false)?)
+    }
+
+    fn deserialize_bool<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.value(false)?.to_lowercase();
+        match value.as_str() {
+            "true" | "yes" | "y" => visitor.visit_bool(true),
+            "false" | "no" | "n" => visitor.visit_bool(false),
+            _ if self.is_value_secret() => {
+                Err(FieldError::Message(String::from("Expected `yes` or `no`.")))
+            }
+            _ => Err(FieldError::Message(format!(
+                "Expected `yes` or `no`, but got `{value}`."
+            ))),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.value(false)?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.value(false)?.into_bytes())
+    }
+
+    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.value(true)?;
+        if value.is_empty() {
+            visitor.visit_none()
+        } else {
+            self.value = Some(value);
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // `Password` holds its encoded form, so the entered plain text is
+        // encoded before it is deserialized.
+        if name == Password::SERDE_NAME {
+            let password = Password::new(self.value(false)?);
+            self.value = Some(password.encoded().to_string());
+        }
+
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(FieldError::Message(String::from(
+            "Lists cannot be entered on the command line.",
+        )))
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(FieldError::Message(String::from(
+            "Maps cannot be entered on the command line.",
+        )))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FieldMapAccess {
+            deserializer: self,
+            fields: fields.iter(),
+            field: None,
+        })
+    }
+
+    fn deserialize_enum<V>(
+        mut self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let secret = self.is_value_secret();
+        visitor
+            .visit_enum(self.value(false)?.into_deserializer())
+            .map_err(|error| {
+                if secret {
+                    // The error names the entered variant.
+                    let variants = variants
+                        .iter()
+                        .map(|variant| format!("`{variant}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    FieldError::Message(format!("Expected one of {variants}."))
+                } else {
+                    error
+                }
+            })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+/// Visits each field of a struct, prompting for its value.
+struct FieldMapAccess<'r> {
+    /// Deserializer for the struct.
+    deserializer: FieldDeserializer<'r>,
+    /// Remaining fields of the struct.
+    fields: std::slice::Iter<'static, &'static str>,
+    /// Field whose value is next.
+    field: Option<&'static str>,
+}
+
+impl<'de, 'r> MapAccess<'de> for FieldMapAccess<'r> {
+    type Error = FieldError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some(field) => {
+                self.field = Some(field);
+                seed.deserialize(StrDeserializer::<FieldError>::new(field))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let field = self.field.take().unwrap_or_default();
+        let deserializer = self.deserializer.field(field);
+        let path = deserializer.path.clone();
+
+        // Errors from parsing the value are attributed to the field.
+        seed.deserialize(deserializer).map_err(|error| match error {
+            FieldError::Message(message) => FieldError::Cli(Error::ValueInvalid {
+                field: path,
+                message,
+            }),
+            error @ FieldError::Cli(_) => error,
+        })
+    }
+}
+
+/// Error while prompting for and deserializing a value.
+#[derive(Debug)]
+pub(crate) enum FieldError {
+    /// Failed to prompt for or read the value.
+    Cli(Error),
+    /// Entered value could not be deserialized.
+    Message(String),
+}
+
+impl From<FieldError> for Error {
+    fn from(error: FieldError) -> Self {
+        match error {
+            FieldError::Cli(error) => error,
+            FieldError::Message(message) => Error::ValueInvalid {
+                field: String::from("value"),
+                message,
+            },
+        }
+    }
+}
+
+impl From<Error> for FieldError {
+    fn from(error: Error) -> Self {
+        Self::Cli(error)
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Cli(error) => write!(f, "{error}"),
+            Self::Message(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+impl de::Error for FieldError {
+    fn custom<T>(message: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::Message(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use credent_cli_model::Error;
+    use credent_model::{Credentials, Password, Username};
+    use serde::Deserialize;
+
+    use super::{FieldDeserializer, FieldSource};
+    use crate::{PromptAnswer, PromptAnswers};
+
+    fn answers(answers: &[(&str, PromptAnswer)]) -> PromptAnswers {
+        let mut prompt_answers = PromptAnswers::new();
+        answers.iter().for_each(|(key, answer)| {
+            prompt_answers.insert(key.to_string(), answer.clone());
+        });
+        prompt_answers
+    }
+
+    fn value_invalid_message<T>(answers: &PromptAnswers) -> String
+    where
+        T: std::fmt::Debug + for<'de> Deserialize<'de>,
+    {
+        match answers.typed::<T>() {
+            Err(Error::ValueInvalid { message, .. }) => message,
+            typed_result => panic!(
+                "Expected `typed_result` to return `ValueInvalid` error, but got `{:?}`.",
+                typed_result
+            ),
+        }
+    }
+
+    #[test]
+    fn deserialize_encodes_password() -> Result<(), Error> {
+        let answers = answers(&[
+            ("username", PromptAnswer::Text(String::from("me"))),
+            ("password", PromptAnswer::Secret(String::from("secret"))),
+        ]);
+
+        let credentials = answers.typed::<Credentials>()?;
+
+        assert_eq!(Username(String::from("me")), credentials.username);
+        assert_eq!(Password::new("secret"), credentials.password);
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_does_not_encode_other_newtypes() -> Result<(), Error> {
+        /// Newtype with the same name as a password type.
+        #[derive(Debug, Deserialize)]
+        struct Base64Encoded(String);
+        #[derive(Debug, Deserialize)]
+        struct ApiCredentials {
+            token: Base64Encoded,
+        }
+        let answers = answers(&[("token", PromptAnswer::Secret(String::from("secret")))]);
+
+        let api_credentials = answers.typed::<ApiCredentials>()?;
+
+        assert_eq!("secret", api_credentials.token.0);
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_bool_masks_secret_value() {
+        #[derive(Debug, Deserialize)]
+        struct Flags {
+            #[allow(dead_code)]
+            enabled: bool,
+        }
+
+        let answers_text = answers(&[("enabled", PromptAnswer::Text(String::from("maybe")))]);
+        assert_eq!(
+            "Expected `yes` or `no`, but got `maybe`.",
+            value_invalid_message::<Flags>(&answers_text)
+        );
+
+        let answers_secret = answers(&[("enabled", PromptAnswer::Secret(String::from("maybe")))]);
+        assert_eq!(
+            "Expected `yes` or `no`.",
+            value_invalid_message::<Flags>(&answers_secret)
+        );
+    }
+
+    #[test]
+    fn deserialize_enum_masks_secret_value() {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Region {
+            UsEast,
+            EuWest,
+        }
+        #[derive(Debug, Deserialize)]
+        struct Endpoint {
+            #[allow(dead_code)]
+            region: Region,
+        }
+
+        let answers_text = answers(&[("region", PromptAnswer::Text(String::from("mars")))]);
+        assert!(value_invalid_message::<Endpoint>(&answers_text).contains("mars"));
+
+        let answers_secret = answers(&[("region", PromptAnswer::Secret(String::from("mars")))]);
+        assert_eq!(
+            "Expected one of `us_east`, `eu_west`.",
+            value_invalid_message::<Endpoint>(&answers_secret)
+        );
+    }
+
+    #[test]
+    fn is_secret_matches_last_path_segment_ignoring_case() {
+        let secret_patterns = [String::from("key")];
+        let source = FieldSource::Prompt {
+            secret_patterns: &secret_patterns,
+        };
+        let deserializer = FieldDeserializer::new(source);
+
+        assert!(deserializer.field("API_KEY").is_secret(&secret_patterns));
+        assert!(!deserializer.field("account").is_secret(&secret_patterns));
+        assert!(!deserializer
+            .field("key_store")
+            .field("path")
+            .is_secret(&secret_patterns));
+    }
+
+    #[test]
+    fn prompt_is_field_path_in_sentence_case() {
+        let deserializer = FieldDeserializer::new(FieldSource::Prompt {
+            secret_patterns: &[],
+        });
+
+        assert_eq!(
+            "Api key (input is hidden): ",
+            deserializer.field("api_key").prompt(true, false)
+        );
+        assert_eq!(
+            "Endpoint port (optional): ",
+            deserializer
+                .field("endpoint")
+                .field("port")
+                .prompt(false, true)
+        );
+        assert_eq!("Value: ", deserializer.prompt(false, false));
+    }
+}
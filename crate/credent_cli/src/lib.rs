@@ -2,8 +2,31 @@
 
 //! Reads in credentials from the CLI.
 
-pub use crate::credentials_cli_reader::CredentialsCliReader;
+pub use crate::{
+    credential::Credential, credentials_cli_reader::CredentialsCliReader,
+    profile_picker::ProfilePicker, profile_selection::ProfileSelection,
+    prompt_answer::PromptAnswer, prompt_answers::PromptAnswers, prompt_kind::PromptKind,
+    prompt_set::PromptSet, prompt_step::PromptStep,
+};
+
+#[cfg(feature = "serde")]
+pub use crate::credentials_field_reader::CredentialsFieldReader;
+
+#[cfg(feature = "derive")]
+pub use credent_derive::Credential;
 
 pub use credent_cli_model as model;
 
+mod credential;
 mod credentials_cli_reader;
+#[cfg(feature = "serde")]
+mod credentials_field_reader;
+#[cfg(feature = "serde")]
+mod field_deserializer;
+mod profile_picker;
+mod profile_selection;
+mod prompt_answer;
+mod prompt_answers;
+mod prompt_kind;
+mod prompt_set;
+mod prompt_step;
@@ -0,0 +1,330 @@
+use std::fmt::Display;
+
+use credent_cli_model::Error;
+use credent_model::{Profile, Profiles};
+
+use crate::{CredentialsCliReader, ProfileSelection};
+
+#[cfg(feature = "raw-mode")]
+mod raw_mode_picker;
+
+/// Reader used for the picker's prompts, which are passed in per call.
+type Reader = CredentialsCliReader<&'static str, &'static str>;
+
+/// Lets the user choose a profile on the command line.
+///
+/// With the `"raw-mode"` feature, profiles are chosen with the arrow keys, and
+/// typing filters the profiles by name. When raw mode is not available, such
+/// as when input is piped, profiles are listed and chosen by number or name.
+///
+/// Each profile is shown with its credentials' `Display` output, which masks
+/// passwords. Profiles listed by `CredentialsFileLoader::load_all` have an
+/// empty password when it is read from a secret reference, so the chosen
+/// profile is loaded by name:
+///
+/// ```rust,ignore
+/// let profiles = CredentialsFileLoader::load_all(CREDENT)
+///     .await?
+///     .unwrap_or_default();
+/// match ProfilePicker::new(&profiles).pick().await? {
+///     ProfileSelection::Existing(profile) => {
+///         Credent::new(CREDENT)
+///             .with_profile_name(profile.name.clone())
+///             .get_or_prompt()
+///             .await?
+///     }
+///     ProfileSelection::New(profile_name) => prompt_and_store(profile_name).await?,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProfilePicker<'p, C>
+where
+    C: Clone + Eq,
+{
+    /// Profiles to choose from.
+    profiles: &'p Profiles<C>,
+    /// Whether "Create new profile" is offered.
+    create: bool,
+}
+
+/// Profile shown in the picker.
+#[derive(Clone, Debug)]
+pub(crate) struct PickerEntry {
+    /// Name of the profile.
+    pub(crate) name: String,
+    /// Masked credentials of the profile.
+    pub(crate) summary: String,
+}
+
+/// Option chosen in the picker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PickerChoice {
+    /// Index of the chosen profile.
+    Profile(usize),
+    /// "Create new profile" was chosen.
+    ///
+    /// Holds the filter text when it matched no profile, which is offered as
+    /// the new profile's name.
+    Create(Option<String>),
+}
+
+impl<'p, C> ProfilePicker<'p, C>
+where
+    C: Clone + Display + Eq,
+{
+    /// Text shown for the option to create a new profile.
+    pub(crate) const CREATE_LABEL: &'static str = "Create new profile";
+
+    /// Returns a new `ProfilePicker` for the given profiles.
+    ///
+    /// # Parameters
+    ///
+    /// * `profiles`: Profiles to choose from, e.g. from
+    ///   `CredentialsFileLoader::load_all`.
+    pub fn new(profiles: &'p Profiles<C>) -> Self {
+        Self {
+            profiles,
+            create: true,
+        }
+    }
+
+    /// Sets whether "Create new profile" is offered.
+    ///
+    /// Defaults to `true`.
+    pub fn with_create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// Lets the user choose a profile, or enter the name of a new profile.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn pick(&self) -> Result<ProfileSelection<'p, C>, Error> {
+        let profiles = self.profiles.iter().collect::<Vec<&'p Profile<C>>>();
+        let entries = profiles
+            .iter()
+            .map(|profile| PickerEntry {
+                name: profile.name.clone(),
+                summary: profile.credentials.to_string(),
+            })
+            .collect::<Vec<PickerEntry>>();
+
+        let choice = if entries.is_empty() && self.create {
+            PickerChoice::Create(None)
+        } else if entries.is_empty() {
+            return Err(Error::ValueInvalid {
+                field: String::from("profile"),
+                message: String::from("No profiles exist."),
+            });
+        } else {
+            Self::choose(entries, self.create).await?
+        };
+
+        match choice {
+            PickerChoice::Profile(index) => profiles
+                .get(index)
+                .copied()
+                .map(ProfileSelection::Existing)
+                .ok_or_else(|| Self::choice_invalid(index.to_string())),
+            PickerChoice::Create(profile_name) => self
+                .profile_name_new(profile_name)
+                .await
+                .map(ProfileSelection::New),
+        }
+    }
+
+    /// Lets the user choose an entry, in raw mode if available.
+    async fn choose(entries: Vec<PickerEntry>, create: bool) -> Result<PickerChoice, Error> {
+        #[cfg(feature = "raw-mode")]
+        {
+            let entries = entries.clone();
+            let choose = move || raw_mode_picker::choose(&entries, create);
+
+            #[cfg(feature = "smol")]
+            let choice = smol::unblock(choose).await?;
+            #[cfg(feature = "tokio")]
+            let choice = tokio::task::spawn_blocking(choose)
+                .await
+                .map_err(Error::StdinReadJoin)??;
+
+            if let Some(choice) = choice {
+                return Ok(choice);
+            }
+        }
+
+        Self::choose_numbered(&entries, create).await
+    }
+
+    /// Lists the entries, and reads the number or name of the chosen one.
+    async fn choose_numbered(entries: &[PickerEntry], create: bool) -> Result<PickerChoice, Error> {
+        let name_width = Self::name_width(entries);
+        let mut prompt = String::from("Profiles:\n");
+        entries.iter().enumerate().for_each(|(index, entry)| {
+            prompt.push_str(&format!(
+                "  {number}) {name:<name_width$}  {summary}\n",
+                number = index + 1,
+                name = entry.name,
+                summary = entry.summary,
+            ));
+        });
+        let option_count = if create {
+            prompt.push_str(&format!(
+                "  {number}) {label}\n",
+                number = entries.len() + 1,
+                label = Self::CREATE_LABEL
+            ));
+            entries.len() + 1
+        } else {
+            entries.len()
+        };
+        prompt.push_str(&format!("Choose [1-{option_count}]: "));
+
+        let value = Reader::plain_text_read(&prompt).await?;
+        Self::choice_numbered_resolve(entries, create, value)
+    }
+
+    /// Returns the entry chosen by its number or name.
+    fn choice_numbered_resolve(
+        entries: &[PickerEntry],
+        create: bool,
+        value: String,
+    ) -> Result<PickerChoice, Error> {
+        let choice_by_number = value
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| {
+                if index < entries.len() {
+                    Some(PickerChoice::Profile(index))
+                } else if create && index == entries.len() {
+                    Some(PickerChoice::Create(None))
+                } else {
+                    None
+                }
+            });
+        let choice_by_name = || {
+            entries
+                .iter()
+                .position(|entry| entry.name == value)
+                .map(PickerChoice::Profile)
+        };
+
+        choice_by_number
+            .or_else(choice_by_name)
+            .ok_or_else(|| Self::choice_invalid(value))
+    }
+
+    /// Reads the name of the new profile.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name_default`: Name used when nothing is entered.
+    async fn profile_name_new(
+        &self,
+        profile_name_default: Option<String>,
+    ) -> Result<String, Error> {
+        let prompt = match profile_name_default.as_deref() {
+            Some(profile_name) => format!("New profile name [{profile_name}]: "),
+            None => String::from("New profile name: "),
+        };
+        let profile_name = match (
+            Reader::plain_text_read(&prompt).await?,
+            profile_name_default,
+        ) {
+            (profile_name, Some(profile_name_default)) if profile_name.is_empty() => {
+                profile_name_default
+            }
+            (profile_name, _) => profile_name,
+        };
+        if profile_name.is_empty() {
+            Err(Error::ValueInvalid {
+                field: String::from("profile"),
+                message: String::from("Profile name must not be empty."),
+            })
+        } else if self.profiles.contains(profile_name.as_str()) {
+            Err(Error::ValueInvalid {
+                field: String::from("profile"),
+                message: format!("Profile `{profile_name}` already exists."),
+            })
+        } else {
+            Ok(profile_name)
+        }
+    }
+
+    /// Returns the width of the longest profile name, for alignment.
+    pub(crate) fn name_width(entries: &[PickerEntry]) -> usize {
+        entries
+            .iter()
+            .map(|entry| entry.name.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the error for a choice that matches no option.
+    fn choice_invalid(value: String) -> Error {
+        Error::ValueInvalid {
+            field: String::from("profile"),
+            message: format!("No profile matches `{value}`."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use credent_cli_model::Error;
+    use credent_model::Credentials;
+
+    use super::{PickerChoice, PickerEntry, ProfilePicker};
+
+    type Picker<'p> = ProfilePicker<'p, Credentials>;
+
+    fn entries() -> Vec<PickerEntry> {
+        ["default", "staging"]
+            .iter()
+            .map(|name| PickerEntry {
+                name: String::from(*name),
+                summary: String::from("user / ********"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn choice_numbered_resolve_reads_number_or_name() -> Result<(), Error> {
+        let entries = entries();
+
+        assert_eq!(
+            PickerChoice::Profile(1),
+            Picker::choice_numbered_resolve(&entries, true, String::from("2"))?
+        );
+        assert_eq!(
+            PickerChoice::Create(None),
+            Picker::choice_numbered_resolve(&entries, true, String::from("3"))?
+        );
+        assert_eq!(
+            PickerChoice::Profile(0),
+            Picker::choice_numbered_resolve(&entries, false, String::from("default"))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn choice_numbered_resolve_returns_err_for_unknown_option() {
+        let entries = entries();
+
+        ["0", "3", "Default", ""].iter().for_each(|value| {
+            let choice_result =
+                Picker::choice_numbered_resolve(&entries, false, String::from(*value));
+            assert!(
+                matches!(
+                    &choice_result,
+                    Err(Error::ValueInvalid { field, .. }) if field == "profile"
+                ),
+                "Expected `choice_result` to return `ValueInvalid` error for `{}`, but got `{:?}`.",
+                value,
+                choice_result
+            );
+        });
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use credent_cli_model::Error;
+use credent_model::Credentials;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    queue,
+    style::Print,
+    terminal::{self, ClearType},
+    tty::IsTty,
+};
+
+use crate::{
+    profile_picker::{PickerChoice, PickerEntry},
+    ProfilePicker,
+};
+
+/// Disables raw mode when dropped, so the terminal is restored on every exit
+/// path.
+struct RawModeGuard;
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lets the user choose an entry with the arrow keys, filtering by typed text.
+///
+/// Returns `None` when raw mode is not available.
+pub(crate) fn choose(entries: &[PickerEntry], create: bool) -> Result<Option<PickerChoice>, Error> {
+    if !io::stdin().is_tty() || !io::stderr().is_tty() || terminal::enable_raw_mode().is_err() {
+        return Ok(None);
+    }
+    let _raw_mode_guard = RawModeGuard;
+
+    let mut stderr = io::stderr();
+    let mut filter = String::new();
+    let mut selected = 0;
+    let mut lines_drawn = 0;
+    loop {
+        let choices = choices_filtered(entries, &filter, create);
+        selected = selected.min(choices.len().saturating_sub(1));
+        lines_drawn = render(
+            &mut stderr,
+            entries,
+            &choices,
+            &filter,
+            selected,
+            lines_drawn,
+        )?;
+
+        let key_event = match event::read().map_err(io::Error::other) {
+            Ok(Event::Key(key_event)) => key_event,
+            Ok(_) => continue,
+            Err(error) => return Err(Error::PickerTerminal(error)),
+        };
+        let KeyEvent { code, modifiers } = key_event;
+        match code {
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down if selected + 1 < choices.len() => selected += 1,
+            KeyCode::Enter => {
+                if let Some(choice) = choices.get(selected) {
+                    clear(&mut stderr, lines_drawn)?;
+                    return Ok(Some(choice.clone()));
+                }
+            }
+            KeyCode::Esc => {
+                clear(&mut stderr, lines_drawn)?;
+                return Err(Error::PromptCancelled);
+            }
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                clear(&mut stderr, lines_drawn)?;
+                return Err(Error::PromptCancelled);
+            }
+            KeyCode::Char(c) => {
+                filter.push(c);
+                selected = 0;
+            }
+            KeyCode::Backspace => {
+                filter.pop();
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the entries whose names contain the filter, ignoring case, then
+/// "Create new profile" if offered.
+///
+/// When the filter matches no profile, "Create new profile" holds the filter
+/// text as the new profile's name.
+fn choices_filtered(entries: &[PickerEntry], filter: &str, create: bool) -> Vec<PickerChoice> {
+    let filter_lower = filter.to_lowercase();
+    let mut choices = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.name.to_lowercase().contains(&filter_lower))
+        .map(|(index, _)| PickerChoice::Profile(index))
+        .collect::<Vec<PickerChoice>>();
+    if create {
+        let profile_name = filter.trim();
+        let profile_name = if choices.is_empty() && !profile_name.is_empty() {
+            Some(profile_name.to_string())
+        } else {
+            None
+        };
+        choices.push(PickerChoice::Create(profile_name));
+    }
+    choices
+}
+
+/// Draws the picker over the previously drawn lines, returning the number of
+/// lines drawn.
+fn render(
+    stderr: &mut io::Stderr,
+    entries: &[PickerEntry],
+    choices: &[PickerChoice],
+    filter: &str,
+    selected: usize,
+    lines_drawn: u16,
+) -> Result<u16, Error> {
+    clear(stderr, lines_drawn)?;
+
+    // Without a known size, lines are drawn in full.
+    let terminal_size = terminal::size().unwrap_or((u16::MAX, u16::MAX));
+    let lines = lines_fitted(entries, choices, filter, selected, terminal_size);
+
+    lines
+        .iter()
+        .try_for_each(|line| queue!(stderr, Print(line), Print("\r\n")))
+        .and_then(|()| stderr.flush().map_err(Into::into))
+        .map_err(|error| Error::PickerTerminal(io::Error::other(error)))?;
+
+    Ok(u16::try_from(lines.len()).unwrap_or(u16::MAX))
+}
+
+/// Returns the picker's lines, fitted to the terminal's `(columns, rows)`.
+///
+/// Lines are cut to the terminal's width so that none wrap, and only as many
+/// choices as fit the terminal's height are shown, scrolled to the selected
+/// one. Otherwise the lines drawn would not match the lines cleared.
+fn lines_fitted(
+    entries: &[PickerEntry],
+    choices: &[PickerChoice],
+    filter: &str,
+    selected: usize,
+    (columns, rows): (u16, u16),
+) -> Vec<String> {
+    // Header lines, and the row the cursor is left on.
+    const ROWS_RESERVED: usize = 3;
+
+    let choices_shown = usize::from(rows)
+        .saturating_sub(ROWS_RESERVED)
+        .max(1)
+        .min(choices.len());
+    let choices_skipped = (selected + 1).saturating_sub(choices_shown);
+    let position = if choices_shown < choices.len() {
+        format!("  ({}/{})", selected + 1, choices.len())
+    } else {
+        String::new()
+    };
+
+    let name_width = ProfilePicker::<Credentials>::name_width(entries);
+    let mut lines = vec![
+        String::from(
+            "Choose a profile (type to filter, ↑/↓ to move, enter to choose, esc to cancel):",
+        ),
+        format!("Filter: {filter}{position}"),
+    ];
+    choices
+        .iter()
+        .enumerate()
+        .skip(choices_skipped)
+        .take(choices_shown)
+        .for_each(|(index, choice)| {
+            let marker = if index == selected { ">" } else { " " };
+            let line = match choice {
+                PickerChoice::Profile(entry_index) => {
+                    let entry = &entries[*entry_index];
+                    format!(
+                        "{marker} {name:<name_width$}  {summary}",
+                        name = entry.name,
+                        summary = entry.summary
+                    )
+                }
+                PickerChoice::Create(None) => format!(
+                    "{marker} + {label}",
+                    label = ProfilePicker::<Credentials>::CREATE_LABEL
+                ),
+                PickerChoice::Create(Some(profile_name)) => format!(
+                    "{marker} + {label} `{profile_name}`",
+                    label = ProfilePicker::<Credentials>::CREATE_LABEL
+                ),
+            };
+            lines.push(line);
+        });
+
+    lines
+        .into_iter()
+        .map(|line| line_truncate(line, usize::from(columns)))
+        .collect()
+}
+
+/// Cuts the line to the given number of characters, ending it with `…` when
+/// cut.
+fn line_truncate(line: String, columns: usize) -> String {
+    if line.chars().count() <= columns {
+        line
+    } else {
+        line.chars()
+            .take(columns.saturating_sub(1))
+            .chain(std::iter::once('…'))
+            .collect()
+    }
+}
+
+/// Clears the previously drawn lines, leaving the cursor where they started.
+fn clear(stderr: &mut io::Stderr, lines_drawn: u16) -> Result<(), Error> {
+    let result = if lines_drawn > 0 {
+        queue!(
+            stderr,
+            cursor::MoveUp(lines_drawn),
+            Print("\r"),
+            terminal::Clear(ClearType::FromCursorDown)
+        )
+    } else {
+        Ok(())
+    };
+
+    result
+        .and_then(|()| stderr.flush().map_err(Into::into))
+        .map_err(|error| Error::PickerTerminal(io::Error::other(error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choices_filtered, line_truncate, lines_fitted};
+    use crate::profile_picker::{PickerChoice, PickerEntry};
+
+    fn entries() -> Vec<PickerEntry> {
+        ["default", "Staging", "staging-eu"]
+            .iter()
+            .map(|name| PickerEntry {
+                name: String::from(*name),
+                summary: String::from("user / ********"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn choices_filtered_matches_names_ignoring_case() {
+        assert_eq!(
+            vec![
+                PickerChoice::Profile(1),
+                PickerChoice::Profile(2),
+                PickerChoice::Create(None),
+            ],
+            choices_filtered(&entries(), "STAG", true)
+        );
+        assert_eq!(
+            vec![PickerChoice::Profile(1), PickerChoice::Profile(2)],
+            choices_filtered(&entries(), "stag", false)
+        );
+        assert_eq!(
+            vec![
+                PickerChoice::Profile(0),
+                PickerChoice::Profile(1),
+                PickerChoice::Profile(2),
+                PickerChoice::Create(None),
+            ],
+            choices_filtered(&entries(), "", true)
+        );
+    }
+
+    #[test]
+    fn choices_filtered_offers_unmatched_filter_as_profile_name() {
+        assert_eq!(
+            vec![PickerChoice::Create(Some(String::from("prod")))],
+            choices_filtered(&entries(), " prod ", true)
+        );
+        assert!(choices_filtered(&entries(), "prod", false).is_empty());
+    }
+
+    #[test]
+    fn lines_fitted_scrolls_to_selected_choice_in_short_terminal() {
+        let choices = choices_filtered(&entries(), "", true);
+
+        let lines = lines_fitted(&entries(), &choices, "", 3, (80, 5));
+
+        assert_eq!(4, lines.len());
+        assert_eq!("Filter:   (4/4)", lines[1]);
+        assert!(lines[2].starts_with("  staging-eu"));
+        assert!(lines[3].starts_with("> + Create new profile"));
+    }
+
+    #[test]
+    fn lines_fitted_cuts_lines_to_terminal_width() {
+        let choices = choices_filtered(&entries(), "", true);
+
+        let lines = lines_fitted(&entries(), &choices, "", 0, (20, 24));
+
+        assert_eq!(6, lines.len());
+        assert!(lines.iter().all(|line| line.chars().count() <= 20));
+        assert_eq!("> default     user …", lines[2]);
+    }
+
+    #[test]
+    fn line_truncate_keeps_short_lines() {
+        assert_eq!("short", line_truncate(String::from("short"), 5));
+        assert_eq!("shor…", line_truncate(String::from("shorter"), 5));
+    }
+}
@@ -0,0 +1,12 @@
+use credent_model::Profile;
+
+/// Profile chosen with a [`ProfilePicker`].
+///
+/// [`ProfilePicker`]: crate::ProfilePicker
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProfileSelection<'p, C> {
+    /// An existing profile was chosen.
+    Existing(&'p Profile<C>),
+    /// A new profile should be created with this name.
+    New(String),
+}
@@ -0,0 +1,44 @@
+use std::fmt::{self, Debug};
+
+/// Answer to a [`PromptStep`].
+///
+/// The `Debug` implementation masks secret answers.
+///
+/// [`PromptStep`]: crate::PromptStep
+#[derive(Clone, PartialEq, Eq)]
+pub enum PromptAnswer {
+    /// Answer to a plain, choice, or multi-line step.
+    Text(String),
+    /// Answer to a secret step.
+    Secret(String),
+    /// Answer to a yes / no step.
+    YesNo(bool),
+}
+
+impl PromptAnswer {
+    /// Returns the text of a text or secret answer.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Text(value) | Self::Secret(value) => Some(value),
+            Self::YesNo(_) => None,
+        }
+    }
+
+    /// Returns the value of a yes / no answer.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::YesNo(value) => Some(*value),
+            Self::Text(_) | Self::Secret(_) => None,
+        }
+    }
+}
+
+impl Debug for PromptAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Text(value) => f.debug_tuple("Text").field(value).finish(),
+            Self::Secret(_) => f.debug_tuple("Secret").field(&"******").finish(),
+            Self::YesNo(value) => f.debug_tuple("YesNo").field(value).finish(),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeMap,
+    ops::{Deref, DerefMut},
+};
+
+use crate::PromptAnswer;
+
+/// Answers from a [`PromptSet`], keyed by step key.
+///
+/// Steps that were skipped by their condition have no answer.
+///
+/// [`PromptSet`]: crate::PromptSet
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PromptAnswers(pub BTreeMap<String, PromptAnswer>);
+
+impl PromptAnswers {
+    /// Returns a new empty `PromptAnswers`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the text of the answer for the step, if it is a text or secret
+    /// answer.
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(PromptAnswer::as_str)
+    }
+
+    /// Returns the value of the answer for the step, if it is a yes / no
+    /// answer.
+    pub fn yes_no(&self, key: &str) -> Option<bool> {
+        self.0.get(key).and_then(PromptAnswer::as_bool)
+    }
+
+    /// Returns the answers as a typed value.
+    ///
+    /// Each field is read from the answer whose key is the field name, or the
+    /// dotted path for nested structs, e.g. `"endpoint.port"`. Numbers are
+    /// parsed from text answers, and `Option`al fields are `None` when their
+    /// step was skipped.
+    #[cfg(feature = "serde")]
+    pub fn typed<T>(&self) -> Result<T, credent_cli_model::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        use crate::field_deserializer::{FieldDeserializer, FieldSource};
+
+        T::deserialize(FieldDeserializer::new(FieldSource::Answers(self)))
+            .map_err(credent_cli_model::Error::from)
+    }
+}
+
+impl Deref for PromptAnswers {
+    type Target = BTreeMap<String, PromptAnswer>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for PromptAnswers {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use credent_cli_model::Error;
+    use serde::Deserialize;
+
+    use super::PromptAnswers;
+    use crate::PromptAnswer;
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Onboarding {
+        username: String,
+        mfa: bool,
+        mfa_device: Option<String>,
+        endpoint: Endpoint,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Endpoint {
+        host: String,
+        port: u16,
+    }
+
+    fn answers(mfa_device: Option<&str>) -> PromptAnswers {
+        let mut answers = PromptAnswers::new();
+        answers.insert(
+            String::from("username"),
+            PromptAnswer::Text(String::from("me")),
+        );
+        answers.insert(
+            String::from("mfa"),
+            PromptAnswer::YesNo(mfa_device.is_some()),
+        );
+        if let Some(mfa_device) = mfa_device {
+            answers.insert(
+                String::from("mfa_device"),
+                PromptAnswer::Text(String::from(mfa_device)),
+            );
+        }
+        answers.insert(
+            String::from("endpoint.host"),
+            PromptAnswer::Text(String::from("example.com")),
+        );
+        answers.insert(
+            String::from("endpoint.port"),
+            PromptAnswer::Text(String::from("443")),
+        );
+        answers
+    }
+
+    #[test]
+    fn typed_reads_nested_and_optional_fields() -> Result<(), Error> {
+        let endpoint = || Endpoint {
+            host: String::from("example.com"),
+            port: 443,
+        };
+
+        assert_eq!(
+            Onboarding {
+                username: String::from("me"),
+                mfa: true,
+                mfa_device: Some(String::from("phone")),
+                endpoint: endpoint(),
+            },
+            answers(Some("phone")).typed::<Onboarding>()?
+        );
+        assert_eq!(
+            Onboarding {
+                username: String::from("me"),
+                mfa: false,
+                mfa_device: None,
+                endpoint: endpoint(),
+            },
+            answers(None).typed::<Onboarding>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn typed_returns_err_for_missing_or_unparsable_answer() {
+        let mut answers = answers(None);
+        answers.insert(
+            String::from("endpoint.port"),
+            PromptAnswer::Text(String::from("https")),
+        );
+        let typed_result = answers.typed::<Onboarding>();
+        assert!(
+            matches!(
+                &typed_result,
+                Err(Error::ValueInvalid { field, .. }) if field == "endpoint.port"
+            ),
+            "Expected `typed_result` to return `ValueInvalid` error for `endpoint.port`, but got `{:?}`.",
+            typed_result
+        );
+
+        answers.remove("username");
+        let typed_result = answers.typed::<Onboarding>();
+        assert!(
+            matches!(
+                &typed_result,
+                Err(Error::ValueInvalid { field, .. }) if field == "username"
+            ),
+            "Expected `typed_result` to return `ValueInvalid` error for `username`, but got `{:?}`.",
+            typed_result
+        );
+    }
+}
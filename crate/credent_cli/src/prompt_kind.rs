@@ -0,0 +1,16 @@
+/// How a [`PromptStep`] reads its answer.
+///
+/// [`PromptStep`]: crate::PromptStep
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Single line of text.
+    Plain,
+    /// Single line of text, read with hidden input.
+    Secret,
+    /// One of the given options, entered by name or by its number.
+    Choice(Vec<String>),
+    /// `yes` or `no`.
+    YesNo,
+    /// Lines of text, ended by an empty line.
+    MultiLine,
+}
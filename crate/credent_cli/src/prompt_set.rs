@@ -0,0 +1,230 @@
+use credent_cli_model::Error;
+
+use crate::{CredentialsCliReader, PromptAnswer, PromptAnswers, PromptKind, PromptStep};
+
+/// Reader used for the steps' prompts, which are passed in per call.
+type Reader = CredentialsCliReader<&'static str, &'static str>;
+
+/// Ordered set of steps to prompt for on the command line.
+///
+/// Each step is asked in order, unless its condition returns `false` for the
+/// earlier answers:
+///
+/// ```rust,ignore
+/// let answers = PromptSet::new()
+///     .with_step(PromptStep::plain("username", "Username"))
+///     .with_step(PromptStep::secret("password", "Password"))
+///     .with_step(PromptStep::choice("region", "Region", ["us-east-1", "eu-west-1"])?)
+///     .with_step(PromptStep::yes_no("mfa", "Use an MFA device?"))
+///     .with_step(
+///         PromptStep::plain("mfa_device", "MFA device")
+///             .with_condition(|answers| answers.yes_no("mfa") == Some(true)),
+///     )
+///     .run()
+///     .await?;
+/// ```
+#[derive(Debug, Default)]
+pub struct PromptSet {
+    /// Steps to prompt for, in order.
+    steps: Vec<PromptStep>,
+}
+
+impl PromptSet {
+    /// Returns a new empty `PromptSet`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a step after the existing steps.
+    pub fn with_step(mut self, step: PromptStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Returns the steps to prompt for, in order.
+    pub fn steps(&self) -> &[PromptStep] {
+        &self.steps
+    }
+
+    /// Prompts for each step on the terminal, returning the answers keyed by
+    /// step key.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn run(&self) -> Result<PromptAnswers, Error> {
+        let mut answers = PromptAnswers::new();
+        for step in self.steps.iter() {
+            if !step.is_asked(&answers) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(key = step.key(), "Skipped prompt step.");
+
+                continue;
+            }
+
+            let answer = Self::ask(step).await?;
+            answers.insert(step.key().to_string(), answer);
+        }
+
+        Ok(answers)
+    }
+
+    /// Prompts for each step on the terminal, returning the answers as a
+    /// typed value.
+    ///
+    /// See [`PromptAnswers::typed`] for how answers map to fields.
+    #[cfg(feature = "serde")]
+    pub async fn run_typed<T>(&self) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.run().await?.typed()
+    }
+
+    /// Prompts for a single step.
+    async fn ask(step: &PromptStep) -> Result<PromptAnswer, Error> {
+        let label = step.label();
+        match step.kind() {
+            PromptKind::Plain => Reader::plain_text_read(&format!("{label}: "))
+                .await
+                .map(PromptAnswer::Text),
+            PromptKind::Secret => Reader::secret_read(&format!("{label} (input is hidden): "))
+                .await
+                .map(PromptAnswer::Secret),
+            PromptKind::Choice(options) => {
+                let mut prompt = format!("{label}:\n");
+                options.iter().enumerate().for_each(|(index, option)| {
+                    prompt.push_str(&format!("  {}) {option}\n", index + 1));
+                });
+                prompt.push_str(&format!("Choose [1-{}]: ", options.len()));
+
+                // A mistyped answer is asked again.
+                loop {
+                    let value = Reader::plain_text_read(&prompt).await?;
+                    match Self::choice_resolve(step, options, &value) {
+                        Ok(option) => return Ok(PromptAnswer::Text(option)),
+                        Err(error) => Reader::message_write(&error.to_string()).await?,
+                    }
+                }
+            }
+            PromptKind::YesNo => loop {
+                let value = Reader::plain_text_read(&format!("{label} [y/n]: ")).await?;
+                match Self::yes_no_resolve(step, &value) {
+                    Ok(yes) => return Ok(PromptAnswer::YesNo(yes)),
+                    Err(error) => Reader::message_write(&error.to_string()).await?,
+                }
+            },
+            PromptKind::MultiLine => {
+                // Lines are kept as entered, so that indentation is preserved.
+                let mut lines = Vec::new();
+                let mut line =
+                    Reader::line_read(&format!("{label} (end with an empty line):\n")).await?;
+                while !line.is_empty() {
+                    lines.push(line);
+                    line = Reader::line_read("").await?;
+                }
+
+                Ok(PromptAnswer::Text(lines.join("\n")))
+            }
+        }
+    }
+
+    /// Returns whether the answer is `yes` or `no`, ignoring case.
+    fn yes_no_resolve(step: &PromptStep, value: &str) -> Result<bool, Error> {
+        match value.to_lowercase().as_str() {
+            "yes" | "y" | "true" => Ok(true),
+            "no" | "n" | "false" => Ok(false),
+            _ => Err(Error::ValueInvalid {
+                field: step.key().to_string(),
+                message: format!("Expected `yes` or `no`, but got `{value}`."),
+            }),
+        }
+    }
+
+    /// Returns the option chosen by its number or name, ignoring case.
+    fn choice_resolve(step: &PromptStep, options: &[String], value: &str) -> Result<String, Error> {
+        let option_by_number = value
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| options.get(index));
+        let option_by_name = || {
+            options
+                .iter()
+                .find(|option| option.eq_ignore_ascii_case(value))
+        };
+
+        option_by_number
+            .or_else(option_by_name)
+            .cloned()
+            .ok_or_else(|| Error::ValueInvalid {
+                field: step.key().to_string(),
+                message: format!(
+                    "Expected one of {}, but got `{value}`.",
+                    options
+                        .iter()
+                        .map(|option| format!("`{option}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use credent_cli_model::Error;
+
+    use super::PromptSet;
+    use crate::PromptStep;
+
+    fn options() -> Vec<String> {
+        vec![String::from("us-east-1"), String::from("eu-west-1")]
+    }
+
+    #[test]
+    fn choice_resolve_reads_number_or_name_ignoring_case() -> Result<(), Error> {
+        let step = PromptStep::choice("region", "Region", options())?;
+
+        assert_eq!(
+            "eu-west-1",
+            PromptSet::choice_resolve(&step, &options(), "2")?
+        );
+        assert_eq!(
+            "us-east-1",
+            PromptSet::choice_resolve(&step, &options(), "US-EAST-1")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn choice_resolve_returns_err_for_unknown_option() -> Result<(), Error> {
+        let step = PromptStep::choice("region", "Region", options())?;
+
+        ["0", "3", "ap-south-1", ""].iter().for_each(|value| {
+            let choice_result = PromptSet::choice_resolve(&step, &options(), value);
+            assert!(
+                matches!(
+                    &choice_result,
+                    Err(Error::ValueInvalid { field, .. }) if field == "region"
+                ),
+                "Expected `choice_result` to return `ValueInvalid` error for `{}`, but got `{:?}`.",
+                value,
+                choice_result
+            );
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn yes_no_resolve_reads_yes_or_no_ignoring_case() -> Result<(), Error> {
+        let step = PromptStep::yes_no("mfa", "Use an MFA device?");
+
+        assert!(PromptSet::yes_no_resolve(&step, "Y")?);
+        assert!(PromptSet::yes_no_resolve(&step, "yes")?);
+        assert!(!PromptSet::yes_no_resolve(&step, "N")?);
+        assert!(!PromptSet::yes_no_resolve(&step, "false")?);
+        assert!(PromptSet::yes_no_resolve(&step, "maybe").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use credent_cli_model::Error;
+
+use crate::{PromptAnswers, PromptKind};
+
+/// Function that returns whether a step is asked, given the earlier answers.
+type Condition = Box<dyn Fn(&PromptAnswers) -> bool + Send + Sync>;
+
+/// Step in a [`PromptSet`].
+///
+/// [`PromptSet`]: crate::PromptSet
+pub struct PromptStep {
+    /// Key of the answer.
+    key: String,
+    /// Text shown to the user.
+    label: String,
+    /// How the answer is read.
+    kind: PromptKind,
+    /// Whether the step is asked, given the earlier answers.
+    condition: Option<Condition>,
+}
+
+impl PromptStep {
+    /// Returns a new step that reads a single line of text.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Key of the answer.
+    /// * `label`: Text shown to the user, e.g. `"Username"`.
+    pub fn plain<K, L>(key: K, label: L) -> Self
+    where
+        K: Into<String>,
+        L: Into<String>,
+    {
+        Self::new(key, label, PromptKind::Plain)
+    }
+
+    /// Returns a new step that reads a single line of text with hidden input.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Key of the answer.
+    /// * `label`: Text shown to the user, e.g. `"Password"`.
+    pub fn secret<K, L>(key: K, label: L) -> Self
+    where
+        K: Into<String>,
+        L: Into<String>,
+    {
+        Self::new(key, label, PromptKind::Secret)
+    }
+
+    /// Returns a new step that reads one of the given options.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Key of the answer.
+    /// * `label`: Text shown to the user, e.g. `"Region"`.
+    /// * `options`: Options to choose from, which must not be empty.
+    pub fn choice<K, L, I, O>(key: K, label: L, options: I) -> Result<Self, Error>
+    where
+        K: Into<String>,
+        L: Into<String>,
+        I: IntoIterator<Item = O>,
+        O: Into<String>,
+    {
+        let options = options.into_iter().map(Into::into).collect::<Vec<String>>();
+        if options.is_empty() {
+            let key = key.into();
+            return Err(Error::PromptOptionsEmpty { key });
+        }
+        Ok(Self::new(key, label, PromptKind::Choice(options)))
+    }
+
+    /// Returns a new step that reads `yes` or `no`.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Key of the answer.
+    /// * `label`: Text shown to the user, e.g. `"Use an MFA device?"`.
+    pub fn yes_no<K, L>(key: K, label: L) -> Self
+    where
+        K: Into<String>,
+        L: Into<String>,
+    {
+        Self::new(key, label, PromptKind::YesNo)
+    }
+
+    /// Returns a new step that reads lines of text until an empty line.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Key of the answer.
+    /// * `label`: Text shown to the user, e.g. `"Certificate"`.
+    pub fn multi_line<K, L>(key: K, label: L) -> Self
+    where
+        K: Into<String>,
+        L: Into<String>,
+    {
+        Self::new(key, label, PromptKind::MultiLine)
+    }
+
+    /// Returns a new step.
+    ///
+    /// # Parameters
+    ///
+    /// * `key`: Key of the answer.
+    /// * `label`: Text shown to the user.
+    /// * `kind`: How the answer is read.
+    pub fn new<K, L>(key: K, label: L, kind: PromptKind) -> Self
+    where
+        K: Into<String>,
+        L: Into<String>,
+    {
+        Self {
+            key: key.into(),
+            label: label.into(),
+            kind,
+            condition: None,
+        }
+    }
+
+    /// Only asks this step when the condition returns `true` for the earlier
+    /// answers.
+    ///
+    /// ```rust,ignore
+    /// PromptStep::plain("mfa_device", "MFA device")
+    ///     .with_condition(|answers| answers.yes_no("mfa") == Some(true))
+    /// ```
+    pub fn with_condition<F>(mut self, condition: F) -> Self
+    where
+        F: Fn(&PromptAnswers) -> bool + Send + Sync + 'static,
+    {
+        self.condition = Some(Box::new(condition));
+        self
+    }
+
+    /// Returns the key of the answer.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the text shown to the user.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns how the answer is read.
+    pub fn kind(&self) -> &PromptKind {
+        &self.kind
+    }
+
+    /// Returns whether the step is asked, given the earlier answers.
+    pub fn is_asked(&self, answers: &PromptAnswers) -> bool {
+        self.condition
+            .as_ref()
+            .is_none_or(|condition| condition(answers))
+    }
+}
+
+impl fmt::Debug for PromptStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PromptStep")
+            .field("key", &self.key)
+            .field("label", &self.label)
+            .field("kind", &self.kind)
+            .field("condition", &self.condition.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use credent_cli_model::Error;
+
+    use super::PromptStep;
+    use crate::{PromptAnswer, PromptAnswers};
+
+    #[test]
+    fn choice_returns_err_when_options_empty() {
+        let step_result = PromptStep::choice("region", "Region", Vec::<String>::new());
+
+        assert!(
+            matches!(&step_result, Err(Error::PromptOptionsEmpty { key }) if key == "region"),
+            "Expected `step_result` to return `PromptOptionsEmpty` error, but got `{:?}`.",
+            step_result
+        );
+    }
+
+    #[test]
+    fn is_asked_without_condition() {
+        let step = PromptStep::plain("username", "Username");
+
+        assert!(step.is_asked(&PromptAnswers::new()));
+    }
+
+    #[test]
+    fn is_asked_when_condition_returns_true() {
+        let step = PromptStep::plain("mfa_device", "MFA device")
+            .with_condition(|answers| answers.yes_no("mfa") == Some(true));
+        let mut answers = PromptAnswers::new();
+
+        assert!(!step.is_asked(&answers));
+
+        answers.insert(String::from("mfa"), PromptAnswer::YesNo(false));
+        assert!(!step.is_asked(&answers));
+
+        answers.insert(String::from("mfa"), PromptAnswer::YesNo(true));
+        assert!(step.is_asked(&answers));
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 
+use credent_model::UsernameError;
+
 #[cfg(all(feature = "smol", not(feature = "tokio")))]
 type IoError = smol::io::Error;
 
@@ -31,12 +33,30 @@ pub enum Error {
     StdErrFlush(IoError),
     /// Failed to read username.
     UsernameRead(std::io::Error),
+    /// Username does not satisfy the username policy.
+    UsernameInvalid(UsernameError),
     /// Failed to read password.
     PasswordRead(std::io::Error),
     /// Failed to read a plain text value from stdin.
     PlainTextRead(std::io::Error),
     /// Failed to read a secret value from stdin.
     SecretRead(std::io::Error),
+    /// Entered value for a credentials field is invalid.
+    ValueInvalid {
+        /// Name of the field.
+        field: String,
+        /// Message describing why the value is invalid.
+        message: String,
+    },
+    /// Failed to read keys from or draw on the terminal.
+    PickerTerminal(std::io::Error),
+    /// User cancelled the prompt.
+    PromptCancelled,
+    /// A choice prompt step has no options to choose from.
+    PromptOptionsEmpty {
+        /// Key of the step's answer.
+        key: String,
+    },
 
     /// Tokio blocking task join error.
     #[cfg(feature = "tokio")]
@@ -51,9 +71,18 @@ impl fmt::Display for Error {
             }
             Self::StdErrFlush(..) => write!(f, "Failed to flush `stderr`."),
             Self::UsernameRead(..) => write!(f, "Failed to read username from stdin."),
+            Self::UsernameInvalid(error) => write!(f, "Username is invalid. {error}"),
             Self::PasswordRead(..) => write!(f, "Failed to read password from stdin."),
             Self::PlainTextRead(..) => write!(f, "Failed to read value from stdin."),
             Self::SecretRead(..) => write!(f, "Failed to read secret value from stdin."),
+            Self::ValueInvalid { field, message } => {
+                write!(f, "Value for `{field}` is invalid. {message}")
+            }
+            Self::PickerTerminal(..) => write!(f, "Failed to interact with the terminal."),
+            Self::PromptCancelled => write!(f, "Prompt was cancelled."),
+            Self::PromptOptionsEmpty { key } => {
+                write!(f, "Prompt step `{key}` has no options to choose from.")
+            }
 
             #[cfg(feature = "tokio")]
             Self::StdinReadJoin(_) => write!(f, "Failed to wait for stdin task to complete."),
@@ -67,9 +96,14 @@ impl std::error::Error for Error {
             Self::PromptWrite { error, .. } => Some(error),
             Self::StdErrFlush(error) => Some(error),
             Self::UsernameRead(error) => Some(error),
+            Self::UsernameInvalid(error) => Some(error),
             Self::PasswordRead(error) => Some(error),
             Self::PlainTextRead(error) => Some(error),
             Self::SecretRead(error) => Some(error),
+            Self::ValueInvalid { .. } => None,
+            Self::PickerTerminal(error) => Some(error),
+            Self::PromptCancelled => None,
+            Self::PromptOptionsEmpty { .. } => None,
 
             #[cfg(feature = "tokio")]
             Self::StdinReadJoin(error) => Some(error),
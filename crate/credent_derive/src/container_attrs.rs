@@ -0,0 +1,61 @@
+use syn::{Attribute, LitStr, Path};
+
+/// `#[credent(..)]` attributes on the credentials struct.
+#[derive(Debug)]
+pub struct ContainerAttrs {
+    /// Path to the `credent_cli` crate.
+    pub crate_path: Path,
+}
+
+impl ContainerAttrs {
+    /// Parses the `#[credent(..)]` attributes on a struct.
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut crate_path = None;
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("credent"))
+            .try_for_each(|attr| {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("crate") {
+                        let path = meta.value()?.parse::<LitStr>()?;
+                        crate_path = Some(path.parse::<Path>()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("Unknown `credent` attribute. Expected `crate`."))
+                    }
+                })
+            })?;
+
+        Ok(Self {
+            crate_path: crate_path.unwrap_or_else(|| syn::parse_quote!(::credent::cli)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, Attribute};
+
+    use super::ContainerAttrs;
+
+    #[test]
+    fn parse_defaults_crate_path_to_credent_cli() -> syn::Result<()> {
+        let container_attrs = ContainerAttrs::parse(&[])?;
+
+        let crate_path = &container_attrs.crate_path;
+        assert_eq!(
+            "::credent::cli",
+            quote::quote!(#crate_path).to_string().replace(' ', "")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_reads_crate_path() -> syn::Result<()> {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[credent(crate = "credent_cli")])];
+        let container_attrs = ContainerAttrs::parse(&attrs)?;
+
+        assert!(container_attrs.crate_path.is_ident("credent_cli"));
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, Data, DeriveInput, Field, Fields, Generics, Ident, Path, WhereClause,
+    WherePredicate,
+};
+
+use crate::{ContainerAttrs, FieldAttrs};
+
+/// Text shown in place of secret values.
+const SECRET_MASK: &str = "******";
+
+/// Message for a secret value that fails to parse, which leaves out the
+/// parse error as it may contain the value.
+const SECRET_INVALID_MESSAGE: &str = "Entered value is not valid.";
+
+/// Returns the `Credential`, `Debug`, and `Display` implementations for the
+/// struct.
+pub fn expand(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let container_attrs = ContainerAttrs::parse(&ast.attrs)?;
+    let fields = match &ast.data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Named(fields_named) => &fields_named.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &ast.ident,
+                    "`Credential` can only be derived for structs with named fields.",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &ast.ident,
+                "`Credential` can only be derived for structs with named fields.",
+            ));
+        }
+    };
+    let fields = fields
+        .iter()
+        .map(|field| {
+            // Named fields always have an ident.
+            let ident = field.ident.clone().expect("Named field has no ident.");
+            FieldAttrs::parse(&ident, &field.attrs).map(|field_attrs| (field, ident, field_attrs))
+        })
+        .collect::<syn::Result<Vec<(&Field, Ident, FieldAttrs)>>>()?;
+
+    let crate_path = &container_attrs.crate_path;
+    let ident = &ast.ident;
+    let ident_str = ident.to_string();
+    let (impl_generics, ty_generics, _) = ast.generics.split_for_impl();
+    let credential_where_clause = where_clause_bounded(
+        &ast.generics,
+        fields.iter().flat_map(|(field, _, field_attrs)| {
+            let ty = &field.ty;
+            let mut predicates: Vec<WherePredicate> =
+                vec![parse_quote!(#ty: ::core::str::FromStr + ::core::marker::Send)];
+            if !field_attrs.secret {
+                predicates.push(parse_quote!(
+                    <#ty as ::core::str::FromStr>::Err: ::core::fmt::Display
+                ));
+            }
+            predicates
+        }),
+    );
+    let debug_where_clause = where_clause_bounded(
+        &ast.generics,
+        fields
+            .iter()
+            .filter(|(_, _, field_attrs)| !field_attrs.secret)
+            .map(|(field, _, _)| {
+                let ty = &field.ty;
+                parse_quote!(#ty: ::core::fmt::Debug)
+            }),
+    );
+    let display_where_clause = where_clause_bounded(
+        &ast.generics,
+        fields
+            .iter()
+            .filter(|(_, _, field_attrs)| !field_attrs.secret)
+            .map(|(field, _, _)| {
+                let ty = &field.ty;
+                parse_quote!(#ty: ::core::fmt::Display)
+            }),
+    );
+
+    let field_idents = fields.iter().map(|(_, ident, _)| ident).collect::<Vec<_>>();
+    let field_prompts = fields.iter().map(|(field, ident, field_attrs)| {
+        let ty = &field.ty;
+        let ident_str = ident.to_string();
+        let prompt = &field_attrs.prompt;
+        let prompt_fn = if field_attrs.secret {
+            quote!(prompt_secret)
+        } else {
+            quote!(prompt_plain_text)
+        };
+        let checks = field_checks(crate_path, ident, field_attrs, quote!(&#ident));
+        let parse_error_map = if field_attrs.secret {
+            quote! {
+                |_| #crate_path::model::Error::ValueInvalid {
+                    field: ::std::string::String::from(#ident_str),
+                    message: ::std::string::String::from(#SECRET_INVALID_MESSAGE),
+                }
+            }
+        } else {
+            quote! {
+                |error| #crate_path::model::Error::ValueInvalid {
+                    field: ::std::string::String::from(#ident_str),
+                    message: ::std::string::ToString::to_string(&error),
+                }
+            }
+        };
+
+        quote! {
+            let #ident = #crate_path::CredentialsCliReader::<&str, &str>::#prompt_fn(#prompt).await?;
+            let #ident = <#ty as ::core::str::FromStr>::from_str(&#ident).map_err(#parse_error_map)?;
+            #checks
+        }
+    });
+    let field_validations = fields.iter().map(|(_, ident, field_attrs)| {
+        field_checks(crate_path, ident, field_attrs, quote!(&self.#ident))
+    });
+    let debug_fields = fields.iter().map(|(_, ident, field_attrs)| {
+        let ident_str = ident.to_string();
+        if field_attrs.secret {
+            quote!(.field(#ident_str, &#SECRET_MASK))
+        } else {
+            quote!(.field(#ident_str, &self.#ident))
+        }
+    });
+    let display_fields = fields
+        .iter()
+        .enumerate()
+        .map(|(index, (_, ident, field_attrs))| {
+            let separator = if index == 0 { "" } else { ", " };
+            let label = format!("{}{}: ", separator, ident);
+            if field_attrs.secret {
+                quote!(::core::write!(f, "{}{}", #label, #SECRET_MASK)?;)
+            } else {
+                quote!(::core::write!(f, "{}{}", #label, self.#ident)?;)
+            }
+        });
+
+    Ok(quote! {
+        impl #impl_generics #crate_path::Credential for #ident #ty_generics #credential_where_clause {
+            fn prompt_from_tty() -> impl ::core::future::Future<
+                Output = ::core::result::Result<Self, #crate_path::model::Error>,
+            > + ::core::marker::Send {
+                async {
+                    #(#field_prompts)*
+
+                    ::core::result::Result::Ok(Self { #(#field_idents),* })
+                }
+            }
+
+            fn validate(&self) -> ::core::result::Result<(), #crate_path::model::Error> {
+                #(#field_validations)*
+
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #debug_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                f.debug_struct(#ident_str)
+                    #(#debug_fields)*
+                    .finish()
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #display_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                #(#display_fields)*
+
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+/// Returns the struct's where clause, with the given bounds on field types
+/// added when the struct is generic.
+///
+/// The bounds are only needed for generic fields, so they are left out for
+/// other structs.
+fn where_clause_bounded<I>(generics: &Generics, predicates: I) -> Option<WhereClause>
+where
+    I: Iterator<Item = WherePredicate>,
+{
+    let mut generics = generics.clone();
+    if !generics.params.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    generics.where_clause
+}
+
+/// Returns statements that run the field's validators on `value`.
+fn field_checks(
+    crate_path: &Path,
+    ident: &Ident,
+    field_attrs: &FieldAttrs,
+    value: TokenStream,
+) -> TokenStream {
+    let ident_str = ident.to_string();
+    let validators = &field_attrs.validators;
+
+    quote! {
+        #(
+            #validators(#value).map_err(|message: ::std::string::String| {
+                #crate_path::model::Error::ValueInvalid {
+                    field: ::std::string::String::from(#ident_str),
+                    message,
+                }
+            })?;
+        )*
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, DeriveInput};
+
+    use super::expand;
+
+    #[test]
+    fn expand_masks_secret_fields() -> syn::Result<()> {
+        let ast: DeriveInput = parse_quote! {
+            struct ApiCredentials {
+                account: String,
+                #[credent(secret)]
+                api_key: String,
+            }
+        };
+        let tokens = expand(&ast)?.to_string();
+
+        assert!(tokens.contains("prompt_plain_text (\"Account: \")"));
+        assert!(tokens.contains("prompt_secret (\"Api key (input is hidden): \")"));
+        assert!(tokens.contains(". field (\"api_key\" , & \"******\")"));
+        assert!(!tokens.contains("self . api_key)"));
+        Ok(())
+    }
+
+    #[test]
+    fn expand_leaves_parse_error_out_for_secret_fields() -> syn::Result<()> {
+        let ast: DeriveInput = parse_quote! {
+            struct ApiCredentials {
+                port: u16,
+                #[credent(secret)]
+                pin: u16,
+            }
+        };
+        let tokens = expand(&ast)?.to_string();
+
+        assert_eq!(1, tokens.matches("ToString :: to_string (& error)").count());
+        assert!(tokens.contains("\"Entered value is not valid.\""));
+        Ok(())
+    }
+
+    #[test]
+    fn expand_bounds_field_types_of_generic_structs() -> syn::Result<()> {
+        let ast: DeriveInput = parse_quote! {
+            struct ApiCredentials<A> {
+                account: A,
+                #[credent(secret)]
+                api_key: String,
+            }
+        };
+        let tokens = expand(&ast)?.to_string();
+
+        assert!(tokens.contains("where A : :: core :: fmt :: Debug"));
+        assert!(tokens.contains("where A : :: core :: fmt :: Display"));
+        assert!(!tokens.contains("String : :: core :: fmt :: Debug"));
+        Ok(())
+    }
+
+    #[test]
+    fn expand_rejects_tuple_structs() {
+        let ast: DeriveInput = parse_quote! {
+            struct ApiCredentials(String);
+        };
+
+        let error = expand(&ast).expect_err("Expected tuple struct to be rejected.");
+        assert_eq!(
+            "`Credential` can only be derived for structs with named fields.",
+            error.to_string()
+        );
+    }
+}
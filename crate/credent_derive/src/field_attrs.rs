@@ -0,0 +1,116 @@
+use syn::{Attribute, Ident, LitStr, Path};
+
+/// `#[credent(..)]` attributes on a credentials field.
+#[derive(Debug)]
+pub struct FieldAttrs {
+    /// Prompt text for the field.
+    pub prompt: String,
+    /// Whether input is hidden and the value is masked.
+    pub secret: bool,
+    /// Functions that check the field value.
+    pub validators: Vec<Path>,
+}
+
+impl FieldAttrs {
+    /// Parses the `#[credent(..)]` attributes on a field.
+    ///
+    /// # Parameters
+    ///
+    /// * `field_name`: Name of the field, used for the default prompt.
+    /// * `attrs`: Attributes on the field.
+    pub fn parse(field_name: &Ident, attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut prompt = None;
+        let mut secret = false;
+        let mut validators = Vec::new();
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("credent"))
+            .try_for_each(|attr| {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("prompt") {
+                        prompt = Some(meta.value()?.parse::<LitStr>()?.value());
+                        Ok(())
+                    } else if meta.path.is_ident("secret") {
+                        secret = true;
+                        Ok(())
+                    } else if meta.path.is_ident("validate") {
+                        let path = meta.value()?.parse::<LitStr>()?;
+                        validators.push(path.parse::<Path>()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error(
+                            "Unknown `credent` attribute. Expected `prompt`, `secret`, or `validate`.",
+                        ))
+                    }
+                })
+            })?;
+
+        let prompt = prompt.unwrap_or_else(|| Self::prompt_default(field_name, secret));
+
+        Ok(Self {
+            prompt,
+            secret,
+            validators,
+        })
+    }
+
+    /// Returns the field name in sentence case as a prompt, e.g. `"Api key: "`.
+    fn prompt_default(field_name: &Ident, secret: bool) -> String {
+        let field_name = field_name.to_string();
+        let field_name = field_name.trim_start_matches("r#").replace('_', " ");
+        let mut chars = field_name.trim().chars();
+        let mut prompt = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default();
+
+        if secret {
+            prompt.push_str(" (input is hidden)");
+        }
+        prompt.push_str(": ");
+        prompt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, Attribute, Ident};
+
+    use super::FieldAttrs;
+
+    #[test]
+    fn parse_defaults_prompt_to_sentence_case_field_name() -> syn::Result<()> {
+        let field_name: Ident = parse_quote!(api_key);
+        let secret_attrs: Vec<Attribute> = vec![parse_quote!(#[credent(secret)])];
+
+        assert_eq!("Api key: ", FieldAttrs::parse(&field_name, &[])?.prompt);
+        assert_eq!(
+            "Api key (input is hidden): ",
+            FieldAttrs::parse(&field_name, &secret_attrs)?.prompt
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_reads_prompt_secret_and_validators() -> syn::Result<()> {
+        let field_name: Ident = parse_quote!(token);
+        let attrs: Vec<Attribute> = vec![
+            parse_quote!(#[credent(prompt = "Token: ", secret)]),
+            parse_quote!(#[credent(validate = "token_validate", validate = "checks::len")]),
+        ];
+        let field_attrs = FieldAttrs::parse(&field_name, &attrs)?;
+
+        assert_eq!("Token: ", field_attrs.prompt);
+        assert!(field_attrs.secret);
+        assert_eq!(2, field_attrs.validators.len());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_rejects_unknown_attribute() {
+        let field_name: Ident = parse_quote!(token);
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[credent(hidden)])];
+
+        assert!(FieldAttrs::parse(&field_name, &attrs).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+#![deny(missing_debug_implementations, missing_docs)]
+
+//! Derives prompting and masked formatting for custom credentials.
+//!
+//! `#[derive(Credential)]` implements `credent::cli::Credential`, `Debug`, and
+//! `Display` for a struct with named fields:
+//!
+//! ```rust,ignore
+//! use credent::cli::Credential;
+//!
+//! #[derive(Clone, PartialEq, Eq, Credential, Deserialize, Serialize)]
+//! struct ApiCredentials {
+//!     #[credent(prompt = "Account: ", validate = "account_validate")]
+//!     account: String,
+//!     #[credent(secret)]
+//!     api_key: String,
+//! }
+//! ```
+//!
+//! Each field's type must implement `FromStr`, which parses the entered text,
+//! such as `String` or `u16`. Fields that are not secret must also implement
+//! `Debug` and `Display`. For generic structs, these bounds are added to the
+//! generated implementations.
+//!
+//! Field attributes:
+//!
+//! * `#[credent(prompt = "..")]`: Prompt text for the field. Defaults to the
+//!   field name in sentence case, e.g. `"Api key: "`.
+//! * `#[credent(secret)]`: Input is hidden, and the value is masked in `Debug`
+//!   and `Display`. A value that fails to parse is reported without the parse
+//!   error, which may contain it.
+//! * `#[credent(validate = "path::to::fn")]`: Function with the signature
+//!   `fn(&T) -> Result<(), String>` that checks the field value. May be
+//!   specified multiple times.
+//!
+//! Container attributes:
+//!
+//! * `#[credent(crate = "..")]`: Path to the `credent_cli` crate. Defaults to
+//!   `credent::cli`.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+use crate::{container_attrs::ContainerAttrs, field_attrs::FieldAttrs};
+
+mod container_attrs;
+mod credential_impl;
+mod field_attrs;
+
+/// Implements `Credential`, `Debug`, and `Display` for a credentials struct.
+#[proc_macro_derive(Credential, attributes(credent))]
+pub fn credential_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    credential_impl::expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
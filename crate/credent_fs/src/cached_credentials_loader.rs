@@ -0,0 +1,486 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+
+use credent_fs_model::{CredentialsMeta, Error, IntegrityKey};
+use credent_model::{Credentials, Profile, Profiles};
+use serde::Deserialize;
+
+use crate::{
+    credentials_audit_log::AuditOperation, CredentialsAuditLog, CredentialsFileLoader,
+    CredentialsTarget,
+};
+
+/// Number of times each credentials file has been written through the
+/// [`CredentialsFileStorer`](crate::CredentialsFileStorer) in this process.
+static WRITE_GENERATIONS: Mutex<BTreeMap<PathBuf, u64>> = Mutex::new(BTreeMap::new());
+
+/// Reads credentials, keeping the loaded profiles in memory.
+///
+/// The profiles of each credentials file are read once, and reused until the
+/// file's modification time or size changes, or the file is written through
+/// the [`CredentialsFileStorer`]. Profiles are only reused for a target with
+/// the same integrity key that they were verified with, so a different key
+/// reads and verifies the file again.
+///
+/// Clones share the same cache, so a `CachedCredentialsLoader` can be cloned
+/// into each task that loads credentials.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let loader = CachedCredentialsLoader::<Credentials>::new();
+///
+/// // Reads the file.
+/// let profile = loader.load_profile(APP_NAME, "work").await?;
+/// // Reuses the profiles read above.
+/// let profile = loader.load_profile(APP_NAME, "home").await?;
+/// ```
+///
+/// [`CredentialsFileStorer`]: crate::CredentialsFileStorer
+#[derive(Clone, Debug)]
+pub struct CachedCredentialsLoader<C = Credentials>
+where
+    C: Clone + Eq,
+{
+    /// Loaded profiles of each credentials file.
+    entries: Arc<Mutex<HashMap<PathBuf, Arc<CacheEntry<C>>>>>,
+}
+
+impl<C> CachedCredentialsLoader<C>
+where
+    C: Clone + Eq + for<'de> Deserialize<'de>,
+{
+    /// Returns a new `CachedCredentialsLoader` with an empty cache.
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the default profile credentials, reading the credentials file
+    /// if it has changed since it was last read.
+    ///
+    /// See [`CredentialsFileLoader::load`].
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    pub async fn load<'s, R>(&self, target: R) -> Result<Option<Profile<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        self.load_profile(target, Profile::<C>::DEFAULT_NAME).await
+    }
+
+    /// Returns the profile credentials, reading the credentials file if it
+    /// has changed since it was last read.
+    ///
+    /// See [`CredentialsFileLoader::load_profile`].
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    /// * `profile_name`: Which profile's credentials to load. This may be an
+    ///   alias, or `"default"` to load the file's default profile.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name),
+            err(Display)
+        )
+    )]
+    pub async fn load_profile<'s, R>(
+        &self,
+        target: R,
+        profile_name: &str,
+    ) -> Result<Option<Profile<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Load, &[profile_name], async {
+            let credentials_path = target.path()?;
+            let entry = match self.entry(&target, &credentials_path).await? {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+
+            let profile_name = entry.meta.profile_name_resolve(profile_name)?;
+            if entry.profile_names_invalid.contains(profile_name) {
+                // Invalid profiles and profiles with secret references are
+                // not cached, so read the file again to resolve the profile or
+                // return its error.
+                let document = CredentialsFileLoader::<C>::document_load(
+                    &credentials_path,
+                    target.options().integrity_key(),
+                )
+                .await?;
+                return match document.profiles.get(profile_name) {
+                    Some(credentials) => CredentialsFileLoader::document_profile(
+                        &document,
+                        profile_name,
+                        credentials,
+                        &credentials_path,
+                        target.secret_resolver(),
+                    )
+                    .await
+                    .map(Some),
+                    None => Ok(None),
+                };
+            }
+
+            Ok(entry
+                .profiles
+                .iter()
+                .find(|profile| profile.name == profile_name)
+                .cloned())
+        })
+        .await
+    }
+
+    /// Returns all profile credentials, reading the credentials file if it
+    /// has changed since it was last read.
+    ///
+    /// See [`CredentialsFileLoader::load_all`].
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load_all<'s, R>(&self, target: R) -> Result<Option<Profiles<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Load, &[], async {
+            let credentials_path = target.path()?;
+            let entry = match self.entry(&target, &credentials_path).await? {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+
+            if entry.profile_names_invalid.is_empty() {
+                Ok(Some(entry.profiles.clone()))
+            } else {
+                // Read the file again to return the first invalid profile's
+                // error, or the profiles with secret references unresolved.
+                let document = CredentialsFileLoader::<C>::document_load(
+                    &credentials_path,
+                    target.options().integrity_key(),
+                )
+                .await?;
+                CredentialsFileLoader::document_profiles(&document, &credentials_path).map(Some)
+            }
+        })
+        .await
+    }
+
+    /// Removes the cached profiles of the given credentials file, so that the
+    /// next load reads the file.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: Path to the credentials file.
+    pub fn invalidate(&self, credentials_path: &Path) {
+        self.entries_lock().remove(credentials_path);
+    }
+
+    /// Removes all cached profiles.
+    pub fn clear(&self) {
+        self.entries_lock().clear();
+    }
+
+    /// Returns the cached entry for the credentials file, reading the file if
+    /// it has changed, or `None` if it does not exist.
+    async fn entry(
+        &self,
+        target: &CredentialsTarget<'_>,
+        credentials_path: &Path,
+    ) -> Result<Option<Arc<CacheEntry<C>>>, Error> {
+        if !credentials_path.exists() {
+            self.invalidate(credentials_path);
+            return Ok(None);
+        }
+
+        // The fingerprint is taken before reading the file, so that a change
+        // made while the file is read invalidates the entry.
+        let fingerprint = FileFingerprint::read(credentials_path).await;
+        let integrity_key = target.options().integrity_key();
+        if let Some(fingerprint) = fingerprint.as_ref() {
+            let entry = self.entries_lock().get(credentials_path).cloned();
+            if let Some(entry) = entry.filter(|entry| {
+                &entry.fingerprint == fingerprint && entry.integrity_key.as_ref() == integrity_key
+            }) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    credentials_path = %credentials_path.display(),
+                    "Reusing cached credentials."
+                );
+                return Ok(Some(entry));
+            }
+        }
+
+        let document =
+            CredentialsFileLoader::<C>::document_load(credentials_path, integrity_key).await?;
+        let mut profiles = Profiles::new();
+        let mut profile_names_invalid = BTreeSet::new();
+        for (profile_name, credentials) in &document.profiles {
+            match CredentialsFileLoader::document_profile_unresolved(
+                &document,
+                profile_name,
+                credentials,
+                credentials_path,
+            ) {
+                Ok(profile) => {
+                    profiles.insert(profile);
+                }
+                Err(_) => {
+                    profile_names_invalid.insert(profile_name.clone());
+                }
+            }
+        }
+
+        let entry = Arc::new(CacheEntry {
+            fingerprint: fingerprint.clone().unwrap_or_default(),
+            integrity_key: integrity_key.cloned(),
+            meta: document.meta,
+            profiles,
+            profile_names_invalid,
+        });
+        // Without a fingerprint, the file cannot be checked for changes, so
+        // it is not cached.
+        if fingerprint.is_some() {
+            self.entries_lock()
+                .insert(credentials_path.to_owned(), Arc::clone(&entry));
+        }
+
+        Ok(Some(entry))
+    }
+
+    fn entries_lock(&self) -> MutexGuard<'_, HashMap<PathBuf, Arc<CacheEntry<C>>>> {
+        // Entries are replaced whole, so a panic while the lock is held cannot
+        // leave an entry partially updated.
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records that a credentials file was written, so that its cached
+    /// profiles are read again.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: Path to the credentials file.
+    pub(crate) fn credentials_file_written(credentials_path: &Path) {
+        let mut write_generations = WRITE_GENERATIONS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *write_generations
+            .entry(credentials_path.to_owned())
+            .or_default() += 1;
+    }
+}
+
+impl<C> Default for CachedCredentialsLoader<C>
+where
+    C: Clone + Eq + for<'de> Deserialize<'de>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Profiles loaded from a credentials file.
+#[derive(Debug)]
+struct CacheEntry<C>
+where
+    C: Clone + Eq,
+{
+    /// State of the file when it was read.
+    fingerprint: FileFingerprint,
+    /// Key that the file was verified with, if any.
+    integrity_key: Option<IntegrityKey>,
+    /// Metadata stored alongside the profiles.
+    meta: CredentialsMeta,
+    /// Profiles that were loaded successfully.
+    profiles: Profiles<C>,
+    /// Names of profiles that failed to be loaded, or have secret references.
+    profile_names_invalid: BTreeSet<String>,
+}
+
+/// State of a credentials file used to detect changes to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct FileFingerprint {
+    /// Time the file was last modified, if the platform records it.
+    modified: Option<SystemTime>,
+    /// Size of the file in bytes.
+    len: u64,
+    /// Number of writes through the storer when the file was read.
+    write_generation: u64,
+}
+
+impl FileFingerprint {
+    /// Returns the fingerprint of the file, or `None` if its metadata cannot
+    /// be read.
+    async fn read(credentials_path: &Path) -> Option<Self> {
+        let write_generation = WRITE_GENERATIONS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(credentials_path)
+            .copied()
+            .unwrap_or_default();
+        let metadata = async_fs::metadata(credentials_path).await.ok()?;
+
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            write_generation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use credent_fs_model::{AppName, Error, IntegrityKey};
+    use credent_model::{Credentials, Password, Profile, Username};
+
+    use super::CachedCredentialsLoader;
+    use crate::{
+        CredentialsDir, CredentialsFileOptions, CredentialsFileStorer, CredentialsPathResolver,
+        CredentialsTarget,
+    };
+
+    fn resolver(tempdir: &tempfile::TempDir) -> CredentialsPathResolver<'static> {
+        CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>)
+    }
+
+    fn profile(username: &str) -> Profile<Credentials> {
+        Profile::new_default(Credentials {
+            username: Username(String::from(username)),
+            password: Password::new("secret"),
+        })
+    }
+
+    #[test]
+    fn reuses_profiles_until_file_changes() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let resolver = resolver(&tempdir);
+            let credentials_path = resolver.resolve()?;
+            CredentialsFileStorer::store(resolver.clone(), &profile("me")).await?;
+
+            let loader = CachedCredentialsLoader::<Credentials>::new();
+            assert_eq!(Some(profile("me")), loader.load(resolver.clone()).await?);
+
+            // Same size and modification time, so the cached profile is used.
+            let modified = std::fs::metadata(&credentials_path)?.modified()?;
+            let contents = std::fs::read_to_string(&credentials_path)?;
+            std::fs::write(&credentials_path, contents.replace("'me'", "'ma'"))?;
+            File::options()
+                .write(true)
+                .open(&credentials_path)?
+                .set_modified(modified)?;
+            assert_eq!(Some(profile("me")), loader.load(resolver.clone()).await?);
+
+            loader.invalidate(&credentials_path);
+            assert_eq!(Some(profile("ma")), loader.load(resolver.clone()).await?);
+
+            std::fs::write(&credentials_path, contents.replace("'me'", "'mine'"))?;
+            assert_eq!(Some(profile("mine")), loader.load(resolver).await?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn reads_profiles_again_after_store() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let resolver = resolver(&tempdir);
+            let loader = CachedCredentialsLoader::<Credentials>::new();
+            assert_eq!(None, loader.load(resolver.clone()).await?);
+
+            CredentialsFileStorer::store(resolver.clone(), &profile("me")).await?;
+            assert_eq!(Some(profile("me")), loader.load(resolver.clone()).await?);
+
+            CredentialsFileStorer::store(resolver.clone(), &profile("ma")).await?;
+            assert_eq!(Some(profile("ma")), loader.load(resolver).await?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn returns_err_for_invalid_profile_only() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let resolver = resolver(&tempdir);
+            CredentialsFileStorer::store(resolver.clone(), &profile("me")).await?;
+            let credentials_path = resolver.resolve()?;
+            let contents = std::fs::read_to_string(&credentials_path)?;
+            std::fs::write(
+                &credentials_path,
+                format!("{contents}\n[broken]\nusername = 'me'\n"),
+            )?;
+
+            let loader = CachedCredentialsLoader::<Credentials>::new();
+            assert_eq!(Some(profile("me")), loader.load(resolver.clone()).await?);
+
+            let load_result = loader.load_profile(resolver.clone(), "broken").await;
+            assert!(
+                matches!(load_result, Err(Error::CredentialsFileDeserialize { .. })),
+                "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                load_result
+            );
+            assert!(loader.load_all(resolver).await.is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn verifies_again_for_different_integrity_key() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let resolver = resolver(&tempdir);
+            let target_signed = CredentialsTarget::new(resolver.clone()).with_options(
+                CredentialsFileOptions::new()
+                    .with_integrity_key(IntegrityKey::Secret(b"right".to_vec())),
+            );
+            let target_wrong = CredentialsTarget::new(resolver).with_options(
+                CredentialsFileOptions::new()
+                    .with_integrity_key(IntegrityKey::Secret(b"wrong".to_vec())),
+            );
+            CredentialsFileStorer::store(target_signed.clone(), &profile("me")).await?;
+
+            let loader = CachedCredentialsLoader::<Credentials>::new();
+            assert_eq!(Some(profile("me")), loader.load(target_signed).await?);
+
+            let load_result = loader.load(target_wrong).await;
+            assert!(
+                matches!(
+                    load_result,
+                    Err(Error::CredentialsFileIntegrityMismatch { .. })
+                ),
+                "Expected `load_result` to return `CredentialsFileIntegrityMismatch` error, but got `{:?}`.",
+                load_result
+            );
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,629 @@
+use std::{
+    fs::OpenOptions,
+    future::Future,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use credent_fs_model::Error;
+use fs2::FileExt;
+use serde::Serialize;
+
+use crate::CredentialsTarget;
+
+/// Records access to and changes of credentials as JSON lines in a file.
+///
+/// Each load, store, remove, and rename through a [`CredentialsTarget`],
+/// [`CredentialsLayers`], or `*_file` function with an audit log appends a
+/// line before the access, and a line after it with its outcome:
+///
+/// ```json
+/// {"timestamp":"2026-10-18T09:30:00.123Z","app":"credent","path":"/home/me/.config/credent/credentials","profile":"default","operation":"store","process":"credent","outcome":"started"}
+/// {"timestamp":"2026-10-18T09:30:00.125Z","app":"credent","path":"/home/me/.config/credent/credentials","profile":"default","operation":"store","process":"credent","outcome":"succeeded"}
+/// ```
+///
+/// Secrets are never recorded.
+///
+/// If the first line cannot be written, the access is not made and
+/// [`Error::AuditLogWrite`] is returned, so that no change goes unrecorded.
+///
+/// When the file would exceed the maximum size, it is renamed to `<file>.1`,
+/// and older files are renamed to `<file>.2` and so on, keeping at most the
+/// maximum number of rotated files. Appending and rotating hold a lock on
+/// `<file>.lock`, so processes may share the audit log.
+///
+/// [`CredentialsLayers`]: crate::CredentialsLayers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsAuditLog {
+    /// Path to the audit log file.
+    path: PathBuf,
+    /// Size in bytes that the file may grow to before it is rotated.
+    max_size: u64,
+    /// Number of rotated files to keep.
+    max_files: usize,
+}
+
+impl CredentialsAuditLog {
+    /// Default size in bytes that the file may grow to before it is rotated.
+    pub const MAX_SIZE_DEFAULT: u64 = 10 * 1024 * 1024;
+    /// Default number of rotated files to keep.
+    pub const MAX_FILES_DEFAULT: usize = 5;
+
+    /// Returns a new `CredentialsAuditLog` that writes to the given file.
+    ///
+    /// # Parameters
+    ///
+    /// * `path`: Path to the audit log file.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            max_size: Self::MAX_SIZE_DEFAULT,
+            max_files: Self::MAX_FILES_DEFAULT,
+        }
+    }
+
+    /// Sets the size in bytes that the file may grow to before it is rotated.
+    ///
+    /// Defaults to [`MAX_SIZE_DEFAULT`](Self::MAX_SIZE_DEFAULT).
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the number of rotated files to keep.
+    ///
+    /// Defaults to [`MAX_FILES_DEFAULT`](Self::MAX_FILES_DEFAULT). If this is
+    /// `0`, the file is truncated instead of rotated.
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Returns the path to the audit log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the size in bytes that the file may grow to before it is
+    /// rotated.
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Returns the number of rotated files to keep.
+    pub fn max_files(&self) -> usize {
+        self.max_files
+    }
+
+    /// Runs the operation and records it in the target's audit log, if it
+    /// has one.
+    ///
+    /// See [`audited_with`](Self::audited_with).
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Credentials file that is accessed.
+    /// * `operation`: Kind of access.
+    /// * `profile_names`: Names of the profiles that are accessed.
+    /// * `operation_fut`: The operation to run.
+    pub(crate) async fn audited<T, Fut>(
+        target: &CredentialsTarget<'_>,
+        operation: AuditOperation<'_>,
+        profile_names: &[&str],
+        operation_fut: Fut,
+    ) -> Result<T, Error>
+    where
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let audit_log = match target.options().audit_log() {
+            Some(audit_log) => audit_log,
+            None => return operation_fut.await,
+        };
+        let app = target.resolver().app_id().to_string();
+        let credentials_path = target.path().ok();
+        let audit_target = AuditTarget {
+            app: Some(&app),
+            credentials_path: credentials_path.as_deref(),
+        };
+
+        Self::audited_with(
+            Some(audit_log),
+            audit_target,
+            operation,
+            profile_names,
+            operation_fut,
+        )
+        .await
+    }
+
+    /// Runs the operation and records it in the audit log, if there is one.
+    ///
+    /// One line is recorded for each profile name, or a single line without
+    /// a profile name if there are none, both before and after the operation.
+    /// If the lines before the operation cannot be written, the operation is
+    /// not run and the error is returned.
+    ///
+    /// The operation's result is returned even if the lines after it cannot be
+    /// written, as the operation has already been made.
+    ///
+    /// # Parameters
+    ///
+    /// * `audit_log`: Audit log to record the access in, if any.
+    /// * `audit_target`: Application and file whose credentials are accessed.
+    /// * `operation`: Kind of access.
+    /// * `profile_names`: Names of the profiles that are accessed.
+    /// * `operation_fut`: The operation to run.
+    pub(crate) async fn audited_with<T, Fut>(
+        audit_log: Option<&Self>,
+        audit_target: AuditTarget<'_>,
+        operation: AuditOperation<'_>,
+        profile_names: &[&str],
+        operation_fut: Fut,
+    ) -> Result<T, Error>
+    where
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let audit_log = match audit_log {
+            Some(audit_log) => audit_log,
+            None => return operation_fut.await,
+        };
+
+        audit_log
+            .record(
+                audit_target,
+                operation,
+                profile_names,
+                AuditOutcome::Started,
+            )
+            .await?;
+
+        let operation_result = operation_fut.await;
+
+        let outcome = if operation_result.is_ok() {
+            AuditOutcome::Succeeded
+        } else {
+            AuditOutcome::Failed
+        };
+        let record_result = audit_log
+            .record(audit_target, operation, profile_names, outcome)
+            .await;
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &record_result {
+            tracing::warn!(error = %error, "Failed to record the outcome in the audit log.");
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = record_result;
+
+        operation_result
+    }
+
+    /// Appends a line for each profile name to the audit log.
+    async fn record(
+        &self,
+        audit_target: AuditTarget<'_>,
+        operation: AuditOperation<'_>,
+        profile_names: &[&str],
+        outcome: AuditOutcome,
+    ) -> Result<(), Error> {
+        let timestamp = Self::timestamp(SystemTime::now());
+        let path = audit_target
+            .credentials_path
+            .map(|credentials_path| credentials_path.display().to_string());
+        let process = Self::process_name();
+        let profile_names = if profile_names.is_empty() {
+            vec![None]
+        } else {
+            profile_names.iter().copied().map(Some).collect()
+        };
+        let lines = profile_names
+            .into_iter()
+            .map(|profile| {
+                let entry = AuditEntry {
+                    timestamp: &timestamp,
+                    app: audit_target.app,
+                    path: path.as_deref(),
+                    profile,
+                    operation,
+                    process: &process,
+                    outcome,
+                };
+                serde_json::to_string(&entry).map(|mut line| {
+                    line.push('\n');
+                    line
+                })
+            })
+            .collect::<Result<String, serde_json::Error>>()
+            .map_err(io::Error::from);
+
+        let audit_log = self.clone();
+        blocking::unblock(move || lines.and_then(|lines| audit_log.append(&lines)))
+            .await
+            .map_err(|error| {
+                let audit_path = self.path.clone();
+                Error::AuditLogWrite { audit_path, error }
+            })
+    }
+
+    fn append(&self, lines: &str) -> io::Result<()> {
+        if let Some(parent_path) = self.path.parent() {
+            std::fs::create_dir_all(parent_path)?;
+        }
+
+        // The audit log itself is renamed when rotated, so a separate file is
+        // locked.
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(self.path_lock())?;
+        lock_file.lock_exclusive()?;
+
+        let append_result = self.rotate_if_full(lines.len() as u64).and_then(|()| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?
+                .write_all(lines.as_bytes())
+        });
+        let unlock_result = FileExt::unlock(&lock_file);
+
+        append_result.and(unlock_result)
+    }
+
+    fn rotate_if_full(&self, lines_len: u64) -> io::Result<()> {
+        let size = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        if size + lines_len <= self.max_size {
+            return Ok(());
+        }
+
+        if self.max_files == 0 {
+            return Self::remove_if_exists(&self.path);
+        }
+        Self::remove_if_exists(&self.path_rotated(self.max_files))?;
+        for index in (1..self.max_files).rev() {
+            let path_rotated = self.path_rotated(index);
+            if path_rotated.exists() {
+                std::fs::rename(path_rotated, self.path_rotated(index + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.path_rotated(1))
+    }
+
+    fn path_lock(&self) -> PathBuf {
+        let mut path_lock = self.path.clone().into_os_string();
+        path_lock.push(".lock");
+        PathBuf::from(path_lock)
+    }
+
+    fn path_rotated(&self, index: usize) -> PathBuf {
+        let mut path_rotated = self.path.clone().into_os_string();
+        path_rotated.push(format!(".{index}"));
+        PathBuf::from(path_rotated)
+    }
+
+    fn remove_if_exists(path: &Path) -> io::Result<()> {
+        match std::fs::remove_file(path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    fn process_name() -> String {
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe_path| {
+                exe_path
+                    .file_stem()
+                    .map(|file_stem| file_stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the time in RFC 3339 format, in UTC with millisecond precision.
+    fn timestamp(time: SystemTime) -> String {
+        let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = duration.as_secs() as i64;
+        let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+        // Converts days since the epoch to a civil date, from
+        // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            secs_of_day / 3600,
+            secs_of_day % 3600 / 60,
+            secs_of_day % 60,
+            duration.subsec_millis()
+        )
+    }
+}
+
+/// Kind of access to credentials that is recorded in the audit log.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "operation")]
+pub(crate) enum AuditOperation<'s> {
+    /// Profiles were read.
+    Load,
+    /// A profile was written.
+    Store,
+    /// A profile was removed.
+    Remove,
+    /// A profile was renamed.
+    Rename {
+        /// New name of the profile.
+        profile_new: &'s str,
+    },
+    /// The default profile was set.
+    SetDefault,
+    /// The file was signed with an integrity key.
+    Sign,
+}
+
+/// Application and file whose credentials are accessed.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AuditTarget<'t> {
+    /// Application whose credentials are accessed, if known.
+    pub(crate) app: Option<&'t str>,
+    /// Path to the credentials file that is accessed, if there is a single
+    /// file.
+    pub(crate) credentials_path: Option<&'t Path>,
+}
+
+impl<'t> AuditTarget<'t> {
+    /// Returns the target for a credentials file accessed by path.
+    pub(crate) fn file(credentials_path: &'t Path) -> Self {
+        Self {
+            app: None,
+            credentials_path: Some(credentials_path),
+        }
+    }
+}
+
+/// Whether an access is about to be made, or how it went.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AuditOutcome {
+    /// The access is about to be made.
+    Started,
+    /// The access succeeded.
+    Succeeded,
+    /// The access failed.
+    Failed,
+}
+
+/// Line in the audit log.
+#[derive(Debug, Serialize)]
+struct AuditEntry<'e> {
+    /// Time of the access, in RFC 3339 format.
+    timestamp: &'e str,
+    /// Application whose credentials were accessed, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    app: Option<&'e str>,
+    /// Path to the credentials file that was accessed, if there is a single
+    /// file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'e str>,
+    /// Name of the profile that was accessed, if the access was for a single
+    /// profile.
+    profile: Option<&'e str>,
+    /// Kind of access.
+    #[serde(flatten)]
+    operation: AuditOperation<'e>,
+    /// Name of the process that accessed the credentials.
+    process: &'e str,
+    /// Whether the access is about to be made, or how it went.
+    outcome: AuditOutcome,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use credent_fs_model::{AppName, Error};
+    use credent_model::{Credentials, Password, Profile, Username};
+    use serde_json::Value;
+
+    use super::CredentialsAuditLog;
+    use crate::{
+        CredentialsDir, CredentialsFileLoader, CredentialsFileOptions, CredentialsFileStorer,
+        CredentialsPathResolver, CredentialsTarget,
+    };
+
+    #[test]
+    fn records_operations_without_secrets() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let audit_path = tempdir.path().join("audit").join("audit.log");
+            let target = target(&tempdir, &audit_path);
+
+            CredentialsFileStorer::store(target.clone(), &profile()).await?;
+            CredentialsFileStorer::<Credentials>::rename(target.clone(), "work", "home").await?;
+            CredentialsFileLoader::<Credentials>::load_profile(target.clone(), "work").await?;
+
+            let contents = std::fs::read_to_string(&audit_path)?;
+            assert!(!contents.contains("secret"));
+            assert!(!contents.contains("password"));
+
+            let entries = contents
+                .lines()
+                .map(serde_json::from_str::<Value>)
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(6, entries.len());
+            let credentials_path = tempdir.path().join("credentials");
+            entries.iter().for_each(|entry| {
+                assert_eq!("credent", entry["app"]);
+                assert_eq!(credentials_path.display().to_string(), entry["path"]);
+                assert!(entry["timestamp"].is_string());
+                assert!(entry["process"].is_string());
+            });
+            assert_eq!("store", entries[0]["operation"]);
+            assert_eq!("work", entries[0]["profile"]);
+            assert_eq!("started", entries[0]["outcome"]);
+            assert_eq!("store", entries[1]["operation"]);
+            assert_eq!("succeeded", entries[1]["outcome"]);
+            assert_eq!("rename", entries[2]["operation"]);
+            assert_eq!("home", entries[2]["profile_new"]);
+            assert_eq!("succeeded", entries[3]["outcome"]);
+            assert_eq!("load", entries[4]["operation"]);
+            assert!(entries[4].get("profile_new").is_none());
+            assert_eq!("succeeded", entries[5]["outcome"]);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn does_not_store_when_audit_log_cannot_be_written() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            // A directory cannot be appended to.
+            let audit_path = tempdir.path().join("audit.log");
+            std::fs::create_dir(&audit_path)?;
+            let target = target(&tempdir, &audit_path);
+
+            let store_result = CredentialsFileStorer::store(target.clone(), &profile()).await;
+
+            assert!(matches!(store_result, Err(Error::AuditLogWrite { .. })));
+            assert!(!tempdir.path().join("credentials").exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn records_file_operations_with_path() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let audit_log = CredentialsAuditLog::new(tempdir.path().join("audit.log"));
+            let credentials_path = tempdir.path().join("credentials");
+
+            CredentialsFileStorer::store_file(
+                &profile(),
+                &credentials_path,
+                None,
+                Some(&audit_log),
+            )
+            .await?;
+            CredentialsFileLoader::<Credentials>::load_file(
+                &credentials_path,
+                None,
+                Some(&audit_log),
+            )
+            .await?;
+
+            let contents = std::fs::read_to_string(audit_log.path())?;
+            let entries = contents
+                .lines()
+                .map(serde_json::from_str::<Value>)
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(4, entries.len());
+            entries.iter().for_each(|entry| {
+                assert!(entry.get("app").is_none());
+                assert_eq!(credentials_path.display().to_string(), entry["path"]);
+            });
+            assert_eq!("store", entries[1]["operation"]);
+            assert_eq!("work", entries[1]["profile"]);
+            assert_eq!("load", entries[3]["operation"]);
+            assert_eq!(Value::Null, entries[3]["profile"]);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn records_load_meta() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let audit_path = tempdir.path().join("audit.log");
+            let target = target(&tempdir, &audit_path);
+
+            CredentialsFileLoader::<Credentials>::load_meta(target).await?;
+
+            let contents = std::fs::read_to_string(&audit_path)?;
+            let outcomes = contents
+                .lines()
+                .map(serde_json::from_str::<Value>)
+                .map(|entry| entry.map(|entry| entry["outcome"].clone()))
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(vec!["started", "succeeded"], outcomes);
+
+            Ok(())
+        })
+    }
+
+    fn target(
+        tempdir: &tempfile::TempDir,
+        audit_path: &std::path::Path,
+    ) -> CredentialsTarget<'static> {
+        let resolver = CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>);
+        CredentialsTarget::new(resolver).with_options(
+            CredentialsFileOptions::new().with_audit_log(CredentialsAuditLog::new(audit_path)),
+        )
+    }
+
+    fn profile() -> Profile<Credentials> {
+        Profile::new(
+            String::from("work"),
+            Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("secret"),
+            },
+        )
+    }
+
+    #[test]
+    fn timestamp_formats_rfc3339() {
+        assert_eq!(
+            "1970-01-01T00:00:00.000Z",
+            CredentialsAuditLog::timestamp(UNIX_EPOCH)
+        );
+        assert_eq!(
+            "2023-11-14T22:13:20.250Z",
+            CredentialsAuditLog::timestamp(UNIX_EPOCH + Duration::from_millis(1_700_000_000_250))
+        );
+        assert_eq!(
+            "2024-02-29T12:00:00.000Z",
+            CredentialsAuditLog::timestamp(UNIX_EPOCH + Duration::from_secs(1_709_208_000))
+        );
+    }
+
+    #[test]
+    fn append_rotates_when_full() -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let audit_log = CredentialsAuditLog::new(tempdir.path().join("audit.log"))
+            .with_max_size(10)
+            .with_max_files(2);
+
+        audit_log.append("first\n")?;
+        audit_log.append("second\n")?;
+        audit_log.append("third\n")?;
+        audit_log.append("fourth\n")?;
+
+        let read = |name: &str| std::fs::read_to_string(tempdir.path().join(name));
+        assert_eq!("fourth\n", read("audit.log")?);
+        assert_eq!("third\n", read("audit.log.1")?);
+        assert_eq!("second\n", read("audit.log.2")?);
+        assert!(!tempdir.path().join("audit.log.3").exists());
+
+        Ok(())
+    }
+}
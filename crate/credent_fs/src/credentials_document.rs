@@ -0,0 +1,734 @@
+use std::path::Path;
+
+use credent_fs_model::{CredentialsMeta, Error, SourceLocation};
+use credent_model::Profile;
+use serde::{Deserialize, Serialize};
+use toml::{value::Table, Value};
+
+use crate::{
+    credentials_integrity::HMAC_KEY,
+    credentials_migration::{CredentialsMigration, VERSION_KEY},
+    credentials_secret_resolver::{
+        PASSWORD_COMMAND_KEY, PASSWORD_FILE_KEY, PASSWORD_KEY, SECRET_REF_KEYS,
+    },
+    CREDENTIALS_FILE_VERSION,
+};
+
+/// Key in a profile that names the profile to inherit missing fields from.
+pub(crate) const INHERITS_KEY: &str = "inherits";
+/// Keys in a profile that provide its password.
+pub(crate) const PASSWORD_SOURCE_KEYS: &[&str] =
+    &[PASSWORD_KEY, PASSWORD_COMMAND_KEY, PASSWORD_FILE_KEY];
+/// Text shown in place of values in errors.
+const VALUE_MASK: &str = "******";
+/// Delimiters of TOML multi-line strings.
+const MULTILINE_STRING_DELIMITERS: [&str; 2] = ["\"\"\"", "'''"];
+
+/// Contents of a credentials file, whose profiles are not yet deserialized.
+///
+/// Profiles are held as TOML values in the order they appear in the file, so
+/// that profiles which are not modified are written back as they were read.
+///
+/// A profile may declare `inherits = "<profile>"`, in which case fields that
+/// it does not specify are taken from that profile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct CredentialsDocument {
+    /// Metadata stored alongside the profiles.
+    pub(crate) meta: CredentialsMeta,
+    /// Profile names mapped to their TOML values.
+    pub(crate) profiles: Table,
+    /// HMAC of the contents, if the file is signed.
+    pub(crate) hmac: Option<String>,
+    /// Version of the layout that the contents were in, before they were
+    /// migrated to the current version.
+    pub(crate) version_source: u32,
+    /// Contents that the document was parsed from, used to locate errors.
+    pub(crate) source: String,
+}
+
+impl CredentialsDocument {
+    /// Parses the contents of a credentials file.
+    ///
+    /// # Parameters
+    ///
+    /// * `profiles_contents`: Contents of the credentials file.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn from_slice(
+        profiles_contents: &[u8],
+        credentials_path: &Path,
+    ) -> Result<Self, Error> {
+        let source = String::from_utf8_lossy(profiles_contents).into_owned();
+        let mut table = toml::from_slice::<Table>(profiles_contents).map_err(|error| {
+            let location = error
+                .line_col()
+                .and_then(|(line, column)| Self::source_location(&source, line, column));
+            let credentials_path = credentials_path.to_owned();
+            let error = Self::error_masked(error);
+            Error::CredentialsFileDeserialize {
+                credentials_path,
+                profile_name: None,
+                location,
+                error,
+            }
+        })?;
+        let version_source = CredentialsMigration::migrate(&mut table, credentials_path)?;
+        let mut hmac = None;
+        let (meta_table, profiles) = table
+            .into_iter()
+            .filter_map(|(key, value)| {
+                if key == HMAC_KEY {
+                    hmac = Some(value);
+                    None
+                } else {
+                    Some((key, value))
+                }
+            })
+            .partition::<Table, _>(|(key, _)| CredentialsMeta::KEYS.contains(&key.as_str()));
+        let hmac = match hmac {
+            None => None,
+            Some(Value::String(hmac)) => Some(hmac),
+            Some(_) => {
+                let error = <toml::de::Error as serde::de::Error>::custom(format!(
+                    "`{HMAC_KEY}` must be a string."
+                ));
+                let credentials_path = credentials_path.to_owned();
+                return Err(Error::CredentialsFileDeserialize {
+                    credentials_path,
+                    profile_name: None,
+                    location: None,
+                    error,
+                });
+            }
+        };
+        let meta = Value::Table(meta_table)
+            .try_into::<CredentialsMeta>()
+            .map_err(|error| {
+                let credentials_path = credentials_path.to_owned();
+                Error::CredentialsFileDeserialize {
+                    credentials_path,
+                    profile_name: None,
+                    location: None,
+                    error,
+                }
+            })?;
+
+        Ok(Self {
+            meta,
+            profiles,
+            hmac,
+            version_source,
+            source,
+        })
+    }
+
+    /// Returns the contents to write to the credentials file.
+    ///
+    /// The current version, HMAC, and metadata are written before the
+    /// profiles.
+    pub(crate) fn to_string_pretty(&self) -> Result<String, toml::ser::Error> {
+        let mut header = Table::new();
+        header.insert(
+            VERSION_KEY.to_string(),
+            Value::Integer(i64::from(CREDENTIALS_FILE_VERSION)),
+        );
+        if let Some(hmac) = self.hmac.as_ref() {
+            header.insert(HMAC_KEY.to_string(), Value::String(hmac.clone()));
+        }
+        if let Value::Table(meta) = Value::try_from(&self.meta)? {
+            header.extend(meta);
+        }
+
+        let mut contents = toml::ser::to_string_pretty(&header)?;
+        let profiles_contents = toml::ser::to_string_pretty(&self.profiles)?;
+        if !profiles_contents.is_empty() {
+            contents.push('\n');
+            contents.push_str(&profiles_contents);
+        }
+        Ok(contents)
+    }
+
+    /// Returns the version, metadata, and profiles serialized in a stable
+    /// form, which is signed to detect changes to the file.
+    ///
+    /// Keys are sorted, so that reordering profiles or fields does not change
+    /// the signed contents.
+    ///
+    /// # Parameters
+    ///
+    /// * `version`: Layout version of the file.
+    pub(crate) fn contents_signed(&self, version: u32) -> Result<String, toml::ser::Error> {
+        let mut contents = Table::new();
+        contents.insert(VERSION_KEY.to_string(), Value::Integer(i64::from(version)));
+        if let Value::Table(meta) = Value::try_from(&self.meta)? {
+            contents.extend(meta);
+        }
+        contents.extend(self.profiles.clone());
+
+        toml::ser::to_string(&Self::value_sorted(Value::Table(contents)))
+    }
+
+    /// Deserializes a single profile.
+    ///
+    /// Errors point at the profile's header in the file, as the TOML value
+    /// does not record where each field was read from.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile.
+    /// * `credentials`: TOML value of the profile's credentials.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn profile_deserialize<C>(
+        &self,
+        profile_name: &str,
+        credentials: Value,
+        credentials_path: &Path,
+    ) -> Result<Profile<C>, Error>
+    where
+        C: Clone + Eq + for<'de> Deserialize<'de>,
+    {
+        credentials
+            .try_into::<C>()
+            .map(|credentials| Profile::new(profile_name.to_string(), credentials))
+            .map_err(|error| self.profile_deserialize_error(profile_name, error, credentials_path))
+    }
+
+    /// Returns an error if the profile fails to be deserialized.
+    ///
+    /// Secret references are not resolved, and are treated as a password.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile.
+    /// * `credentials`: TOML value of the profile's credentials.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn profile_validate<C>(
+        &self,
+        profile_name: &str,
+        credentials: &Value,
+        credentials_path: &Path,
+    ) -> Result<(), Error>
+    where
+        C: Clone + Eq + for<'de> Deserialize<'de>,
+    {
+        let credentials = self.profile_resolve(profile_name, credentials, credentials_path)?;
+        let credentials = Self::secret_refs_cleared(credentials);
+
+        self.profile_deserialize::<C>(profile_name, credentials, credentials_path)
+            .map(|_profile| ())
+    }
+
+    /// Returns resolved credentials with their secret reference replaced by an
+    /// empty password, so that they can be deserialized without running it.
+    pub(crate) fn secret_refs_cleared(credentials: Value) -> Value {
+        match credentials {
+            Value::Table(table) if SECRET_REF_KEYS.iter().any(|key| table.contains_key(*key)) => {
+                let password = (PASSWORD_KEY.to_string(), Value::String(String::new()));
+                let table = table
+                    .into_iter()
+                    .filter(|(key, _)| !SECRET_REF_KEYS.contains(&key.as_str()))
+                    .chain(std::iter::once(password))
+                    .collect();
+                Value::Table(table)
+            }
+            credentials => credentials,
+        }
+    }
+
+    /// Returns a profile's credentials with the fields from its parent
+    /// profiles filled in.
+    ///
+    /// Fields in a profile replace the same fields in its parent. The
+    /// `inherits` key is not included in the returned value.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile.
+    /// * `credentials`: TOML value of the profile's credentials.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn profile_resolve(
+        &self,
+        profile_name: &str,
+        credentials: &Value,
+        credentials_path: &Path,
+    ) -> Result<Value, Error> {
+        let mut lineage = vec![(profile_name, credentials)];
+        let mut parent_name = self.parent_name(profile_name, credentials, credentials_path)?;
+        while let Some(parent_name_current) = parent_name {
+            if lineage.iter().any(|(name, _)| *name == parent_name_current) {
+                let profile_name = profile_name.to_string();
+                let credentials_path = credentials_path.to_owned();
+                return Err(Error::ProfileInheritanceCycle {
+                    profile_name,
+                    credentials_path,
+                });
+            }
+
+            let parent_credentials = self.profiles.get(parent_name_current).ok_or_else(|| {
+                let profile_name = lineage
+                    .last()
+                    .map(|(name, _)| name.to_string())
+                    .unwrap_or_default();
+                let parent_name = parent_name_current.to_string();
+                let credentials_path = credentials_path.to_owned();
+                Error::ProfileParentNotFound {
+                    profile_name,
+                    parent_name,
+                    credentials_path,
+                }
+            })?;
+            parent_name =
+                self.parent_name(parent_name_current, parent_credentials, credentials_path)?;
+            lineage.push((parent_name_current, parent_credentials));
+        }
+
+        // Apply fields from the furthest ancestor down to the profile itself.
+        let mut credentials_resolved = Value::Table(Table::new());
+        lineage.into_iter().rev().for_each(|(_, credentials)| {
+            Self::credentials_overlay(&mut credentials_resolved, credentials)
+        });
+
+        Ok(Self::inherits_strip(credentials_resolved))
+    }
+
+    /// Inserts or replaces a profile.
+    ///
+    /// A replaced profile keeps its position in the file. If the replaced
+    /// profile inherits from another profile, only the fields that differ
+    /// from the parent profile are written.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile`: Profile to insert.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn profile_insert<C>(
+        &mut self,
+        profile: &Profile<C>,
+        credentials_path: &Path,
+    ) -> Result<(), Error>
+    where
+        C: Clone + Eq + Serialize,
+    {
+        let credentials = Self::credentials_serialize(profile)?;
+        self.profile_insert_value(&profile.name, credentials, credentials_path)
+    }
+
+    /// Inserts a profile in place of one that reads its password from a
+    /// secret reference, writing the reference back instead of the password.
+    ///
+    /// A reference that the existing profile inherits is inherited again.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile`: Profile to insert.
+    /// * `credentials_existing`: TOML value of the existing profile.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn profile_insert_secret_retained<C>(
+        &mut self,
+        profile: &Profile<C>,
+        credentials_existing: &Value,
+        credentials_path: &Path,
+    ) -> Result<(), Error>
+    where
+        C: Clone + Eq + Serialize,
+    {
+        let mut credentials = Self::credentials_serialize(profile)?;
+        if let Value::Table(table) = &mut credentials {
+            table.remove(PASSWORD_KEY);
+            SECRET_REF_KEYS.iter().for_each(|key| {
+                if let Some(secret_ref) = credentials_existing.get(*key) {
+                    table.insert(key.to_string(), secret_ref.clone());
+                }
+            });
+        }
+        self.profile_insert_value(&profile.name, credentials, credentials_path)
+    }
+
+    /// Returns the profile's credentials as a TOML value.
+    fn credentials_serialize<C>(profile: &Profile<C>) -> Result<Value, Error>
+    where
+        C: Clone + Eq + Serialize,
+    {
+        Value::try_from(&profile.credentials).map_err(|error| {
+            let profile_names = vec![profile.name.clone()];
+            Error::CredentialsFileSerialize {
+                profile_names,
+                error,
+            }
+        })
+    }
+
+    /// Inserts or replaces a profile's TOML value, writing only the fields
+    /// that differ from its parent profile.
+    fn profile_insert_value(
+        &mut self,
+        profile_name: &str,
+        credentials: Value,
+        credentials_path: &Path,
+    ) -> Result<(), Error> {
+        Self::profile_name_ensure_unreserved(profile_name, credentials_path)?;
+        let parent_name = match self.profiles.get(profile_name) {
+            Some(credentials_existing) => {
+                self.parent_name(profile_name, credentials_existing, credentials_path)?
+            }
+            None => None,
+        };
+        let credentials = match parent_name {
+            Some(parent_name) => {
+                let parent_credentials = self.profiles.get(parent_name).ok_or_else(|| {
+                    let profile_name = profile_name.to_string();
+                    let parent_name = parent_name.to_string();
+                    let credentials_path = credentials_path.to_owned();
+                    Error::ProfileParentNotFound {
+                        profile_name,
+                        parent_name,
+                        credentials_path,
+                    }
+                })?;
+                let parent_resolved =
+                    self.profile_resolve(parent_name, parent_credentials, credentials_path)?;
+                Self::inherits_retain(credentials, parent_name, &parent_resolved)
+            }
+            None => credentials,
+        };
+
+        self.profiles.insert(profile_name.to_string(), credentials);
+
+        Ok(())
+    }
+
+    /// Removes a profile.
+    ///
+    /// Profiles that inherit from the removed profile take on its fields, so
+    /// that they resolve to the same credentials as before.
+    pub(crate) fn profile_remove(&mut self, profile_name: &str) {
+        let mut credentials_removed = None;
+        let profiles = std::mem::take(&mut self.profiles)
+            .into_iter()
+            .filter_map(|(name, credentials)| {
+                if name == profile_name {
+                    credentials_removed = Some(credentials);
+                    None
+                } else {
+                    Some((name, credentials))
+                }
+            })
+            .collect::<Table>();
+
+        let credentials_removed = match credentials_removed {
+            Some(credentials_removed) => credentials_removed,
+            None => {
+                self.profiles = profiles;
+                return;
+            }
+        };
+
+        self.profiles = profiles
+            .into_iter()
+            .map(|(name, credentials)| {
+                let inherits_removed = matches!(
+                    credentials.get(INHERITS_KEY),
+                    Some(Value::String(parent_name)) if parent_name == profile_name
+                );
+                if inherits_removed {
+                    let credentials = Self::inherits_strip(credentials);
+                    let mut credentials_spliced = credentials_removed.clone();
+                    Self::credentials_overlay(&mut credentials_spliced, &credentials);
+                    (name, credentials_spliced)
+                } else {
+                    (name, credentials)
+                }
+            })
+            .collect();
+        self.meta.profile_removed(profile_name);
+    }
+
+    /// Renames a profile, keeping its position in the file.
+    ///
+    /// Profiles that inherit from the renamed profile are updated to inherit
+    /// from the new name.
+    pub(crate) fn profile_rename(
+        &mut self,
+        profile_name: &str,
+        profile_name_new: &str,
+        credentials_path: &Path,
+    ) -> Result<(), Error> {
+        Self::profile_name_ensure_unreserved(profile_name_new, credentials_path)?;
+        self.profiles = std::mem::take(&mut self.profiles)
+            .into_iter()
+            .map(|(name, mut credentials)| {
+                if let Some(Value::String(parent_name)) = credentials.get_mut(INHERITS_KEY) {
+                    if parent_name == profile_name {
+                        *parent_name = profile_name_new.to_string();
+                    }
+                }
+
+                if name == profile_name {
+                    (profile_name_new.to_string(), credentials)
+                } else {
+                    (name, credentials)
+                }
+            })
+            .collect();
+        self.meta.profile_renamed(profile_name, profile_name_new);
+
+        Ok(())
+    }
+
+    /// Returns [`Error::ProfileNameReserved`] if the name is a top level key
+    /// of the file, which would be read back as something other than a
+    /// profile.
+    fn profile_name_ensure_unreserved(
+        profile_name: &str,
+        credentials_path: &Path,
+    ) -> Result<(), Error> {
+        let reserved = profile_name == VERSION_KEY
+            || profile_name == HMAC_KEY
+            || CredentialsMeta::KEYS.contains(&profile_name);
+        if reserved {
+            let profile_name = profile_name.to_string();
+            let credentials_path = credentials_path.to_owned();
+            Err(Error::ProfileNameReserved {
+                profile_name,
+                credentials_path,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the name of the profile that the given profile inherits from.
+    fn parent_name<'v>(
+        &self,
+        profile_name: &str,
+        credentials: &'v Value,
+        credentials_path: &Path,
+    ) -> Result<Option<&'v str>, Error> {
+        match credentials.get(INHERITS_KEY) {
+            None => Ok(None),
+            Some(Value::String(parent_name)) => Ok(Some(parent_name)),
+            Some(_) => {
+                let error = <toml::de::Error as serde::de::Error>::custom(format!(
+                    "`{INHERITS_KEY}` must be the name of a profile."
+                ));
+                Err(self.profile_deserialize_error(profile_name, error, credentials_path))
+            }
+        }
+    }
+
+    /// Returns a deserialization error that points at the profile's header.
+    fn profile_deserialize_error(
+        &self,
+        profile_name: &str,
+        error: toml::de::Error,
+        credentials_path: &Path,
+    ) -> Error {
+        let location = self.profile_location(profile_name);
+        let profile_name = Some(profile_name.to_string());
+        let credentials_path = credentials_path.to_owned();
+        let error = Self::error_masked(error);
+        Error::CredentialsFileDeserialize {
+            credentials_path,
+            profile_name,
+            location,
+            error,
+        }
+    }
+
+    /// Returns the location of the `[profile_name]` header in the source.
+    fn profile_location(&self, profile_name: &str) -> Option<SourceLocation> {
+        self.source
+            .lines()
+            .enumerate()
+            .find_map(|(line, line_text)| {
+                let header = line_text.trim_start();
+                let header_name = header
+                    .strip_prefix('[')
+                    .filter(|header| !header.starts_with('['))
+                    .and_then(|header| header.split(']').next())?
+                    .trim();
+                let header_name = header_name
+                    .strip_prefix('"')
+                    .and_then(|name| name.strip_suffix('"'))
+                    .or_else(|| {
+                        header_name
+                            .strip_prefix('\'')
+                            .and_then(|name| name.strip_suffix('\''))
+                    })
+                    .unwrap_or(header_name);
+
+                if header_name == profile_name {
+                    let column = line_text.len() - header.len();
+                    Self::source_location(&self.source, line, column)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Returns the location at the given zero based line and byte column.
+    ///
+    /// Values are masked, so that secrets are not shown in errors. Only the
+    /// key of a `key = value` line is shown, and lines within multi-line
+    /// strings are masked entirely.
+    fn source_location(source: &str, line: usize, column: usize) -> Option<SourceLocation> {
+        let line_text = source.lines().nth(line)?;
+        let column = line_text
+            .get(..column)
+            .map(|line_prefix| line_prefix.chars().count())
+            .unwrap_or(column);
+        let is_in_multiline_string = source
+            .lines()
+            .take(line)
+            .flat_map(|line_text| {
+                line_text
+                    .matches(MULTILINE_STRING_DELIMITERS[0])
+                    .chain(line_text.matches(MULTILINE_STRING_DELIMITERS[1]))
+            })
+            .count()
+            % 2
+            == 1;
+        let line_text = if is_in_multiline_string {
+            String::from(VALUE_MASK)
+        } else {
+            match line_text.split_once('=') {
+                Some((key, _)) if !key.trim_start().starts_with('#') => {
+                    format!("{}= {}", key, VALUE_MASK)
+                }
+                _ => line_text.to_string(),
+            }
+        };
+
+        Some(SourceLocation {
+            line: line + 1,
+            column: column + 1,
+            line_text,
+        })
+    }
+
+    /// Returns the error with values in its message masked.
+    ///
+    /// Deserialization errors such as `invalid type: string "secret", expected
+    /// a boolean` include the value, which may be a password.
+    fn error_masked(error: toml::de::Error) -> toml::de::Error {
+        let message = error.to_string();
+        let unexpected_masked = ["invalid type: ", "invalid value: "]
+            .iter()
+            .find_map(|prefix| {
+                let unexpected_begin = message.find(prefix)? + prefix.len();
+                let unexpected_end = message.rfind(", expected")?;
+                let unexpected = message.get(unexpected_begin..unexpected_end)?;
+                let unexpected_kind = unexpected
+                    .split(&['"', '`'][..])
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end();
+                Some(format!(
+                    "{}{}{}",
+                    &message[..unexpected_begin],
+                    unexpected_kind,
+                    &message[unexpected_end..]
+                ))
+            });
+        let char_masked = || {
+            [" in string: `", " escape value: `"]
+                .iter()
+                .find_map(|prefix| {
+                    let char_begin = message.find(prefix)? + prefix.len();
+                    let char_end = char_begin + message[char_begin..].find('`')?;
+                    Some(format!(
+                        "{}{}{}",
+                        &message[..char_begin],
+                        VALUE_MASK,
+                        &message[char_end..]
+                    ))
+                })
+        };
+
+        match unexpected_masked.or_else(char_masked) {
+            Some(message) => <toml::de::Error as serde::de::Error>::custom(message),
+            None => error,
+        }
+    }
+
+    /// Returns the value with the keys of every table sorted.
+    fn value_sorted(value: Value) -> Value {
+        match value {
+            Value::Table(table) => {
+                let mut entries = table.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+                Value::Table(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, Self::value_sorted(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(values) => {
+                Value::Array(values.into_iter().map(Self::value_sorted).collect())
+            }
+            value => value,
+        }
+    }
+
+    /// Replaces fields in the parent's credentials with those of the child.
+    ///
+    /// A password or secret reference in the child replaces any of these from
+    /// the parent, rather than being combined with them.
+    fn credentials_overlay(credentials_parent: &mut Value, credentials: &Value) {
+        let password_sourced = PASSWORD_SOURCE_KEYS
+            .iter()
+            .any(|key| credentials.get(*key).is_some());
+        if let (true, Value::Table(table_parent)) = (password_sourced, &mut *credentials_parent) {
+            PASSWORD_SOURCE_KEYS.iter().for_each(|key| {
+                table_parent.remove(*key);
+            });
+        }
+        Self::value_overlay(credentials_parent, credentials)
+    }
+
+    /// Replaces fields in `base` with those in `overlay`.
+    ///
+    /// Fields that exist in `base` keep their position.
+    fn value_overlay(base: &mut Value, overlay: &Value) {
+        match (base, overlay) {
+            (Value::Table(base), Value::Table(overlay)) => {
+                overlay.iter().for_each(|(key, value)| {
+                    base.insert(key.clone(), value.clone());
+                });
+            }
+            (base, overlay) => *base = overlay.clone(),
+        }
+    }
+
+    /// Returns the credentials without the `inherits` key.
+    fn inherits_strip(credentials: Value) -> Value {
+        match credentials {
+            Value::Table(table) => Value::Table(
+                table
+                    .into_iter()
+                    .filter(|(key, _)| key != INHERITS_KEY)
+                    .collect(),
+            ),
+            credentials => credentials,
+        }
+    }
+
+    /// Returns the credentials with only the fields that differ from the
+    /// parent profile, and the `inherits` key.
+    fn inherits_retain(credentials: Value, parent_name: &str, parent_resolved: &Value) -> Value {
+        match credentials {
+            Value::Table(table) => {
+                let inherits = (
+                    INHERITS_KEY.to_string(),
+                    Value::String(parent_name.to_string()),
+                );
+                let fields = table
+                    .into_iter()
+                    .filter(|(key, value)| parent_resolved.get(key) != Some(value));
+
+                Value::Table(std::iter::once(inherits).chain(fields).collect())
+            }
+            credentials => credentials,
+        }
+    }
+}
@@ -1,19 +1,17 @@
-use std::{marker::PhantomData, path::PathBuf};
+use std::path::PathBuf;
 
-use credent_fs_model::{AppName, Error};
-use credent_model::Credentials;
+use credent_fs_model::Error;
+
+use crate::CredentialsPathResolver;
 
 /// Name of the file used to store credentials.
 pub const CREDENTIALS_FILE_NAME: &str = "credentials";
 
 /// Returns the path to the credentials file.
 #[derive(Debug)]
-pub struct CredentialsFile<C = Credentials>(PhantomData<C>);
+pub struct CredentialsFile;
 
-impl<C> CredentialsFile<C>
-where
-    C: Clone + Eq,
-{
+impl CredentialsFile {
     /// Returns the path to the credentials in the user's configuration
     /// directory.
     ///
@@ -25,9 +23,34 @@ where
     /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>\credentials`
     /// * `Linux`: `$XDG_CONFIG_HOME` or `$HOME/.config/<app>/credentials`
     /// * `OS X`: `$HOME/Library/Application Support/<app>/credentials`
-    pub fn path(app_name: AppName<'_>) -> Result<PathBuf, Error<C>> {
-        dirs::config_dir()
-            .map(|config_dir| config_dir.join(*app_name).join(CREDENTIALS_FILE_NAME))
-            .ok_or(Error::UserConfigDirNotFound)
+    ///
+    /// This may be customized by passing in a [`CredentialsPathResolver`],
+    /// and is overridden by the `<APP>_CREDENTIALS_FILE` environment variable.
+    ///
+    /// # Parameters
+    ///
+    /// * `resolver`: Application name, or [`CredentialsPathResolver`].
+    pub fn path<'s, R>(resolver: R) -> Result<PathBuf, Error>
+    where
+        R: Into<CredentialsPathResolver<'s>>,
+    {
+        let resolver = resolver.into();
+        let credentials_path = resolver.resolve();
+
+        #[cfg(feature = "tracing")]
+        match &credentials_path {
+            Ok(credentials_path) => tracing::debug!(
+                app = %resolver.app_id(),
+                credentials_path = %credentials_path.display(),
+                "Resolved credentials file path."
+            ),
+            Err(error) => tracing::warn!(
+                app = %resolver.app_id(),
+                %error,
+                "Failed to resolve credentials file path."
+            ),
+        }
+
+        credentials_path
     }
 }
@@ -1,10 +1,21 @@
-use std::{marker::PhantomData, path::Path};
+use std::{
+    collections::BTreeSet,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
 
-use credent_fs_model::{AppName, Error};
+use credent_fs_model::{CredentialsMeta, Error, IntegrityKey, LayeredProfiles, LenientProfiles};
 use credent_model::{Credentials, Profile, Profiles};
 use serde::Deserialize;
+use toml::{value::Table, Value};
 
-use crate::CredentialsFile;
+use crate::{
+    credentials_audit_log::{AuditOperation, AuditTarget},
+    credentials_document::CredentialsDocument,
+    credentials_integrity::CredentialsIntegrity,
+    credentials_secret_resolver::CredentialsSecretResolver,
+    CredentialsAuditLog, CredentialsLayer, CredentialsLayers, CredentialsTarget,
+};
 
 /// Reads credentials from the user's configuration directory.
 #[derive(Debug)]
@@ -17,6 +28,12 @@ where
     /// Returns the default profile credentials stored in the user's
     /// configuration directory.
     ///
+    /// A profile's `password_command` is run, or `password_file` is read, to
+    /// obtain its password.
+    ///
+    /// If the file has a `default_profile` key, the profile it names is
+    /// loaded instead of the `"default"` profile.
+    ///
     /// The path differs depending on the user's operating system:
     ///
     /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>\credentials`
@@ -25,11 +42,20 @@ where
     ///
     /// # Parameters
     ///
-    /// * `app_name`: Name of the application whose credentials to load.
-    pub async fn load(app_name: AppName<'_>) -> Result<Option<Profile<C>>, Error<C>> {
-        let credentials_path = CredentialsFile::<C>::path(app_name)?;
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load<'s, R>(target: R) -> Result<Option<Profile<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        let credentials_path = target.path()?;
         if credentials_path.exists() {
-            Self::load_profile(app_name, Profile::<C>::DEFAULT_NAME).await
+            Self::load_profile_with(&target, Profile::<C>::DEFAULT_NAME).await
         } else {
             Ok(None)
         }
@@ -38,6 +64,13 @@ where
     /// Returns the profile credentials stored in the user's configuration
     /// directory.
     ///
+    /// The profile's `password_command` is run, or `password_file` is read, to
+    /// obtain its password. Commands are refused for project-local files
+    /// unless allowed with
+    /// [`CredentialsFileOptions::with_secret_commands_allowed`].
+    ///
+    /// [`CredentialsFileOptions::with_secret_commands_allowed`]: crate::CredentialsFileOptions::with_secret_commands_allowed
+    ///
     /// The path differs depending on the user's operating system:
     ///
     /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>\credentials`
@@ -46,28 +79,70 @@ where
     ///
     /// # Parameters
     ///
-    /// * `app_name`: Name of the application whose credentials to load.
-    /// * `profile_name`: Which profile's credentials to load.
-    pub async fn load_profile(
-        app_name: AppName<'_>,
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    /// * `profile_name`: Which profile's credentials to load. This may be an
+    ///   alias, or `"default"` to load the file's default profile.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name),
+            err(Display)
+        )
+    )]
+    pub async fn load_profile<'s, R>(
+        target: R,
         profile_name: &str,
-    ) -> Result<Option<Profile<C>>, Error<C>> {
-        Self::load_all(app_name)
-            .await
-            .map(|profiles_result| {
-                profiles_result.map(|profiles| {
-                    profiles
-                        .0
-                        .into_iter()
-                        .find(|profile| profile.name == profile_name)
-                })
-            })
-            .map(Option::flatten)
+    ) -> Result<Option<Profile<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        Self::load_profile_with(&target.into(), profile_name).await
+    }
+
+    /// Returns the profile credentials without a span of its own, so that
+    /// errors are recorded once by the caller's span.
+    async fn load_profile_with(
+        target: &CredentialsTarget<'_>,
+        profile_name: &str,
+    ) -> Result<Option<Profile<C>>, Error> {
+        CredentialsAuditLog::audited(target, AuditOperation::Load, &[profile_name], async {
+            let credentials_path = target.path()?;
+            if credentials_path.exists() {
+                let document =
+                    Self::document_load(&credentials_path, target.options().integrity_key())
+                        .await?;
+                let profile_name = document.meta.profile_name_resolve(profile_name)?;
+                match document.profiles.get(profile_name) {
+                    Some(credentials) => Self::document_profile(
+                        &document,
+                        profile_name,
+                        credentials,
+                        &credentials_path,
+                        target.secret_resolver(),
+                    )
+                    .await
+                    .map(Some),
+                    None => Ok(None),
+                }
+            } else {
+                Ok(None)
+            }
+        })
+        .await
     }
 
     /// Returns all profile credentials stored in the user's configuration
     /// directory.
     ///
+    /// Secret references are not resolved, and a profile with a
+    /// `password_command` or `password_file` is returned with an empty
+    /// password. Load it with [`load_profile`] to resolve its password.
+    ///
+    /// [`load_profile`]: Self::load_profile
+    ///
     /// The path differs depending on the user's operating system:
     ///
     /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>\credentials`
@@ -76,22 +151,427 @@ where
     ///
     /// # Parameters
     ///
-    /// * `app_name`: Name of the application whose credentials to load.
-    pub async fn load_all(app_name: AppName<'_>) -> Result<Option<Profiles<C>>, Error<C>> {
-        let credentials_path = CredentialsFile::<C>::path(app_name)?;
-        if credentials_path.exists() {
-            Self::load_file(credentials_path.as_ref()).await.map(Some)
-        } else {
-            Ok(None)
-        }
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load_all<'s, R>(target: R) -> Result<Option<Profiles<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Load, &[], async {
+            let credentials_path = target.path()?;
+            if credentials_path.exists() {
+                let document =
+                    Self::document_load(&credentials_path, target.options().integrity_key())
+                        .await?;
+                Self::document_profiles(&document, &credentials_path).map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Returns all profile credentials stored in the user's configuration
+    /// directory, skipping profiles that fail to be loaded.
+    ///
+    /// The errors for skipped profiles are returned alongside the profiles
+    /// that were loaded. Errors that affect the whole file, such as invalid
+    /// TOML syntax, are still returned as an `Err`.
+    ///
+    /// Secret references are not resolved, and a profile with a
+    /// `password_command` or `password_file` is returned with an empty
+    /// password, and its name is recorded in
+    /// [`LenientProfiles::secret_referenced`].
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load_all_lenient<'s, R>(target: R) -> Result<Option<LenientProfiles<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Load, &[], async {
+            let credentials_path = target.path()?;
+            if credentials_path.exists() {
+                let document =
+                    Self::document_load(&credentials_path, target.options().integrity_key())
+                        .await?;
+                Ok(Some(Self::document_profiles_lenient(
+                    &document,
+                    &credentials_path,
+                )))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Returns the profile credentials merged from the given layers, and the
+    /// path to the file it was read from.
+    ///
+    /// # Parameters
+    ///
+    /// * `layers`: Credentials files to load, from lowest to highest
+    ///   precedence.
+    /// * `profile_name`: Which profile's credentials to load.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name),
+            err(Display)
+        )
+    )]
+    pub async fn load_profile_layered(
+        layers: &CredentialsLayers,
+        profile_name: &str,
+    ) -> Result<Option<(Profile<C>, PathBuf)>, Error> {
+        let audit_target = AuditTarget::default();
+        CredentialsAuditLog::audited_with(
+            layers.audit_log(),
+            audit_target,
+            AuditOperation::Load,
+            &[profile_name],
+            async {
+                let layer_documents = Self::layer_documents_load(layers).await?;
+                let mut meta = CredentialsMeta::default();
+                layer_documents
+                    .iter()
+                    .for_each(|(_layer, document)| meta.merge(document.meta.clone()));
+                let profiles_layered = Self::profiles_layered(&layer_documents);
+
+                // Only the profile from the highest layer that has it is
+                // resolved, so that secret references in other layers are not
+                // run.
+                let profile_name = meta.profile_name_resolve(profile_name)?;
+                let layer_document = layer_documents.iter().rev().find_map(|(layer, document)| {
+                    document
+                        .profiles
+                        .get(profile_name)
+                        .map(|credentials| (layer, document, credentials))
+                });
+                match layer_document {
+                    Some((layer, document, credentials)) => Self::document_profile(
+                        &Self::document_layered(document, &profiles_layered),
+                        profile_name,
+                        credentials,
+                        &layer.path,
+                        layers.secret_resolver(layer),
+                    )
+                    .await
+                    .map(|profile| Some((profile, layer.path.clone()))),
+                    None => Ok(None),
+                }
+            },
+        )
+        .await
+    }
+
+    /// Returns all profile credentials merged from the given layers.
+    ///
+    /// Profiles in later layers replace profiles with the same name in earlier
+    /// layers, and so does the metadata such as the default profile. Layers
+    /// whose file does not exist are skipped.
+    ///
+    /// A profile may inherit from a profile in any layer, which is read from
+    /// the highest layer that has it.
+    ///
+    /// Secret references are not resolved, and a profile with a
+    /// `password_command` or `password_file` is returned with an empty
+    /// password -- use [`load_profile_layered`] to resolve its password.
+    ///
+    /// [`load_profile_layered`]: Self::load_profile_layered
+    ///
+    /// # Parameters
+    ///
+    /// * `layers`: Credentials files to load, from lowest to highest
+    ///   precedence.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load_layered(layers: &CredentialsLayers) -> Result<LayeredProfiles<C>, Error> {
+        let audit_target = AuditTarget::default();
+        CredentialsAuditLog::audited_with(
+            layers.audit_log(),
+            audit_target,
+            AuditOperation::Load,
+            &[],
+            async {
+                let layer_documents = Self::layer_documents_load(layers).await?;
+                let profiles_layered = Self::profiles_layered(&layer_documents);
+                let mut layered_profiles = LayeredProfiles::new();
+                for (layer, document) in layer_documents.iter() {
+                    // Profiles replaced by a later layer are not loaded, so that
+                    // they cannot fail the load.
+                    let document_layered = Self::document_layered(document, &profiles_layered);
+                    let profiles = document
+                        .profiles
+                        .iter()
+                        .filter(|(profile_name, credentials)| {
+                            profiles_layered.get(profile_name.as_str()) == Some(*credentials)
+                        })
+                        .map(|(profile_name, credentials)| {
+                            Self::document_profile_listed(
+                                &document_layered,
+                                profile_name,
+                                credentials,
+                                &layer.path,
+                            )
+                            .map(|(profile, _secret_referenced)| profile)
+                        })
+                        .collect::<Result<BTreeSet<_>, Error>>()
+                        .map(Profiles)?;
+                    layered_profiles.merge(profiles, &layer.path);
+                    layered_profiles.meta.merge(document.meta.clone());
+                }
+
+                Ok(layered_profiles)
+            },
+        )
+        .await
     }
 
     /// Loads all credential profiles from the given file.
     ///
+    /// Secret references are not resolved, and a profile with a
+    /// `password_command` or `password_file` is returned with an empty
+    /// password.
+    ///
     /// # Parameters
     ///
     /// * `credentials_path`: File to load credentials from.
-    pub async fn load_file(credentials_path: &Path) -> Result<Profiles<C>, Error<C>> {
+    /// * `integrity_key`: Key to verify the file's HMAC with, if any.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn load_file(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<Profiles<C>, Error> {
+        let audit_target = AuditTarget::file(credentials_path);
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            audit_target,
+            AuditOperation::Load,
+            &[],
+            async {
+                let document = Self::document_load(credentials_path, integrity_key).await?;
+                Self::document_profiles(&document, credentials_path)
+            },
+        )
+        .await
+    }
+
+    /// Loads all credential profiles from the given file, skipping profiles
+    /// that fail to be loaded.
+    ///
+    /// Secret references are not resolved, and a profile with a
+    /// `password_command` or `password_file` is returned with an empty
+    /// password, and its name is recorded in
+    /// [`LenientProfiles::secret_referenced`].
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: File to load credentials from.
+    /// * `integrity_key`: Key to verify the file's HMAC with, if any.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn load_file_lenient(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<LenientProfiles<C>, Error> {
+        let audit_target = AuditTarget::file(credentials_path);
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            audit_target,
+            AuditOperation::Load,
+            &[],
+            async {
+                let document = Self::document_load(credentials_path, integrity_key).await?;
+                Ok(Self::document_profiles_lenient(&document, credentials_path))
+            },
+        )
+        .await
+    }
+
+    /// Returns the metadata stored in the user's credentials file, such as the
+    /// default profile and profile aliases.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load_meta<'s, R>(target: R) -> Result<Option<CredentialsMeta>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Load, &[], async {
+            let credentials_path = target.path()?;
+            if credentials_path.exists() {
+                Self::document_load(&credentials_path, target.options().integrity_key())
+                    .await
+                    .map(|document| Some(document.meta))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Returns the names of the profiles stored in the user's credentials file.
+    ///
+    /// Profiles are not loaded, so this includes profiles that are invalid or
+    /// whose secret reference cannot be resolved. Aliases are not included.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to load, or
+    ///   [`CredentialsTarget`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn load_profile_names<'s, R>(target: R) -> Result<Option<BTreeSet<String>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Load, &[], async {
+            let credentials_path = target.path()?;
+            if credentials_path.exists() {
+                Self::document_load(&credentials_path, target.options().integrity_key())
+                    .await
+                    .map(|document| Some(document.profiles.keys().cloned().collect()))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+
+    /// Loads the metadata from the given file, such as the default profile and
+    /// profile aliases.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: File to load metadata from.
+    /// * `integrity_key`: Key to verify the file's HMAC with, if any.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn load_file_meta(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<CredentialsMeta, Error> {
+        let audit_target = AuditTarget::file(credentials_path);
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            audit_target,
+            AuditOperation::Load,
+            &[],
+            async {
+                Self::document_load(credentials_path, integrity_key)
+                    .await
+                    .map(|document| document.meta)
+            },
+        )
+        .await
+    }
+
+    /// Reads the files of the layers that exist, from lowest to highest
+    /// precedence.
+    async fn layer_documents_load(
+        layers: &CredentialsLayers,
+    ) -> Result<Vec<(&CredentialsLayer, CredentialsDocument)>, Error> {
+        let mut layer_documents = Vec::new();
+        for layer in layers {
+            if layer.path.exists() {
+                let document =
+                    Self::document_load(&layer.path, layer.integrity_key.as_ref()).await?;
+                layer_documents.push((layer, document));
+            }
+        }
+
+        Ok(layer_documents)
+    }
+
+    /// Returns each profile's credentials from the highest layer that has it.
+    fn profiles_layered(layer_documents: &[(&CredentialsLayer, CredentialsDocument)]) -> Table {
+        let mut profiles_layered = Table::new();
+        layer_documents.iter().for_each(|(_layer, document)| {
+            document
+                .profiles
+                .iter()
+                .for_each(|(profile_name, credentials)| {
+                    profiles_layered.insert(profile_name.clone(), credentials.clone());
+                })
+        });
+
+        profiles_layered
+    }
+
+    /// Returns the layer's document with the profiles of every layer, so that
+    /// a profile may inherit from a profile in another layer.
+    fn document_layered(
+        document: &CredentialsDocument,
+        profiles_layered: &Table,
+    ) -> CredentialsDocument {
+        let mut document_layered = document.clone();
+        document_layered.profiles = profiles_layered.clone();
+        document_layered
+    }
+
+    /// Reads the given file without deserializing its profiles.
+    ///
+    /// If an integrity key is given, the file's HMAC is verified.
+    pub(crate) async fn document_load(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<CredentialsDocument, Error> {
         if !credentials_path.exists() {
             let credentials_path = credentials_path.to_owned();
             Err(Error::CredentialsFileNonExistent { credentials_path })
@@ -99,28 +579,138 @@ where
             let credentials_path = credentials_path.to_owned();
             Err(Error::CredentialsFileIsDir { credentials_path })
         } else {
+            #[cfg(feature = "tracing")]
+            let started = std::time::Instant::now();
+
             let profiles_contents = Self::credentials_file_read(credentials_path).await?;
-            Self::credentials_deserialize(profiles_contents, credentials_path)
+            let document = CredentialsDocument::from_slice(&profiles_contents, credentials_path)?;
+            if let Some(integrity_key) = integrity_key {
+                CredentialsIntegrity::verify(&document, integrity_key, credentials_path).await?;
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                credentials_path = %credentials_path.display(),
+                profile_count = document.profiles.len(),
+                integrity_verified = integrity_key.is_some(),
+                elapsed = ?started.elapsed(),
+                "Read credentials file."
+            );
+
+            Ok(document)
         }
     }
 
-    async fn credentials_file_read(credentials_path: &Path) -> Result<Vec<u8>, Error<C>> {
-        async_fs::read(credentials_path).await.map_err(|error| {
-            let credentials_path = credentials_path.to_owned();
-            Error::CredentialsFileRead {
+    /// Returns all profiles in the document, with inherited fields resolved.
+    ///
+    /// Secret references are not resolved, and profiles that have one are
+    /// returned with an empty password.
+    pub(crate) fn document_profiles(
+        document: &CredentialsDocument,
+        credentials_path: &Path,
+    ) -> Result<Profiles<C>, Error> {
+        document
+            .profiles
+            .iter()
+            .map(|(profile_name, credentials)| {
+                Self::document_profile_listed(document, profile_name, credentials, credentials_path)
+                    .map(|(profile, _secret_referenced)| profile)
+            })
+            .collect::<Result<BTreeSet<_>, Error>>()
+            .map(Profiles)
+    }
+
+    /// Returns the profiles in the document that load successfully, and the
+    /// errors for those that do not.
+    ///
+    /// Secret references are not resolved, and profiles that have one are
+    /// returned with an empty password and recorded in `secret_referenced`.
+    fn document_profiles_lenient(
+        document: &CredentialsDocument,
+        credentials_path: &Path,
+    ) -> LenientProfiles<C> {
+        let mut lenient_profiles = LenientProfiles::new();
+        for (profile_name, credentials) in &document.profiles {
+            match Self::document_profile_listed(
+                document,
+                profile_name,
+                credentials,
                 credentials_path,
-                error,
+            ) {
+                Ok((profile, secret_referenced)) => {
+                    if secret_referenced {
+                        lenient_profiles
+                            .secret_referenced
+                            .insert(profile_name.clone());
+                    }
+                    lenient_profiles.profiles.insert(profile);
+                }
+                Err(error) => lenient_profiles.errors.push(error),
             }
-        })
+        }
+
+        lenient_profiles
+    }
+
+    /// Returns a profile in the document, with inherited fields and secret
+    /// references resolved.
+    pub(crate) async fn document_profile(
+        document: &CredentialsDocument,
+        profile_name: &str,
+        credentials: &Value,
+        credentials_path: &Path,
+        secret_resolver: CredentialsSecretResolver,
+    ) -> Result<Profile<C>, Error> {
+        let credentials = document.profile_resolve(profile_name, credentials, credentials_path)?;
+        let credentials = secret_resolver
+            .resolve(profile_name, credentials, credentials_path)
+            .await?;
+        document.profile_deserialize(profile_name, credentials, credentials_path)
+    }
+
+    /// Returns a profile in the document, with inherited fields resolved, or
+    /// [`Error::ProfileSecretUnresolved`] if it has a secret reference.
+    pub(crate) fn document_profile_unresolved(
+        document: &CredentialsDocument,
+        profile_name: &str,
+        credentials: &Value,
+        credentials_path: &Path,
+    ) -> Result<Profile<C>, Error> {
+        let credentials = document.profile_resolve(profile_name, credentials, credentials_path)?;
+        if CredentialsSecretResolver::is_secret_referenced(&credentials) {
+            let profile_name = profile_name.to_string();
+            let credentials_path = credentials_path.to_owned();
+            return Err(Error::ProfileSecretUnresolved {
+                profile_name,
+                credentials_path,
+            });
+        }
+        document.profile_deserialize(profile_name, credentials, credentials_path)
     }
 
-    fn credentials_deserialize(
-        profiles_contents: Vec<u8>,
+    /// Returns a profile in the document, with inherited fields resolved, and
+    /// whether it has a secret reference.
+    ///
+    /// A secret reference is not resolved, and the profile is returned with an
+    /// empty password instead.
+    fn document_profile_listed(
+        document: &CredentialsDocument,
+        profile_name: &str,
+        credentials: &Value,
         credentials_path: &Path,
-    ) -> Result<Profiles<C>, Error<C>> {
-        toml::from_slice(&profiles_contents).map_err(|error| {
+    ) -> Result<(Profile<C>, bool), Error> {
+        let credentials = document.profile_resolve(profile_name, credentials, credentials_path)?;
+        let secret_referenced = CredentialsSecretResolver::is_secret_referenced(&credentials);
+        let credentials = CredentialsDocument::secret_refs_cleared(credentials);
+        document
+            .profile_deserialize(profile_name, credentials, credentials_path)
+            .map(|profile| (profile, secret_referenced))
+    }
+
+    async fn credentials_file_read(credentials_path: &Path) -> Result<Vec<u8>, Error> {
+        async_fs::read(credentials_path).await.map_err(|error| {
             let credentials_path = credentials_path.to_owned();
-            Error::CredentialsFileDeserialize {
+            Error::CredentialsFileRead {
                 credentials_path,
                 error,
             }
@@ -133,10 +723,16 @@ mod tests {
     use std::io::Write;
 
     use credent_fs_model::Error;
-    use credent_model::{Credentials, Password, Profile, Profiles, Username};
+    use credent_model::{
+        Credentials, EmailUsernamePolicy, Password, Profile, Profiles, Username, ValidatedUsername,
+    };
     use tempfile::NamedTempFile;
 
     use super::CredentialsFileLoader;
+    use crate::{
+        model::AppName, CredentialsDir, CredentialsFileOptions, CredentialsLayer,
+        CredentialsLayers, CredentialsPathResolver, CredentialsTarget,
+    };
 
     #[cfg(feature = "base64")]
     const PROFILES_CONTENT: &str = r#"
@@ -165,7 +761,8 @@ mod tests {
         let mut file = NamedTempFile::new()?;
         write!(file, "{}", PROFILES_CONTENT)?;
 
-        let profiles: Profiles = smol::block_on(CredentialsFileLoader::load_file(file.path()))?;
+        let profiles: Profiles =
+            smol::block_on(CredentialsFileLoader::load_file(file.path(), None, None))?;
         let profile_default = profiles.get(Profile::<Credentials>::DEFAULT_NAME);
         let profile_other = profiles.get("profile_other");
 
@@ -187,6 +784,241 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn loads_layered_credentials_with_later_layers_overriding(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let system_path = tempdir.path().join("system");
+        let user_path = tempdir.path().join("user");
+        let project_path = tempdir.path().join("project");
+        std::fs::write(&system_path, PROFILES_CONTENT)?;
+        #[cfg(feature = "base64")]
+        std::fs::write(
+            &user_path,
+            "[profile_other]\nusername = 'them'\npassword = 'Y29kZQ=='\n",
+        )?;
+        #[cfg(not(feature = "base64"))]
+        std::fs::write(
+            &user_path,
+            "[profile_other]\nusername = 'them'\npassword = 'code'\n",
+        )?;
+
+        let layers = CredentialsLayers::new()
+            .with_layer(CredentialsLayer::read_only(&system_path))
+            .with_layer(CredentialsLayer::writable(&user_path))
+            .with_layer(CredentialsLayer::read_only(&project_path));
+
+        let layered_profiles =
+            smol::block_on(CredentialsFileLoader::<Credentials>::load_layered(&layers))?;
+
+        let profile_default_expected = Profile::new_default(Credentials {
+            username: Username(String::from("me")),
+            password: Password::new("secret"),
+        });
+        let profile_other_expected = Profile::new(
+            String::from("profile_other"),
+            Credentials {
+                username: Username(String::from("them")),
+                password: Password::new("code"),
+            },
+        );
+        assert_eq!(
+            Some(&profile_default_expected),
+            layered_profiles.get(Profile::<Credentials>::DEFAULT_NAME)
+        );
+        assert_eq!(
+            Some(system_path.as_path()),
+            layered_profiles.source(Profile::<Credentials>::DEFAULT_NAME)
+        );
+        assert_eq!(
+            Some(&profile_other_expected),
+            layered_profiles.get("profile_other")
+        );
+        assert_eq!(
+            Some(user_path.as_path()),
+            layered_profiles.source("profile_other")
+        );
+
+        let profile_other = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_profile_layered(&layers, "profile_other"),
+        )?;
+        assert_eq!(Some((profile_other_expected, user_path)), profile_other);
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_layered_credentials_inheriting_from_other_layer(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let system_path = tempdir.path().join("system");
+        let user_path = tempdir.path().join("user");
+        std::fs::write(&system_path, PROFILES_CONTENT)?;
+        std::fs::write(
+            &user_path,
+            "[work]\ninherits = 'default'\nusername = 'you'\n",
+        )?;
+
+        let layers = CredentialsLayers::new()
+            .with_layer(CredentialsLayer::read_only(&system_path))
+            .with_layer(CredentialsLayer::writable(&user_path));
+
+        let layered_profiles =
+            smol::block_on(CredentialsFileLoader::<Credentials>::load_layered(&layers))?;
+        let profile_work = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_profile_layered(&layers, "work"),
+        )?;
+
+        let profile_work_expected = Profile::new(
+            String::from("work"),
+            Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("secret"),
+            },
+        );
+        assert_eq!(Some(&profile_work_expected), layered_profiles.get("work"));
+        assert_eq!(Some(user_path.as_path()), layered_profiles.source("work"));
+        assert_eq!(Some((profile_work_expected, user_path)), profile_work);
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_profile_names_includes_invalid_profiles() -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        std::fs::write(
+            tempdir.path().join("credentials"),
+            "[default]\nusername = 'me'\npassword_command = 'false'\n\n\
+             [broken]\nusername = 1\n\n\
+             [orphan]\ninherits = 'missing'\n",
+        )?;
+        let resolver = CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>);
+
+        let profile_names = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_profile_names(resolver),
+        )?;
+
+        assert_eq!(
+            Some(vec!["broken", "default", "orphan"]),
+            profile_names
+                .as_ref()
+                .map(|profile_names| profile_names.iter().map(String::as_str).collect())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_default_profile_and_aliases_from_meta() -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let credentials_path = tempdir.path().join("credentials");
+        let profiles_content = format!(
+            "default_profile = 'profile_other'\n\n[aliases]\nother = 'profile_other'\n{}",
+            PROFILES_CONTENT
+        );
+        std::fs::write(&credentials_path, profiles_content)?;
+        let resolver = CredentialsPathResolver::new(AppName::new("credent_test"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>);
+
+        let profile_default =
+            smol::block_on(CredentialsFileLoader::<Credentials>::load(resolver.clone()))?;
+        let profile_alias = smol::block_on(CredentialsFileLoader::<Credentials>::load_profile(
+            resolver, "other",
+        ))?;
+
+        let profile_other_expected = Profile::new(
+            String::from("profile_other"),
+            Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("code"),
+            },
+        );
+        assert_eq!(Some(&profile_other_expected), profile_default.as_ref());
+        assert_eq!(Some(profile_other_expected), profile_alias);
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_credentials_with_inherited_fields() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "{}", PROFILES_CONTENT)?;
+        write!(
+            file,
+            "\n[profile_child]\ninherits = 'profile_other'\nusername = 'them'\n"
+        )?;
+
+        let profiles: Profiles =
+            smol::block_on(CredentialsFileLoader::load_file(file.path(), None, None))?;
+
+        let profile_child_expected = Profile::new(
+            String::from("profile_child"),
+            Credentials {
+                username: Username(String::from("them")),
+                password: Password::new("code"),
+            },
+        );
+        assert_eq!(Some(&profile_child_expected), profiles.get("profile_child"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_credentials_with_normalized_usernames() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        #[cfg(feature = "base64")]
+        write!(
+            file,
+            "[default]\nusername = ' Me@Example.com '\npassword = 'c2VjcmV0'\n"
+        )?;
+        #[cfg(not(feature = "base64"))]
+        write!(
+            file,
+            "[default]\nusername = ' Me@Example.com '\npassword = 'secret'\n"
+        )?;
+
+        let profiles = smol::block_on(CredentialsFileLoader::<
+            Credentials<ValidatedUsername<EmailUsernamePolicy>>,
+        >::load_file(file.path(), None, None))?;
+
+        let username = profiles
+            .get(Profile::<Credentials>::DEFAULT_NAME)
+            .map(|profile| profile.credentials.username.as_str());
+        assert_eq!(Some("me@example.com"), username);
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_inheritance_cycle_when_profiles_inherit_from_each_other(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "[profile_a]\ninherits = 'profile_b'\n\n[profile_b]\ninherits = 'profile_a'\n"
+        )?;
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            file.path(),
+            None,
+            None,
+        ));
+
+        if let Err(Error::ProfileInheritanceCycle { profile_name, .. }) = &load_result {
+            assert_eq!("profile_a", profile_name);
+        } else {
+            panic!(
+                "Expected `load_result` to return `ProfileInheritanceCycle` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn returns_err_file_non_existent_when_file_not_exist() -> Result<(), Box<dyn std::error::Error>>
     {
@@ -194,7 +1026,9 @@ mod tests {
         let path = file.path().to_path_buf();
         file.close()?;
 
-        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(&path));
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            &path, None, None,
+        ));
 
         if let Err(Error::CredentialsFileNonExistent { credentials_path }) = &load_result {
             assert_eq!(&path, credentials_path);
@@ -210,7 +1044,9 @@ mod tests {
         let temp_dir = tempfile::tempdir()?;
         let path = temp_dir.path();
 
-        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(path));
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            path, None, None,
+        ));
 
         if let Err(Error::CredentialsFileIsDir { credentials_path }) = &load_result {
             assert_eq!(path, credentials_path);
@@ -230,14 +1066,184 @@ mod tests {
         write!(file, "garbage")?;
         let path = file.path();
 
-        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(path));
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            path, None, None,
+        ));
 
         if let Err(Error::CredentialsFileDeserialize {
             credentials_path,
+            profile_name: None,
+            location: Some(location),
             error: _,
         }) = &load_result
         {
             assert_eq!(path, credentials_path);
+            assert_eq!(1, location.line);
+            assert_eq!("garbage", location.line_text);
+        } else {
+            panic!(
+                "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_deserialize_pointing_at_profile() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "[default]\nusername = 'me'\npassword = 'c2VjcmV0'\n\n  [prod]\nusername = 'me'\n"
+        )?;
+        let path = file.path();
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            path, None, None,
+        ));
+
+        if let Err(
+            error @ Error::CredentialsFileDeserialize {
+                profile_name: Some(profile_name),
+                location: Some(location),
+                ..
+            },
+        ) = &load_result
+        {
+            assert_eq!("prod", profile_name);
+            assert_eq!((5, 3), (location.line, location.column));
+            assert!(error.to_string().ends_with(&format!(
+                "\n --> {}:5:3\n  |\n5 |   [prod]\n  |   ^",
+                path.display()
+            )));
+        } else {
+            panic!(
+                "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_deserialize_with_password_masked() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "[default]\nusername = 'me'\npassword = 'c2VjcmV0\n")?;
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            file.path(),
+            None,
+            None,
+        ));
+
+        if let Err(Error::CredentialsFileDeserialize {
+            location: Some(location),
+            ..
+        }) = &load_result
+        {
+            assert_eq!(3, location.line);
+            assert_eq!("password = ******", location.line_text);
+        } else {
+            panic!(
+                "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_deserialize_with_values_masked() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "[default]\nusername = 'hunter2\npassword = 'c2VjcmV0'\n"
+        )?;
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            file.path(),
+            None,
+            None,
+        ));
+
+        if let Err(
+            error @ Error::CredentialsFileDeserialize {
+                location: Some(location),
+                ..
+            },
+        ) = &load_result
+        {
+            assert_eq!("username = ******", location.line_text);
+            assert!(!error.to_string().contains("hunter2"));
+        } else {
+            panic!(
+                "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_deserialize_with_multiline_string_masked(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "[default]\nusername = 'me'\npassword = \"\"\"\nhunter2\\q\n\"\"\"\n"
+        )?;
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            file.path(),
+            None,
+            None,
+        ));
+
+        if let Err(
+            error @ Error::CredentialsFileDeserialize {
+                location: Some(location),
+                ..
+            },
+        ) = &load_result
+        {
+            assert_eq!(4, location.line);
+            assert_eq!("******", location.line_text);
+            assert!(!error.to_string().contains("hunter2"));
+            assert!(!error.to_string().contains("`q`"));
+        } else {
+            panic!(
+                "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_deserialize_with_unexpected_value_masked(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "[default]\nusername = 'me'\npassword = 1234567\n")?;
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            file.path(),
+            None,
+            None,
+        ));
+
+        if let Err(error @ Error::CredentialsFileDeserialize { .. }) = &load_result {
+            let message = error.to_string();
+            assert!(
+                message.contains("invalid type: integer, expected"),
+                "{}",
+                message
+            );
+            assert!(!message.contains("1234567"), "{}", message);
         } else {
             panic!(
                 "Expected `load_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
@@ -247,4 +1253,184 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn load_file_lenient_skips_invalid_profiles() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        #[cfg(feature = "base64")]
+        write!(
+            file,
+            "[default]\nusername = 'me'\npassword = 'c2VjcmV0'\n\n[broken]\nusername = 'me'\n"
+        )?;
+        #[cfg(not(feature = "base64"))]
+        write!(
+            file,
+            "[default]\nusername = 'me'\npassword = 'secret'\n\n[broken]\nusername = 'me'\n"
+        )?;
+
+        assert!(
+            smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+                file.path(),
+                None,
+                None,
+            ))
+            .is_err()
+        );
+
+        let lenient_profiles = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_file_lenient(file.path(), None, None),
+        )?;
+
+        let profile_default_expected = Profile::new_default(Credentials {
+            username: Username(String::from("me")),
+            password: Password::new("secret"),
+        });
+        assert_eq!(
+            Some(&profile_default_expected),
+            lenient_profiles.get("default")
+        );
+        assert_eq!(1, lenient_profiles.profiles.len());
+        assert!(!lenient_profiles.is_valid());
+        assert!(matches!(
+            lenient_profiles.errors.as_slice(),
+            [Error::CredentialsFileDeserialize {
+                profile_name: Some(profile_name),
+                ..
+            }] if profile_name == "broken"
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_secret_command_only_for_profile_loaded_by_name(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let marker_path = tempdir.path().join("marker");
+        let resolver = CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>);
+        std::fs::write(
+            tempdir.path().join("credentials"),
+            format!(
+                "[default]\nusername = 'me'\npassword_command = 'echo secret'\n\n\
+                [other]\nusername = 'you'\npassword_command = 'touch {}; echo code'\n",
+                marker_path.display()
+            ),
+        )?;
+
+        let lenient_profiles = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_all_lenient(resolver.clone()),
+        )?
+        .unwrap_or_default();
+        let profile_default = smol::block_on(CredentialsFileLoader::<Credentials>::load(resolver))?;
+
+        assert!(lenient_profiles.errors.is_empty());
+        assert_eq!(
+            vec![Password::new(""), Password::new("")],
+            lenient_profiles
+                .profiles
+                .iter()
+                .map(|profile| profile.credentials.password.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["default", "other"],
+            lenient_profiles
+                .secret_referenced
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(Password::new("secret")),
+            profile_default.map(|profile| profile.credentials.password)
+        );
+        assert!(!marker_path.exists());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn load_profile_layered_refuses_secret_command_in_read_only_layer(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let user_path = tempdir.path().join("user");
+        let project_path = tempdir.path().join("project");
+        std::fs::write(
+            &user_path,
+            "[default]\nusername = 'me'\npassword_command = 'echo secret'\n",
+        )?;
+        std::fs::write(
+            &project_path,
+            "[work]\nusername = 'you'\npassword_command = 'echo code'\n",
+        )?;
+
+        let layers = CredentialsLayers::new()
+            .with_layer(CredentialsLayer::writable(&user_path))
+            .with_layer(CredentialsLayer::read_only(&project_path));
+        let profile_default = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_profile_layered(&layers, "default"),
+        )?;
+        let profile_work_result = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_profile_layered(&layers, "work"),
+        );
+
+        assert_eq!(
+            Some((Password::new("secret"), user_path)),
+            profile_default.map(|(profile, path)| (profile.credentials.password, path))
+        );
+        assert!(matches!(
+            profile_work_result,
+            Err(Error::SecretCommandRefused { .. })
+        ));
+
+        let layers = CredentialsLayers::new().with_layer(
+            CredentialsLayer::read_only(&project_path).with_secret_commands_allowed(true),
+        );
+        let profile_work = smol::block_on(
+            CredentialsFileLoader::<Credentials>::load_profile_layered(&layers, "work"),
+        )?;
+        assert_eq!(
+            Some(Password::new("code")),
+            profile_work.map(|(profile, _path)| profile.credentials.password)
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn load_profile_refuses_secret_command_in_project_local_file(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        std::fs::create_dir(tempdir.path().join(".credent"))?;
+        std::fs::write(
+            tempdir.path().join(".credent").join("credentials"),
+            "[default]\nusername = 'me'\npassword_command = 'echo secret'\n",
+        )?;
+        let resolver = CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::ProjectLocal(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>);
+
+        let load_result =
+            smol::block_on(CredentialsFileLoader::<Credentials>::load(resolver.clone()));
+        let profile = smol::block_on(CredentialsFileLoader::<Credentials>::load(
+            CredentialsTarget::new(resolver)
+                .with_options(CredentialsFileOptions::new().with_secret_commands_allowed(true)),
+        ))?;
+
+        assert!(matches!(
+            load_result,
+            Err(Error::SecretCommandRefused { .. })
+        ));
+        assert_eq!(
+            Some(Password::new("secret")),
+            profile.map(|profile| profile.credentials.password)
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,343 @@
+use std::{marker::PhantomData, path::Path};
+
+use credent_fs_model::{CredentialsFileMigration, Error, IntegrityKey};
+use credent_model::Credentials;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    credentials_document::CredentialsDocument, credentials_migration::CredentialsMigration,
+    CredentialsFileLoader, CredentialsFileStorer, CredentialsTarget, CREDENTIALS_FILE_VERSION,
+};
+
+/// Upgrades credentials files to the current [`CREDENTIALS_FILE_VERSION`].
+///
+/// Older files are upgraded in memory whenever they are loaded, so migrating
+/// is only needed to write the upgraded layout back to the file.
+#[derive(Debug)]
+pub struct CredentialsFileMigrator<C = Credentials>(PhantomData<C>);
+
+impl<C> CredentialsFileMigrator<C>
+where
+    C: Clone + Eq + for<'de> Deserialize<'de> + Serialize,
+{
+    /// Upgrades the default application credentials file to the current
+    /// version.
+    ///
+    /// Returns `None` if the file does not exist. The file is not written to
+    /// if it is already at the current version.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to migrate, or
+    ///   [`CredentialsTarget`].
+    pub async fn migrate<'s, R>(target: R) -> Result<Option<CredentialsFileMigration>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        let credentials_path = target.path()?;
+        if credentials_path.exists() {
+            Self::migrate_file_with(&credentials_path, target.options().integrity_key())
+                .await
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the changes that migrating the default application credentials
+    /// file would make, without writing to it.
+    ///
+    /// Returns `None` if the file does not exist.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to migrate, or
+    ///   [`CredentialsTarget`].
+    pub async fn migrate_dry_run<'s, R>(
+        target: R,
+    ) -> Result<Option<CredentialsFileMigration>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        let credentials_path = target.path()?;
+        if credentials_path.exists() {
+            let document = CredentialsFileLoader::<C>::document_load(
+                &credentials_path,
+                target.options().integrity_key(),
+            )
+            .await?;
+            Self::document_migration(&document).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Upgrades the given file to the current version.
+    ///
+    /// The file is not written to if it is already at the current version.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: File to migrate.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    pub async fn migrate_file(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<CredentialsFileMigration, Error> {
+        Self::migrate_file_with(credentials_path, integrity_key).await
+    }
+
+    /// Returns the changes that migrating the given file would make, without
+    /// writing to it.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: File to migrate.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    pub async fn migrate_file_dry_run(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<CredentialsFileMigration, Error> {
+        let document =
+            CredentialsFileLoader::<C>::document_load(credentials_path, integrity_key).await?;
+        Self::document_migration(&document)
+    }
+
+    async fn migrate_file_with(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<CredentialsFileMigration, Error> {
+        let mut document =
+            CredentialsFileLoader::<C>::document_load(credentials_path, integrity_key).await?;
+        let migration = Self::document_migration(&document)?;
+        if migration.is_needed() {
+            CredentialsFileStorer::<C>::document_write(
+                &mut document,
+                credentials_path,
+                integrity_key,
+            )
+            .await?;
+        }
+
+        Ok(migration)
+    }
+
+    fn document_migration(
+        document: &CredentialsDocument,
+    ) -> Result<CredentialsFileMigration, Error> {
+        let contents_after = document.to_string_pretty().map_err(|error| {
+            let profile_names = document.profiles.keys().cloned().collect();
+            Error::CredentialsFileSerialize {
+                profile_names,
+                error,
+            }
+        })?;
+        let steps = CredentialsMigration::pending(document.version_source)
+            .map(|migration| migration.description.to_string())
+            .collect();
+
+        Ok(CredentialsFileMigration {
+            version_from: document.version_source,
+            version_to: CREDENTIALS_FILE_VERSION,
+            steps,
+            contents_before: document.source.clone(),
+            contents_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use credent_fs_model::Error;
+    use credent_model::Credentials;
+    use tempfile::NamedTempFile;
+
+    use super::CredentialsFileMigrator;
+    use crate::CredentialsFileLoader;
+
+    const PROFILES_CONTENT_V0: &str = "[default]\nusername = 'me'\npassword = 'c2VjcmV0'\n";
+
+    #[test]
+    fn migrate_file_dry_run_does_not_write_file() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "{}", PROFILES_CONTENT_V0)?;
+
+        let migration = smol::block_on(
+            CredentialsFileMigrator::<Credentials>::migrate_file_dry_run(file.path(), None),
+        )?;
+
+        assert!(migration.is_needed());
+        assert_eq!((0, 1), (migration.version_from, migration.version_to));
+        assert_eq!(
+            vec![String::from(
+                "Record the credentials file version. \
+                Profiles named `version`, `hmac`, `default_profile`, or `aliases` are renamed, \
+                and their names are kept as aliases."
+            )],
+            migration.steps
+        );
+        assert_eq!(PROFILES_CONTENT_V0, migration.contents_before);
+        assert_eq!(
+            format!("version = 1\n\n{}", PROFILES_CONTENT_V0),
+            migration.contents_after
+        );
+        assert_eq!(PROFILES_CONTENT_V0, std::fs::read_to_string(file.path())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_file_writes_current_version() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "{}", PROFILES_CONTENT_V0)?;
+
+        smol::block_on(CredentialsFileMigrator::<Credentials>::migrate_file(
+            file.path(),
+            None,
+        ))?;
+        let migration = smol::block_on(CredentialsFileMigrator::<Credentials>::migrate_file(
+            file.path(),
+            None,
+        ))?;
+
+        assert!(!migration.is_needed());
+        assert!(migration.steps.is_empty());
+        assert_eq!(
+            format!("version = 1\n\n{}", PROFILES_CONTENT_V0),
+            std::fs::read_to_string(file.path())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_file_renames_profiles_named_after_reserved_keys(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "{}\n[version]\nusername = 'v'\npassword = 'c2VjcmV0'\n\n\
+            [hmac]\nusername = 'h'\npassword = 'c2VjcmV0'\n",
+            PROFILES_CONTENT_V0
+        )?;
+
+        let migration = smol::block_on(CredentialsFileMigrator::<Credentials>::migrate_file(
+            file.path(),
+            None,
+        ))?;
+
+        assert_eq!(
+            format!(
+                "version = 1\n\n\
+                [aliases]\n\
+                hmac = 'hmac_profile'\n\
+                version = 'version_profile'\n\n\
+                {}\n\
+                [version_profile]\nusername = 'v'\npassword = 'c2VjcmV0'\n\n\
+                [hmac_profile]\nusername = 'h'\npassword = 'c2VjcmV0'\n",
+                PROFILES_CONTENT_V0
+            ),
+            migration.contents_after
+        );
+        let meta = smol::block_on(CredentialsFileLoader::<Credentials>::load_file_meta(
+            file.path(),
+            None,
+            None,
+        ))?;
+        assert_eq!("version_profile", meta.profile_name_resolve("version")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_file_renames_profiles_named_after_meta_keys(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "[aliases]\nusername = 'a'\npassword = 'c2VjcmV0'\n\n\
+            [default_profile]\nusername = 'd'\npassword = 'c2VjcmV0'\n",
+        )?;
+
+        let migration = smol::block_on(CredentialsFileMigrator::<Credentials>::migrate_file(
+            file.path(),
+            None,
+        ))?;
+
+        assert_eq!(
+            "version = 1\n\n\
+            [aliases]\n\
+            aliases = 'aliases_profile'\n\
+            default_profile = 'default_profile_profile'\n\n\
+            [aliases_profile]\nusername = 'a'\npassword = 'c2VjcmV0'\n\n\
+            [default_profile_profile]\nusername = 'd'\npassword = 'c2VjcmV0'\n",
+            migration.contents_after
+        );
+        let meta = smol::block_on(CredentialsFileLoader::<Credentials>::load_file_meta(
+            file.path(),
+            None,
+            None,
+        ))?;
+        assert_eq!("aliases_profile", meta.profile_name_resolve("aliases")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_file_keeps_aliases_meta() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(
+            file,
+            "[aliases]\nprod = 'default'\n\n{}",
+            PROFILES_CONTENT_V0
+        )?;
+
+        let migration = smol::block_on(
+            CredentialsFileMigrator::<Credentials>::migrate_file_dry_run(file.path(), None),
+        )?;
+
+        assert_eq!(
+            format!(
+                "version = 1\n\n[aliases]\nprod = 'default'\n\n{}",
+                PROFILES_CONTENT_V0
+            ),
+            migration.contents_after
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_file_returns_err_when_version_unsupported() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = NamedTempFile::new()?;
+        write!(file, "version = 99\n\n{}", PROFILES_CONTENT_V0)?;
+
+        let load_result = smol::block_on(CredentialsFileLoader::<Credentials>::load_file(
+            file.path(),
+            None,
+            None,
+        ));
+
+        if let Err(Error::CredentialsFileVersionUnsupported {
+            version,
+            version_supported,
+            ..
+        }) = &load_result
+        {
+            assert_eq!((99, 1), (*version, *version_supported));
+        } else {
+            panic!(
+                "Expected `load_result` to return `CredentialsFileVersionUnsupported` error, but got `{:?}`.",
+                load_result
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use credent_fs_model::IntegrityKey;
+
+use crate::{CredentialsAuditLog, SECRET_COMMAND_TIMEOUT};
+
+/// Options for accessing a credentials file, apart from where it is.
+///
+/// These are passed alongside a [`CredentialsPathResolver`] in a
+/// [`CredentialsTarget`].
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use credent_fs::{CredentialsFileOptions, CredentialsTarget};
+///
+/// let target = CredentialsTarget::new(AppName::new("credent")).with_options(
+///     CredentialsFileOptions::new()
+///         .with_integrity_key(IntegrityKey::Secret(secret))
+///         .with_secret_command_timeout(Duration::from_secs(5)),
+/// );
+/// let profile = CredentialsFileLoader::<Credentials>::load(target).await?;
+/// ```
+///
+/// [`CredentialsPathResolver`]: crate::CredentialsPathResolver
+/// [`CredentialsTarget`]: crate::CredentialsTarget
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsFileOptions {
+    /// Secret that the credentials file is signed with.
+    integrity_key: Option<IntegrityKey>,
+    /// Log that access to the credentials file is recorded in.
+    audit_log: Option<CredentialsAuditLog>,
+    /// Whether profiles' `password_command`s may be run, `None` to decide by
+    /// the directory.
+    secret_commands_allowed: Option<bool>,
+    /// Maximum duration to wait for a `password_command` to complete.
+    secret_command_timeout: Duration,
+}
+
+impl CredentialsFileOptions {
+    /// Returns a new `CredentialsFileOptions` with the default settings.
+    pub fn new() -> Self {
+        Self {
+            integrity_key: None,
+            audit_log: None,
+            secret_commands_allowed: None,
+            secret_command_timeout: SECRET_COMMAND_TIMEOUT,
+        }
+    }
+
+    /// Sets the secret that the credentials file is signed with.
+    ///
+    /// When set, loading the file returns
+    /// [`Error::CredentialsFileIntegrityMismatch`] if the file was changed
+    /// without this key, and storing to the file signs it with this key.
+    ///
+    /// Use [`CredentialsFileStorer::sign`] to sign an existing file.
+    ///
+    /// [`Error::CredentialsFileIntegrityMismatch`]: credent_fs_model::Error::CredentialsFileIntegrityMismatch
+    /// [`CredentialsFileStorer::sign`]: crate::CredentialsFileStorer::sign
+    pub fn with_integrity_key(mut self, integrity_key: IntegrityKey) -> Self {
+        self.integrity_key = Some(integrity_key);
+        self
+    }
+
+    /// Sets the log that access to the credentials file is recorded in.
+    ///
+    /// When set, each load, store, remove, and rename through the
+    /// [`CredentialsFileLoader`] and [`CredentialsFileStorer`] functions that
+    /// take a [`CredentialsTarget`] is recorded in the log.
+    ///
+    /// [`CredentialsFileLoader`]: crate::CredentialsFileLoader
+    /// [`CredentialsFileStorer`]: crate::CredentialsFileStorer
+    /// [`CredentialsTarget`]: crate::CredentialsTarget
+    pub fn with_audit_log(mut self, audit_log: CredentialsAuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Sets whether profiles' `password_command`s may be run.
+    ///
+    /// Defaults to `true`, except for [`CredentialsDir::ProjectLocal`], where a
+    /// checked out repository could otherwise run arbitrary commands.
+    ///
+    /// [`CredentialsDir::ProjectLocal`]: crate::CredentialsDir::ProjectLocal
+    pub fn with_secret_commands_allowed(mut self, secret_commands_allowed: bool) -> Self {
+        self.secret_commands_allowed = Some(secret_commands_allowed);
+        self
+    }
+
+    /// Sets the maximum duration to wait for a `password_command` to complete.
+    ///
+    /// Defaults to [`SECRET_COMMAND_TIMEOUT`].
+    pub fn with_secret_command_timeout(mut self, secret_command_timeout: Duration) -> Self {
+        self.secret_command_timeout = secret_command_timeout;
+        self
+    }
+
+    /// Returns the secret that the credentials file is signed with.
+    pub fn integrity_key(&self) -> Option<&IntegrityKey> {
+        self.integrity_key.as_ref()
+    }
+
+    /// Returns the log that access to the credentials file is recorded in.
+    pub fn audit_log(&self) -> Option<&CredentialsAuditLog> {
+        self.audit_log.as_ref()
+    }
+
+    /// Returns whether profiles' `password_command`s may be run, or `None` if
+    /// this is decided by the directory.
+    pub fn secret_commands_allowed(&self) -> Option<bool> {
+        self.secret_commands_allowed
+    }
+
+    /// Returns the maximum duration to wait for a `password_command` to
+    /// complete.
+    pub fn secret_command_timeout(&self) -> Duration {
+        self.secret_command_timeout
+    }
+}
+
+impl Default for CredentialsFileOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,10 +1,19 @@
 use std::{marker::PhantomData, path::Path};
 
-use credent_fs_model::{AppName, Error};
+use credent_fs_model::{Error, IntegrityKey};
 use credent_model::{Credentials, Profile, Profiles};
 use serde::{Deserialize, Serialize};
+use toml::{value::Table, Value};
 
-use crate::{CredentialsFile, CredentialsFileLoader};
+use crate::{
+    cached_credentials_loader::CachedCredentialsLoader,
+    credentials_audit_log::{AuditOperation, AuditTarget},
+    credentials_document::CredentialsDocument,
+    credentials_integrity::CredentialsIntegrity,
+    credentials_secret_resolver::CredentialsSecretResolver,
+    CredentialsAuditLog, CredentialsFileLoader, CredentialsLayer, CredentialsLayers,
+    CredentialsTarget,
+};
 
 /// Writes credentials to the user's configuration directory.
 #[derive(Debug)]
@@ -16,16 +25,81 @@ where
 {
     /// Stores a `Profile` in the default application credentials file.
     ///
-    /// This replaces the profile's credentials in the file.
+    /// This replaces the profile's credentials in the file. If the profile in
+    /// the file reads its password from a `password_command` or
+    /// `password_file`, the reference is kept and the profile's password is
+    /// not written.
     ///
     /// The path differs depending on the user's operating system:
     ///
     /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>\credentials`
     /// * `Linux`: `$XDG_CONFIG_HOME` or `$HOME/.config/<app>/credentials`
     /// * `OS X`: `$HOME/Library/Application Support/<app>/credentials`
-    pub async fn store(app_name: AppName<'_>, profile: &Profile<C>) -> Result<(), Error<C>> {
-        let credentials_path = CredentialsFile::path(app_name)?;
-        Self::store_file(profile, credentials_path.as_ref()).await
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to store, or
+    ///   [`CredentialsTarget`].
+    /// * `profile`: Profile to store.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile.name),
+            err(Display)
+        )
+    )]
+    pub async fn store<'s, R>(target: R, profile: &Profile<C>) -> Result<(), Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Store, &[&profile.name], async {
+            let credentials_path = target.path()?;
+            let integrity_key = target.options().integrity_key();
+            Self::store_file_with(profile, &credentials_path, integrity_key, false).await
+        })
+        .await
+    }
+
+    /// Stores a `Profile` in the default application credentials file, even if
+    /// the existing profile is invalid.
+    ///
+    /// [`store`] refuses to overwrite a profile that fails to be loaded, so
+    /// that a hand edited profile is not lost. This overwrites it.
+    ///
+    /// Returns [`Error::ProfileSecretReferenced`] if the profile in the file
+    /// reads its password from a `password_command` or `password_file`, which
+    /// this would replace. Remove the profile first to replace the reference.
+    ///
+    /// [`store`]: Self::store
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to store, or
+    ///   [`CredentialsTarget`].
+    /// * `profile`: Profile to store.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile.name),
+            err(Display)
+        )
+    )]
+    pub async fn store_force<'s, R>(target: R, profile: &Profile<C>) -> Result<(), Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Store, &[&profile.name], async {
+            let credentials_path = target.path()?;
+            let integrity_key = target.options().integrity_key();
+            Self::store_file_with(profile, &credentials_path, integrity_key, true).await
+        })
+        .await
     }
 
     /// Stores multiple `Profile`s in the default application credentials file.
@@ -38,34 +112,349 @@ where
     /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>\credentials`
     /// * `Linux`: `$XDG_CONFIG_HOME` or `$HOME/.config/<app>/credentials`
     /// * `OS X`: `$HOME/Library/Application Support/<app>/credentials`
-    pub async fn store_many(app_name: AppName<'_>, profiles: Profiles<C>) -> Result<(), Error<C>> {
-        let credentials_path = CredentialsFile::path(app_name)?;
-        Self::store_many_file(profiles, credentials_path.as_ref()).await
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to store, or
+    ///   [`CredentialsTarget`].
+    /// * `profiles`: Profiles to store.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_count = profiles.len()),
+            err(Display)
+        )
+    )]
+    pub async fn store_many<'s, R>(target: R, profiles: Profiles<C>) -> Result<(), Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        let profile_names = profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect::<Vec<String>>();
+        let profile_names = profile_names.iter().map(String::as_str).collect::<Vec<_>>();
+        CredentialsAuditLog::audited(&target, AuditOperation::Store, &profile_names, async {
+            let credentials_path = target.path()?;
+            let integrity_key = target.options().integrity_key();
+            Self::store_many_file_with(profiles, &credentials_path, integrity_key).await
+        })
+        .await
     }
 
-    /// Stores a `Profile` in the given file.
+    /// Removes a `Profile` from the default application credentials file.
     ///
-    /// This replaces the profile's credentials in the file.
+    /// Returns the removed profile, or `None` if the profile does not exist or
+    /// fails to load. A profile that fails to load is removed all the same.
     ///
     /// # Parameters
     ///
-    /// * `profile`: Profile to store.
-    /// * `credentials_path`: File to write credentials to.
-    pub async fn store_file(profile: &Profile<C>, credentials_path: &Path) -> Result<(), Error<C>> {
-        let profiles_existing = Self::profiles_existing(credentials_path).await?;
-        let mut profiles = profiles_existing.unwrap_or_else(Profiles::<C>::new);
+    /// * `target`: Name of the application whose credentials to remove, or
+    ///   [`CredentialsTarget`].
+    /// * `profile_name`: Name of the profile to remove.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name),
+            err(Display)
+        )
+    )]
+    pub async fn remove<'s, R>(target: R, profile_name: &str) -> Result<Option<Profile<C>>, Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Remove, &[profile_name], async {
+            let credentials_path = target.path()?;
+            let integrity_key = target.options().integrity_key();
+            Self::remove_file_with(profile_name, &credentials_path, integrity_key).await
+        })
+        .await
+    }
+
+    /// Renames a `Profile` in the default application credentials file.
+    ///
+    /// Renaming a profile to its own name does not change the file.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to rename, or
+    ///   [`CredentialsTarget`].
+    /// * `profile_name`: Name of the profile to rename.
+    /// * `profile_name_new`: New name of the profile.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name, profile_name_new = %profile_name_new),
+            err(Display)
+        )
+    )]
+    pub async fn rename<'s, R>(
+        target: R,
+        profile_name: &str,
+        profile_name_new: &str,
+    ) -> Result<(), Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(
+            &target,
+            AuditOperation::Rename {
+                profile_new: profile_name_new,
+            },
+            &[profile_name],
+            async {
+                let credentials_path = target.path()?;
+                let integrity_key = target.options().integrity_key();
+                Self::rename_file_with(
+                    profile_name,
+                    profile_name_new,
+                    &credentials_path,
+                    integrity_key,
+                )
+                .await
+            },
+        )
+        .await
+    }
 
-        // [`BTreeSet::insert`] does not replace the value if the `Ordering` is the
-        // same, which it is for `Profile`s with the same name, even if the
-        // username or password differ.
-        profiles.replace(profile.clone());
+    /// Makes a `Profile` the default profile in the default application
+    /// credentials file.
+    ///
+    /// This records the profile name in the file's `default_profile` key, so
+    /// that loading the `"default"` profile loads this profile.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose default profile to set, or
+    ///   [`CredentialsTarget`].
+    /// * `profile_name`: Name of the profile, or an alias, to use as the
+    ///   default.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name),
+            err(Display)
+        )
+    )]
+    pub async fn set_default<'s, R>(target: R, profile_name: &str) -> Result<(), Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(
+            &target,
+            AuditOperation::SetDefault,
+            &[profile_name],
+            async {
+                let credentials_path = target.path()?;
+                let integrity_key = target.options().integrity_key();
+                Self::set_default_file_with(profile_name, &credentials_path, integrity_key).await
+            },
+        )
+        .await
+    }
 
-        let profiles_contents = Self::profiles_serialize(&profiles)?;
+    /// Signs the default application credentials file with the target's
+    /// integrity key, accepting its current contents.
+    ///
+    /// This is used to start checking the integrity of an existing file, or to
+    /// accept changes made to it by hand. If the target has no integrity
+    /// key, the file's signature is removed.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: [`CredentialsTarget`] with the integrity key to sign the
+    ///   file with.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, err(Display))
+    )]
+    pub async fn sign<'s, R>(target: R) -> Result<(), Error>
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let target = target.into();
+        CredentialsAuditLog::audited(&target, AuditOperation::Sign, &[], async {
+            let credentials_path = target.path()?;
+            let mut document =
+                CredentialsFileLoader::<C>::document_load(&credentials_path, None).await?;
+            // Without a key, the signature is removed on purpose.
+            document.hmac = None;
 
-        Self::credentials_parent_create(credentials_path).await?;
-        Self::credentials_file_write(profiles_contents.as_bytes(), credentials_path).await?;
+            Self::document_write(
+                &mut document,
+                &credentials_path,
+                target.options().integrity_key(),
+            )
+            .await
+        })
+        .await
+    }
 
-        Ok(())
+    /// Stores a `Profile` in the writable layer.
+    ///
+    /// This replaces the profile's credentials in the writable layer's file.
+    /// Other layers are never written to.
+    ///
+    /// # Parameters
+    ///
+    /// * `layers`: Credentials files, one of which is writable.
+    /// * `profile`: Profile to store.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile.name),
+            err(Display)
+        )
+    )]
+    pub async fn store_layered(
+        layers: &CredentialsLayers,
+        profile: &Profile<C>,
+    ) -> Result<(), Error> {
+        let layer = Self::writable_layer(layers)?;
+        CredentialsAuditLog::audited_with(
+            layers.audit_log(),
+            AuditTarget::file(&layer.path),
+            AuditOperation::Store,
+            &[&profile.name],
+            Self::store_file_with(profile, &layer.path, layer.integrity_key.as_ref(), false),
+        )
+        .await
+    }
+
+    /// Stores multiple `Profile`s in the writable layer.
+    ///
+    /// This replaces the specified profiles' credentials in the writable
+    /// layer's file, other profiles not included in the parameter are
+    /// untouched in the file. Other layers are never written to.
+    ///
+    /// # Parameters
+    ///
+    /// * `layers`: Credentials files, one of which is writable.
+    /// * `profiles`: Profiles to store.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_count = profiles.len()),
+            err(Display)
+        )
+    )]
+    pub async fn store_many_layered(
+        layers: &CredentialsLayers,
+        profiles: Profiles<C>,
+    ) -> Result<(), Error> {
+        let layer = Self::writable_layer(layers)?;
+        let profile_names = profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect::<Vec<String>>();
+        let profile_names = profile_names.iter().map(String::as_str).collect::<Vec<_>>();
+        CredentialsAuditLog::audited_with(
+            layers.audit_log(),
+            AuditTarget::file(&layer.path),
+            AuditOperation::Store,
+            &profile_names,
+            Self::store_many_file_with(profiles, &layer.path, layer.integrity_key.as_ref()),
+        )
+        .await
+    }
+
+    /// Stores a `Profile` in the given file.
+    ///
+    /// This replaces the profile's credentials in the file. If the profile
+    /// inherits from another profile, only the fields that differ from the
+    /// parent profile are written.
+    ///
+    /// Returns [`Error::ProfileOverwriteInvalid`] if the profile in the file
+    /// fails to be loaded. Use [`store_file_force`] to overwrite it. If it
+    /// reads its password from a `password_command` or `password_file`, the
+    /// reference is kept and the profile's password is not written.
+    ///
+    /// [`store_file_force`]: Self::store_file_force
+    ///
+    /// # Parameters
+    ///
+    /// * `profile`: Profile to store.
+    /// * `credentials_path`: File to write credentials to.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile.name, credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn store_file(
+        profile: &Profile<C>,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<(), Error> {
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            AuditTarget::file(credentials_path),
+            AuditOperation::Store,
+            &[&profile.name],
+            Self::store_file_with(profile, credentials_path, integrity_key, false),
+        )
+        .await
+    }
+
+    /// Stores a `Profile` in the given file, even if the existing profile is
+    /// invalid.
+    ///
+    /// Returns [`Error::ProfileSecretReferenced`] if the profile in the file
+    /// reads its password from a `password_command` or `password_file`.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile`: Profile to store.
+    /// * `credentials_path`: File to write credentials to.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile.name, credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn store_file_force(
+        profile: &Profile<C>,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<(), Error> {
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            AuditTarget::file(credentials_path),
+            AuditOperation::Store,
+            &[&profile.name],
+            Self::store_file_with(profile, credentials_path, integrity_key, true),
+        )
+        .await
     }
 
     /// Stores multiple `Profile`s in the given file.
@@ -73,45 +462,432 @@ where
     /// This replaces the specified profiles' credentials in the file, other
     /// profiles not included in the parameter are untouched in the file.
     ///
+    /// Returns [`Error::ProfileOverwriteInvalid`] if any of the profiles in
+    /// the file fail to be loaded, in which case the file is not written to.
+    ///
     /// # Parameters
     ///
     /// * `profiles`: Profiles to store.
     /// * `credentials_path`: File to write credentials to.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_count = profiles.len(), credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
     pub async fn store_many_file(
-        mut profiles: Profiles<C>,
+        profiles: Profiles<C>,
         credentials_path: &Path,
-    ) -> Result<(), Error<C>> {
-        let profiles_existing = Self::profiles_existing(credentials_path).await?;
-        let profiles_from_file = profiles_existing.unwrap_or_else(Profiles::<C>::new);
-
-        profiles_from_file
-            .0
-            .into_iter()
-            .for_each(|profile_from_file| {
-                if !profiles.contains(&profile_from_file) {
-                    profiles.insert(profile_from_file);
-                }
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<(), Error> {
+        let profile_names = profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect::<Vec<String>>();
+        let profile_names = profile_names.iter().map(String::as_str).collect::<Vec<_>>();
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            AuditTarget::file(credentials_path),
+            AuditOperation::Store,
+            &profile_names,
+            Self::store_many_file_with(profiles, credentials_path, integrity_key),
+        )
+        .await
+    }
+
+    /// Removes a `Profile` from the given file.
+    ///
+    /// Returns the removed profile, or `None` if the profile does not exist or
+    /// fails to load. A profile that fails to load is removed all the same.
+    /// The file is not written to if the profile does not exist. If the
+    /// profile is the default profile or has aliases, these are removed as
+    /// well. Profiles that inherit from the removed profile take on its fields.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile to remove.
+    /// * `credentials_path`: File to remove credentials from.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name, credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn remove_file(
+        profile_name: &str,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<Option<Profile<C>>, Error> {
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            AuditTarget::file(credentials_path),
+            AuditOperation::Remove,
+            &[profile_name],
+            Self::remove_file_with(profile_name, credentials_path, integrity_key),
+        )
+        .await
+    }
+
+    /// Renames a `Profile` in the given file.
+    ///
+    /// The default profile, aliases, and profiles that inherit from the
+    /// profile are updated to refer to the new name. Renaming a profile to its
+    /// own name does not change the file.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile to rename.
+    /// * `profile_name_new`: New name of the profile.
+    /// * `credentials_path`: File to rename credentials in.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                profile_name = %profile_name,
+                profile_name_new = %profile_name_new,
+                credentials_path = %credentials_path.display(),
+            ),
+            err(Display)
+        )
+    )]
+    pub async fn rename_file(
+        profile_name: &str,
+        profile_name_new: &str,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<(), Error> {
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            AuditTarget::file(credentials_path),
+            AuditOperation::Rename {
+                profile_new: profile_name_new,
+            },
+            &[profile_name],
+            Self::rename_file_with(
+                profile_name,
+                profile_name_new,
+                credentials_path,
+                integrity_key,
+            ),
+        )
+        .await
+    }
+
+    /// Makes a `Profile` the default profile in the given file.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile, or an alias, to use as the
+    ///   default.
+    /// * `credentials_path`: File to set the default profile in.
+    /// * `integrity_key`: Key to verify and sign the file with, if it is
+    ///   signed.
+    /// * `audit_log`: Log to record the access in, if any.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(profile_name = %profile_name, credentials_path = %credentials_path.display()),
+            err(Display)
+        )
+    )]
+    pub async fn set_default_file(
+        profile_name: &str,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        audit_log: Option<&CredentialsAuditLog>,
+    ) -> Result<(), Error> {
+        CredentialsAuditLog::audited_with(
+            audit_log,
+            AuditTarget::file(credentials_path),
+            AuditOperation::SetDefault,
+            &[profile_name],
+            Self::set_default_file_with(profile_name, credentials_path, integrity_key),
+        )
+        .await
+    }
+
+    async fn store_file_with(
+        profile: &Profile<C>,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        force: bool,
+    ) -> Result<(), Error> {
+        let mut document = Self::document_existing(credentials_path, integrity_key).await?;
+        Self::document_profile_insert(&mut document, profile, credentials_path, force)?;
+
+        Self::document_write(&mut document, credentials_path, integrity_key).await
+    }
+
+    async fn store_many_file_with(
+        profiles: Profiles<C>,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<(), Error> {
+        let mut document = Self::document_existing(credentials_path, integrity_key).await?;
+        profiles.iter().try_for_each(|profile| {
+            Self::document_profile_insert(&mut document, profile, credentials_path, false)
+        })?;
+
+        Self::document_write(&mut document, credentials_path, integrity_key).await
+    }
+
+    async fn remove_file_with(
+        profile_name: &str,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<Option<Profile<C>>, Error> {
+        if !credentials_path.exists() {
+            return Ok(None);
+        }
+
+        let mut document =
+            CredentialsFileLoader::<C>::document_load(credentials_path, integrity_key).await?;
+        // A profile that fails to load is still removed, so that broken entries
+        // can be cleaned up.
+        let profile = match document.profiles.get(profile_name) {
+            Some(credentials) => CredentialsFileLoader::document_profile_unresolved(
+                &document,
+                profile_name,
+                credentials,
+                credentials_path,
+            )
+            .ok(),
+            None => return Ok(None),
+        };
+        document.profile_remove(profile_name);
+        Self::document_write(&mut document, credentials_path, integrity_key).await?;
+
+        Ok(profile)
+    }
+
+    async fn rename_file_with(
+        profile_name: &str,
+        profile_name_new: &str,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<(), Error> {
+        let mut document = Self::document_existing(credentials_path, integrity_key).await?;
+
+        // Renaming a profile to its own name leaves the file as is.
+        if profile_name == profile_name_new {
+            return Self::profile_ensure_exists(&document, profile_name, credentials_path);
+        }
+        if document.profiles.contains_key(profile_name_new) {
+            let profile_name = profile_name_new.to_string();
+            let credentials_path = credentials_path.to_owned();
+            return Err(Error::ProfileExists {
+                profile_name,
+                credentials_path,
             });
+        }
+        Self::profile_ensure_exists(&document, profile_name, credentials_path)?;
+        document.profile_rename(profile_name, profile_name_new, credentials_path)?;
 
-        let profiles_contents = Self::profiles_serialize(&profiles)?;
+        Self::document_write(&mut document, credentials_path, integrity_key).await
+    }
 
-        Self::credentials_parent_create(credentials_path).await?;
-        Self::credentials_file_write(profiles_contents.as_bytes(), credentials_path).await?;
+    async fn set_default_file_with(
+        profile_name: &str,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<(), Error> {
+        let mut document = Self::document_existing(credentials_path, integrity_key).await?;
+        let profile_name = document.meta.alias_resolve(profile_name)?.to_string();
+        Self::profile_ensure_exists(&document, &profile_name, credentials_path)?;
 
-        Ok(())
+        // The `"default"` profile is used when there is no `default_profile`.
+        document.meta.default_profile = if profile_name == Profile::<C>::DEFAULT_NAME {
+            None
+        } else {
+            Some(profile_name)
+        };
+
+        Self::document_write(&mut document, credentials_path, integrity_key).await
+    }
+
+    /// Inserts a profile into the document.
+    ///
+    /// # Parameters
+    ///
+    /// * `document`: Document to insert the profile into.
+    /// * `profile`: Profile to insert.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    /// * `force`: Whether to overwrite the existing profile if it is invalid.
+    fn document_profile_insert(
+        document: &mut CredentialsDocument,
+        profile: &Profile<C>,
+        credentials_path: &Path,
+        force: bool,
+    ) -> Result<(), Error> {
+        let validate_result = match document.profiles.get(&profile.name) {
+            Some(credentials_existing) => document.profile_validate::<C>(
+                &profile.name,
+                credentials_existing,
+                credentials_path,
+            ),
+            None => Ok(()),
+        };
+
+        match validate_result {
+            Ok(()) => {}
+            Err(_error) if force => {
+                // Clear the invalid profile in place, so that it keeps its
+                // position but nothing is retained from it.
+                document
+                    .profiles
+                    .insert(profile.name.clone(), Value::Table(Table::new()));
+            }
+            Err(error) => {
+                let profile_name = profile.name.clone();
+                let credentials_path = credentials_path.to_owned();
+                let error = Box::new(error);
+                return Err(Error::ProfileOverwriteInvalid {
+                    profile_name,
+                    credentials_path,
+                    error,
+                });
+            }
+        }
+
+        // A stored profile whose password comes from a `password_command` or
+        // `password_file` is loaded with the resolved password, so storing it
+        // again writes the reference back rather than the password.
+        let credentials_secret_referenced = match document.profiles.get(&profile.name) {
+            Some(credentials_existing) => {
+                let credentials_existing_resolved = document.profile_resolve(
+                    &profile.name,
+                    credentials_existing,
+                    credentials_path,
+                )?;
+                CredentialsSecretResolver::is_secret_referenced(&credentials_existing_resolved)
+                    .then(|| credentials_existing.clone())
+            }
+            None => None,
+        };
+        match credentials_secret_referenced {
+            Some(_credentials_existing) if force => {
+                let profile_name = profile.name.clone();
+                let credentials_path = credentials_path.to_owned();
+                Err(Error::ProfileSecretReferenced {
+                    profile_name,
+                    credentials_path,
+                })
+            }
+            Some(credentials_existing) => document.profile_insert_secret_retained(
+                profile,
+                &credentials_existing,
+                credentials_path,
+            ),
+            None => document.profile_insert(profile, credentials_path),
+        }
     }
 
-    async fn profiles_existing(credentials_path: &Path) -> Result<Option<Profiles<C>>, Error<C>> {
+    fn writable_layer(layers: &CredentialsLayers) -> Result<&CredentialsLayer, Error> {
+        layers
+            .writable_layer()
+            .ok_or(Error::CredentialsLayerWritableNotFound)
+    }
+
+    fn profile_ensure_exists(
+        document: &CredentialsDocument,
+        profile_name: &str,
+        credentials_path: &Path,
+    ) -> Result<(), Error> {
+        if document.profiles.contains_key(profile_name) {
+            Ok(())
+        } else {
+            let profile_name = profile_name.to_string();
+            let credentials_path = credentials_path.to_owned();
+            Err(Error::ProfileNotFound {
+                profile_name,
+                credentials_path,
+            })
+        }
+    }
+
+    async fn document_existing(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<CredentialsDocument, Error> {
         if credentials_path.exists() {
-            CredentialsFileLoader::<C>::load_file(credentials_path)
-                .await
-                .map(Some)
+            CredentialsFileLoader::<C>::document_load(credentials_path, integrity_key).await
         } else {
-            Ok(None)
+            Ok(CredentialsDocument::default())
+        }
+    }
+
+    /// Writes the document to the given file, creating its parent directory.
+    ///
+    /// If an integrity key is given, the document is signed with it.
+    /// Otherwise [`Error::CredentialsFileIntegrityKeyRequired`] is returned if
+    /// the document is signed, so that its signature is not silently removed.
+    pub(crate) async fn document_write(
+        document: &mut CredentialsDocument,
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
+        match integrity_key {
+            Some(integrity_key) => CredentialsIntegrity::sign(document, integrity_key).await?,
+            None if document.hmac.is_some() => {
+                let credentials_path = credentials_path.to_owned();
+                return Err(Error::CredentialsFileIntegrityKeyRequired { credentials_path });
+            }
+            None => {}
         }
+
+        let profiles_contents = document.to_string_pretty().map_err(|error| {
+            let profile_names = document.profiles.keys().cloned().collect();
+            Error::CredentialsFileSerialize {
+                profile_names,
+                error,
+            }
+        })?;
+
+        Self::credentials_parent_create(credentials_path).await?;
+        let write_result =
+            Self::credentials_file_write(profiles_contents.as_bytes(), credentials_path).await;
+        // The file may be partially written even if writing failed.
+        CachedCredentialsLoader::<C>::credentials_file_written(credentials_path);
+        write_result?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            credentials_path = %credentials_path.display(),
+            profile_count = document.profiles.len(),
+            integrity_signed = integrity_key.is_some(),
+            elapsed = ?started.elapsed(),
+            "Wrote credentials file."
+        );
+
+        Ok(())
     }
 
-    async fn credentials_parent_create(credentials_path: &Path) -> Result<(), Error<C>> {
+    async fn credentials_parent_create(credentials_path: &Path) -> Result<(), Error> {
         if let Some(parent_path) = credentials_path.parent() {
             async_fs::create_dir_all(parent_path)
                 .await
@@ -126,7 +902,7 @@ where
     async fn credentials_file_write(
         credentials_contents: &[u8],
         credentials_path: &Path,
-    ) -> Result<(), Error<C>> {
+    ) -> Result<(), Error> {
         async_fs::write(credentials_path, credentials_contents)
             .await
             .map_err(|error| {
@@ -137,13 +913,6 @@ where
                 }
             })
     }
-
-    fn profiles_serialize(profiles: &Profiles<C>) -> Result<String, Error<C>> {
-        toml::ser::to_string_pretty(&profiles).map_err(|error| {
-            let profiles = profiles.clone();
-            Error::CredentialsFileSerialize { profiles, error }
-        })
-    }
 }
 
 #[cfg(test)]
@@ -156,6 +925,7 @@ mod tests {
     use tempfile::NamedTempFile;
 
     use super::CredentialsFileStorer;
+    use crate::{CredentialsFileLoader, CredentialsLayer, CredentialsLayers};
 
     #[cfg(feature = "base64")]
     const PROFILES_CONTENT: &str = r#"
@@ -189,16 +959,20 @@ mod tests {
                 password: Password::new("secret"),
             });
 
-            CredentialsFileStorer::store_file(&profile_default, &file_path).await?;
+            CredentialsFileStorer::store_file(&profile_default, &file_path, None, None).await?;
 
             #[cfg(feature = "base64")]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'c2VjcmV0'\n\
             ";
             #[cfg(not(feature = "base64"))]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'secret'\n\
@@ -225,16 +999,20 @@ mod tests {
                 },
             );
 
-            CredentialsFileStorer::store_file(&profile_other, file.path()).await?;
+            CredentialsFileStorer::store_file(&profile_other, file.path(), None, None).await?;
 
             #[cfg(feature = "base64")]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [profile_other]\n\
                 username = 'me'\n\
                 password = 'c2VjcmV0'\n\
             ";
             #[cfg(not(feature = "base64"))]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [profile_other]\n\
                 username = 'me'\n\
                 password = 'secret'\n\
@@ -260,10 +1038,12 @@ mod tests {
                 password: Password::new("boo"),
             });
 
-            CredentialsFileStorer::store_file(&profile_default, file.path()).await?;
+            CredentialsFileStorer::store_file(&profile_default, file.path(), None, None).await?;
 
             #[cfg(feature = "base64")]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'Ym9v'\n\
@@ -274,6 +1054,8 @@ mod tests {
             ";
             #[cfg(not(feature = "base64"))]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'boo'\n\
@@ -318,10 +1100,12 @@ mod tests {
                 profiles_new
             };
 
-            CredentialsFileStorer::store_many_file(profiles_new, file.path()).await?;
+            CredentialsFileStorer::store_many_file(profiles_new, file.path(), None, None).await?;
 
             #[cfg(feature = "base64")]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [profile_new_a]\n\
                 username = 'me_a'\n\
                 password = 'c2VjcmV0'\n\
@@ -332,6 +1116,8 @@ mod tests {
             ";
             #[cfg(not(feature = "base64"))]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [profile_new_a]\n\
                 username = 'me_a'\n\
                 password = 'secret'\n\
@@ -384,10 +1170,13 @@ mod tests {
                 profiles_replace
             };
 
-            CredentialsFileStorer::store_many_file(profiles_replace, file.path()).await?;
+            CredentialsFileStorer::store_many_file(profiles_replace, file.path(), None, None)
+                .await?;
 
             #[cfg(feature = "base64")]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'Ym9v'\n\
@@ -402,6 +1191,8 @@ mod tests {
             ";
             #[cfg(not(feature = "base64"))]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'boo'\n\
@@ -452,10 +1243,13 @@ mod tests {
                 profiles_replace
             };
 
-            CredentialsFileStorer::store_many_file(profiles_replace, file.path()).await?;
+            CredentialsFileStorer::store_many_file(profiles_replace, file.path(), None, None)
+                .await?;
 
             #[cfg(feature = "base64")]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'c2VjcmV0'\n\
@@ -470,6 +1264,8 @@ mod tests {
             ";
             #[cfg(not(feature = "base64"))]
             let content_expected = "\
+                version = 1\n\
+                \n\
                 [default]\n\
                 username = 'me'\n\
                 password = 'secret'\n\
@@ -491,4 +1287,678 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn store_layered_writes_to_writable_layer_only() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let system_path = tempdir.path().join("system");
+            let user_path = tempdir.path().join("user");
+            std::fs::write(&system_path, PROFILES_CONTENT)?;
+
+            let layers = CredentialsLayers::new()
+                .with_layer(CredentialsLayer::read_only(&system_path))
+                .with_layer(CredentialsLayer::writable(&user_path));
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("boo"),
+            });
+
+            CredentialsFileStorer::store_layered(&layers, &profile_default).await?;
+
+            #[cfg(feature = "base64")]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'Ym9v'\n\
+            ";
+            #[cfg(not(feature = "base64"))]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'boo'\n\
+            ";
+
+            assert_eq!(content_expected, std::fs::read_to_string(&user_path)?);
+            assert_eq!(PROFILES_CONTENT, std::fs::read_to_string(&system_path)?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_layered_returns_err_when_no_layer_writable() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let layers = CredentialsLayers::new().with_layer(CredentialsLayer::read_only("system"));
+        let profile_default = Profile::new_default(Credentials {
+            username: Username(String::from("me")),
+            password: Password::new("boo"),
+        });
+
+        let store_result = smol::block_on(CredentialsFileStorer::store_layered(
+            &layers,
+            &profile_default,
+        ));
+
+        assert!(matches!(
+            store_result,
+            Err(credent_fs_model::Error::CredentialsLayerWritableNotFound)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn remove_file_removes_profile_when_pre_existent() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            let profile_removed = CredentialsFileStorer::<Credentials>::remove_file(
+                "profile_other",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+
+            let profile_removed_expected = Profile::new(
+                String::from("profile_other"),
+                Credentials {
+                    username: Username(String::from("you")),
+                    password: Password::new("code"),
+                },
+            );
+            assert_eq!(Some(profile_removed_expected), profile_removed);
+
+            #[cfg(feature = "base64")]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'c2VjcmV0'\n\
+            ";
+            #[cfg(not(feature = "base64"))]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'secret'\n\
+            ";
+
+            assert_eq!(content_expected, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn remove_file_keeps_child_password_source_when_parent_spliced(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(
+                file,
+                "[base]\nusername = 'me'\npassword = 'c2VjcmV0'\n\n\
+                [ci]\ninherits = 'base'\npassword_file = 'secret.txt'\n"
+            )?;
+
+            CredentialsFileStorer::<Credentials>::remove_file("base", file.path(), None, None)
+                .await?;
+
+            assert_eq!(
+                "version = 1\n\n[ci]\nusername = 'me'\npassword_file = 'secret.txt'\n",
+                std::fs::read_to_string(file.path())?
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn remove_file_removes_profile_that_fails_to_load() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(
+                file,
+                "[default]\nusername = 'me'\npassword = 'c2VjcmV0'\n\n[broken]\nusername = 'you'\n"
+            )?;
+
+            let profile_removed = CredentialsFileStorer::<Credentials>::remove_file(
+                "broken",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+
+            assert_eq!(None, profile_removed);
+            assert_eq!(
+                "version = 1\n\n[default]\nusername = 'me'\npassword = 'c2VjcmV0'\n",
+                std::fs::read_to_string(file.path())?
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn rename_file_renames_profile() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            CredentialsFileStorer::<Credentials>::rename_file(
+                "profile_other",
+                "profile_renamed",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+
+            #[cfg(feature = "base64")]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'c2VjcmV0'\n\
+                \n\
+                [profile_renamed]\n\
+                username = 'you'\n\
+                password = 'Y29kZQ=='\n\
+            ";
+            #[cfg(not(feature = "base64"))]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'secret'\n\
+                \n\
+                [profile_renamed]\n\
+                username = 'you'\n\
+                password = 'code'\n\
+            ";
+
+            assert_eq!(content_expected, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_retains_inheritance_when_pre_existent() -> Result<(), Box<dyn std::error::Error>>
+    {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+            write!(file, "\n[profile_child]\ninherits = 'default'\n")?;
+
+            let profile_child = Profile::new(
+                String::from("profile_child"),
+                Credentials {
+                    username: Username(String::from("me")),
+                    password: Password::new("boo"),
+                },
+            );
+            CredentialsFileStorer::store_file(&profile_child, file.path(), None, None).await?;
+
+            #[cfg(feature = "base64")]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'c2VjcmV0'\n\
+                \n\
+                [profile_other]\n\
+                username = 'you'\n\
+                password = 'Y29kZQ=='\n\
+                \n\
+                [profile_child]\n\
+                inherits = 'default'\n\
+                password = 'Ym9v'\n\
+            ";
+            #[cfg(not(feature = "base64"))]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'secret'\n\
+                \n\
+                [profile_other]\n\
+                username = 'you'\n\
+                password = 'code'\n\
+                \n\
+                [profile_child]\n\
+                inherits = 'default'\n\
+                password = 'boo'\n\
+            ";
+
+            assert_eq!(content_expected, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_retains_pre_existent_secret_ref() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let secret_path = tempdir.path().join("secret");
+            let file_path = tempdir.path().join("credentials");
+            std::fs::write(
+                &file_path,
+                format!(
+                    "[default]\nusername = 'me'\npassword_file = '{}'\n",
+                    secret_path.display()
+                ),
+            )?;
+
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store_file(&profile_default, &file_path, None, None).await?;
+
+            let content_expected = format!(
+                "version = 1\n\n[default]\nusername = 'you'\npassword_file = '{}'\n",
+                secret_path.display()
+            );
+            assert_eq!(content_expected, std::fs::read_to_string(&file_path)?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_retains_inherited_secret_ref() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let file_path = tempdir.path().join("credentials");
+            std::fs::write(
+                &file_path,
+                "[base]\nusername = 'me'\npassword_command = 'pass show base'\n\n\
+                [default]\ninherits = 'base'\n",
+            )?;
+
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store_file(&profile_default, &file_path, None, None).await?;
+
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [base]\n\
+                username = 'me'\n\
+                password_command = 'pass show base'\n\
+                \n\
+                [default]\n\
+                inherits = 'base'\n\
+                username = 'you'\n\
+            ";
+            assert_eq!(content_expected, std::fs::read_to_string(&file_path)?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_force_returns_err_when_pre_existent_secret_ref(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let file_path = tempdir.path().join("credentials");
+            let content = "[base]\nusername = 'me'\npassword_command = 'pass show base'\n\n\
+                [default]\ninherits = 'base'\n";
+            std::fs::write(&file_path, content)?;
+
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("secret"),
+            });
+            let store_result =
+                CredentialsFileStorer::store_file_force(&profile_default, &file_path, None, None)
+                    .await;
+
+            assert!(
+                matches!(
+                    store_result,
+                    Err(credent_fs_model::Error::ProfileSecretReferenced { .. })
+                ),
+                "Expected `store_result` to return `ProfileSecretReferenced` error, but got `{:?}`.",
+                store_result
+            );
+            assert_eq!(content, std::fs::read_to_string(&file_path)?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn set_default_file_writes_meta_and_store_file_retains_it(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            CredentialsFileStorer::<Credentials>::set_default_file(
+                "profile_other",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("boo"),
+            });
+            CredentialsFileStorer::store_file(&profile_default, file.path(), None, None).await?;
+
+            #[cfg(feature = "base64")]
+            let content_expected = "\
+                version = 1\n\
+                default_profile = 'profile_other'\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'Ym9v'\n\
+                \n\
+                [profile_other]\n\
+                username = 'you'\n\
+                password = 'Y29kZQ=='\n\
+            ";
+            #[cfg(not(feature = "base64"))]
+            let content_expected = "\
+                version = 1\n\
+                default_profile = 'profile_other'\n\
+                \n\
+                [default]\n\
+                username = 'me'\n\
+                password = 'boo'\n\
+                \n\
+                [profile_other]\n\
+                username = 'you'\n\
+                password = 'code'\n\
+            ";
+
+            assert_eq!(content_expected, std::fs::read_to_string(file.path())?);
+
+            CredentialsFileStorer::<Credentials>::rename_file(
+                "profile_other",
+                "profile_renamed",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+            let meta =
+                CredentialsFileLoader::<Credentials>::load_file_meta(file.path(), None, None)
+                    .await?;
+            assert_eq!(Some("profile_renamed"), meta.default_profile.as_deref());
+
+            CredentialsFileStorer::<Credentials>::remove_file(
+                "profile_renamed",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+            let meta =
+                CredentialsFileLoader::<Credentials>::load_file_meta(file.path(), None, None)
+                    .await?;
+            assert_eq!(None, meta.default_profile);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn set_default_file_returns_err_when_profile_not_found(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            let set_default_result = CredentialsFileStorer::<Credentials>::set_default_file(
+                "missing",
+                file.path(),
+                None,
+                None,
+            )
+            .await;
+
+            assert!(matches!(
+                set_default_result,
+                Err(credent_fs_model::Error::ProfileNotFound { .. })
+            ));
+            assert_eq!(PROFILES_CONTENT, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn rename_file_returns_err_when_new_name_exists() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            let rename_result = CredentialsFileStorer::<Credentials>::rename_file(
+                "profile_other",
+                "default",
+                file.path(),
+                None,
+                None,
+            )
+            .await;
+
+            if let Err(credent_fs_model::Error::ProfileExists { profile_name, .. }) = &rename_result
+            {
+                assert_eq!("default", profile_name);
+            } else {
+                panic!(
+                    "Expected `rename_result` to return `ProfileExists` error, but got `{:?}`.",
+                    rename_result
+                );
+            }
+            assert_eq!(PROFILES_CONTENT, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn rename_file_to_same_name_leaves_file_unchanged() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            CredentialsFileStorer::<Credentials>::rename_file(
+                "profile_other",
+                "profile_other",
+                file.path(),
+                None,
+                None,
+            )
+            .await?;
+            let rename_missing_result = CredentialsFileStorer::<Credentials>::rename_file(
+                "missing",
+                "missing",
+                file.path(),
+                None,
+                None,
+            )
+            .await;
+
+            assert_eq!(PROFILES_CONTENT, std::fs::read_to_string(file.path())?);
+            assert!(matches!(
+                rename_missing_result,
+                Err(credent_fs_model::Error::ProfileNotFound { .. })
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_returns_err_when_pre_existent_profile_invalid(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            let content_invalid = "[default]\nusername = 'me'\n";
+            write!(file, "{}", content_invalid)?;
+
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("secret"),
+            });
+            let store_result =
+                CredentialsFileStorer::store_file(&profile_default, file.path(), None, None).await;
+
+            if let Err(credent_fs_model::Error::ProfileOverwriteInvalid { profile_name, .. }) =
+                &store_result
+            {
+                assert_eq!("default", profile_name);
+            } else {
+                panic!(
+                    "Expected `store_result` to return `ProfileOverwriteInvalid` error, but got `{:?}`.",
+                    store_result
+                );
+            }
+            assert_eq!(content_invalid, std::fs::read_to_string(file.path())?);
+
+            CredentialsFileStorer::store_file_force(&profile_default, file.path(), None, None)
+                .await?;
+
+            #[cfg(feature = "base64")]
+            let content_expected =
+                "version = 1\n\n[default]\nusername = 'you'\npassword = 'c2VjcmV0'\n";
+            #[cfg(not(feature = "base64"))]
+            let content_expected =
+                "version = 1\n\n[default]\nusername = 'you'\npassword = 'secret'\n";
+            assert_eq!(content_expected, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_returns_err_when_profile_name_reserved() -> Result<(), Box<dyn std::error::Error>>
+    {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            for profile_name in ["default_profile", "aliases", "version", "hmac"] {
+                let profile = Profile::new(
+                    String::from(profile_name),
+                    Credentials {
+                        username: Username(String::from("you")),
+                        password: Password::new("secret"),
+                    },
+                );
+                let store_result =
+                    CredentialsFileStorer::store_file_force(&profile, file.path(), None, None)
+                        .await;
+
+                assert!(
+                    matches!(
+                        &store_result,
+                        Err(credent_fs_model::Error::ProfileNameReserved { profile_name: name, .. })
+                            if name == profile_name
+                    ),
+                    "Expected `store_result` to return `ProfileNameReserved` error, but got `{:?}`.",
+                    store_result
+                );
+            }
+            assert_eq!(PROFILES_CONTENT, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn rename_file_returns_err_when_profile_name_reserved() -> Result<(), Box<dyn std::error::Error>>
+    {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "{}", PROFILES_CONTENT)?;
+
+            for profile_name_new in ["default_profile", "aliases", "version", "hmac"] {
+                let rename_result = CredentialsFileStorer::<Credentials>::rename_file(
+                    "profile_other",
+                    profile_name_new,
+                    file.path(),
+                    None,
+                    None,
+                )
+                .await;
+
+                assert!(
+                    matches!(
+                        &rename_result,
+                        Err(credent_fs_model::Error::ProfileNameReserved { profile_name, .. })
+                            if profile_name == profile_name_new
+                    ),
+                    "Expected `rename_result` to return `ProfileNameReserved` error, but got `{:?}`.",
+                    rename_result
+                );
+            }
+            assert_eq!(PROFILES_CONTENT, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_retains_other_invalid_profiles() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let mut file = NamedTempFile::new()?;
+            write!(file, "[broken]\nusername = 'me'\n")?;
+
+            let profile_default = Profile::new_default(Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store_file(&profile_default, file.path(), None, None).await?;
+
+            #[cfg(feature = "base64")]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [broken]\n\
+                username = 'me'\n\
+                \n\
+                [default]\n\
+                username = 'you'\n\
+                password = 'c2VjcmV0'\n\
+            ";
+            #[cfg(not(feature = "base64"))]
+            let content_expected = "\
+                version = 1\n\
+                \n\
+                [broken]\n\
+                username = 'me'\n\
+                \n\
+                [default]\n\
+                username = 'you'\n\
+                password = 'secret'\n\
+            ";
+            assert_eq!(content_expected, std::fs::read_to_string(file.path())?);
+
+            Ok(())
+        })
+    }
 }
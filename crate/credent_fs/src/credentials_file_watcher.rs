@@ -0,0 +1,404 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use async_channel::Sender;
+use credent_fs_model::{CredentialsFileEvent, Error, IntegrityKey, LenientProfiles, ProfilesDiff};
+use credent_model::{Credentials, Profiles};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+
+use crate::CredentialsFileLoader;
+
+/// Options for watching a credentials file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CredentialsFileWatchOptions {
+    /// Duration to wait for further changes before reloading the file.
+    pub debounce: Duration,
+    /// Interval between checks for changes when polling the file.
+    pub poll_interval: Duration,
+    /// Whether to poll the file instead of using native file system events.
+    pub polling: bool,
+}
+
+impl Default for CredentialsFileWatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(100),
+            poll_interval: Duration::from_secs(1),
+            polling: false,
+        }
+    }
+}
+
+/// Watches a credentials file, and emits the profiles that changed.
+///
+/// Native file system events are used where available -- `inotify` on Linux --
+/// otherwise the file is polled for changes to its modification time or size.
+///
+/// The file is watched on a separate thread, which stops when the watcher is
+/// dropped.
+#[derive(Debug)]
+pub struct CredentialsFileWatcher<C = Credentials>
+where
+    C: Clone + Eq,
+{
+    /// Path to the watched credentials file.
+    credentials_path: PathBuf,
+    /// Receives events from the watching thread.
+    event_receiver: async_channel::Receiver<CredentialsFileEvent<C>>,
+}
+
+impl<C> CredentialsFileWatcher<C>
+where
+    C: Clone + Eq + Send + 'static + for<'de> Deserialize<'de>,
+{
+    /// Watches the given credentials file with the default options.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: File to watch.
+    /// * `integrity_key`: Key to verify the file's HMAC with, if any.
+    pub async fn watch(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+    ) -> Result<Self, Error> {
+        Self::watch_with(
+            credentials_path,
+            integrity_key,
+            CredentialsFileWatchOptions::default(),
+        )
+        .await
+    }
+
+    /// Watches the given credentials file.
+    ///
+    /// The file does not need to exist -- a file that does not exist is
+    /// treated as having no profiles. If the file fails to be loaded, or its
+    /// HMAC does not match the integrity key, a
+    /// [`CredentialsFileEvent::LoadFailed`] event is emitted.
+    ///
+    /// Profiles with a `password_command` or `password_file` are included with
+    /// an empty password, as their secrets are only resolved when they are
+    /// loaded by name.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_path`: File to watch.
+    /// * `integrity_key`: Key to verify the file's HMAC with, if any.
+    /// * `options`: Debounce and polling options.
+    pub async fn watch_with(
+        credentials_path: &Path,
+        integrity_key: Option<&IntegrityKey>,
+        options: CredentialsFileWatchOptions,
+    ) -> Result<Self, Error> {
+        let credentials_path = credentials_path.to_path_buf();
+        let (event_sender, event_receiver) = async_channel::unbounded();
+
+        // Start watching before the initial load, so changes in between are not
+        // missed.
+        let (change_sender, change_receiver) = mpsc::channel();
+        let native_watcher = if options.polling {
+            None
+        } else {
+            Self::native_watcher(&credentials_path, change_sender)
+        };
+        let fingerprint = Fingerprint::of(&credentials_path);
+
+        let mut watch_state = WatchState {
+            credentials_path: credentials_path.clone(),
+            integrity_key: integrity_key.cloned(),
+            options,
+            event_sender,
+            profiles: Profiles::new(),
+        };
+        watch_state.reload().await;
+
+        thread::Builder::new()
+            .name(String::from("credent_fs_watch"))
+            .spawn(move || {
+                if native_watcher.is_some() {
+                    watch_state.watch_events(change_receiver);
+                } else {
+                    watch_state.watch_poll(fingerprint);
+                }
+            })
+            .map_err(|error| {
+                let credentials_path = credentials_path.clone();
+                Error::CredentialsFileWatch {
+                    credentials_path,
+                    error,
+                }
+            })?;
+
+        Ok(Self {
+            credentials_path,
+            event_receiver,
+        })
+    }
+
+    /// Returns the path to the watched credentials file.
+    pub fn path(&self) -> &Path {
+        &self.credentials_path
+    }
+
+    /// Returns the next change to the credentials file.
+    ///
+    /// Returns `None` if the watching thread has stopped.
+    pub async fn next_event(&self) -> Option<CredentialsFileEvent<C>> {
+        self.event_receiver.recv().await.ok()
+    }
+
+    /// Returns a native file system watcher for the credentials file, or
+    /// `None` if one cannot be created.
+    ///
+    /// The parent directory is watched, as editors commonly replace the file
+    /// instead of writing to it.
+    fn native_watcher(
+        credentials_path: &Path,
+        change_sender: mpsc::Sender<()>,
+    ) -> Option<RecommendedWatcher> {
+        let file_name = credentials_path.file_name()?.to_os_string();
+        let parent_dir = match credentials_path.parent() {
+            Some(parent_dir) if parent_dir != Path::new("") => parent_dir,
+            _ => Path::new("."),
+        };
+
+        let mut native_watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let is_credentials_change = event
+                    .map(|event| {
+                        !matches!(event.kind, EventKind::Access(_))
+                            && event
+                                .paths
+                                .iter()
+                                .any(|path| path.file_name() == Some(file_name.as_os_str()))
+                    })
+                    .unwrap_or(true);
+                if is_credentials_change {
+                    let _ = change_sender.send(());
+                }
+            })
+            .ok()?;
+        native_watcher
+            .watch(parent_dir, RecursiveMode::NonRecursive)
+            .ok()?;
+
+        Some(native_watcher)
+    }
+}
+
+/// State of the thread that watches the credentials file.
+struct WatchState<C>
+where
+    C: Clone + Eq,
+{
+    /// Path to the watched credentials file.
+    credentials_path: PathBuf,
+    /// Key to verify the file's HMAC with, if any.
+    integrity_key: Option<IntegrityKey>,
+    /// Debounce and polling options.
+    options: CredentialsFileWatchOptions,
+    /// Sends events to the watcher.
+    event_sender: Sender<CredentialsFileEvent<C>>,
+    /// Profiles that were last successfully loaded.
+    profiles: Profiles<C>,
+}
+
+impl<C> WatchState<C>
+where
+    C: Clone + Eq + for<'de> Deserialize<'de>,
+{
+    /// Reloads the file whenever a native file system event is received.
+    fn watch_events(mut self, change_receiver: Receiver<()>) {
+        while !self.event_sender.is_closed() {
+            match change_receiver.recv_timeout(self.options.poll_interval) {
+                Ok(()) => {
+                    // Wait until changes have settled.
+                    while let Ok(()) = change_receiver.recv_timeout(self.options.debounce) {}
+
+                    futures_lite::future::block_on(self.reload());
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    /// Reloads the file whenever its modification time or size changes.
+    fn watch_poll(mut self, mut fingerprint: Option<Fingerprint>) {
+        while !self.event_sender.is_closed() {
+            thread::sleep(self.options.poll_interval);
+
+            let fingerprint_current = Fingerprint::of(&self.credentials_path);
+            if fingerprint_current != fingerprint {
+                // Wait until changes have settled.
+                thread::sleep(self.options.debounce);
+                fingerprint = Fingerprint::of(&self.credentials_path);
+
+                futures_lite::future::block_on(self.reload());
+            }
+        }
+    }
+
+    /// Returns the loaded profiles, or the first error for an invalid
+    /// profile.
+    ///
+    /// Profiles with secret references are included with an empty password,
+    /// as their secrets are only resolved when they are loaded by name.
+    fn profiles_loaded(lenient_profiles: LenientProfiles<C>) -> Result<Profiles<C>, Error> {
+        let LenientProfiles {
+            profiles, errors, ..
+        } = lenient_profiles;
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(profiles),
+        }
+    }
+
+    /// Reloads the file, and sends the changed profiles or load error.
+    async fn reload(&mut self) {
+        let profiles_result = if self.credentials_path.exists() {
+            CredentialsFileLoader::<C>::load_file_lenient(
+                &self.credentials_path,
+                self.integrity_key.as_ref(),
+                None,
+            )
+            .await
+            .and_then(Self::profiles_loaded)
+        } else {
+            Ok(Profiles::new())
+        };
+
+        let event = match profiles_result {
+            Ok(profiles) => {
+                let profiles_diff = ProfilesDiff::between(&self.profiles, &profiles);
+                self.profiles = profiles;
+
+                if profiles_diff.is_empty() {
+                    return;
+                }
+                CredentialsFileEvent::Changed(profiles_diff)
+            }
+            Err(error) => CredentialsFileEvent::LoadFailed(error),
+        };
+
+        // The watcher may have been dropped, in which case the thread stops on
+        // its next iteration.
+        let _ = self.event_sender.try_send(event);
+    }
+}
+
+/// Modification time and size of a file, used to detect changes when polling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> Option<Self> {
+        fs::metadata(path).ok().map(|metadata| Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use credent_fs_model::CredentialsFileEvent;
+    use credent_model::{Credentials, Password, Profile, Username};
+
+    use super::{CredentialsFileWatchOptions, CredentialsFileWatcher};
+
+    #[cfg(feature = "base64")]
+    const PROFILES_CONTENT: &str = r#"
+        [default]
+        username = "me"
+        password = "c2VjcmV0" # secret
+    "#;
+
+    #[cfg(not(feature = "base64"))]
+    const PROFILES_CONTENT: &str = r#"
+        [default]
+        username = "me"
+        password = "secret"
+    "#;
+
+    fn options(polling: bool) -> CredentialsFileWatchOptions {
+        CredentialsFileWatchOptions {
+            debounce: Duration::from_millis(20),
+            poll_interval: Duration::from_millis(20),
+            polling,
+        }
+    }
+
+    fn emits_changes_and_load_failures(polling: bool) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let credentials_path = tempdir.path().join("credentials");
+
+            let watcher = CredentialsFileWatcher::<Credentials>::watch_with(
+                &credentials_path,
+                None,
+                options(polling),
+            )
+            .await?;
+
+            std::fs::write(&credentials_path, PROFILES_CONTENT)?;
+            match watcher.next_event().await {
+                Some(CredentialsFileEvent::Changed(profiles_diff)) => {
+                    let profile_default_expected = Profile::new_default(Credentials {
+                        username: Username(String::from("me")),
+                        password: Password::new("secret"),
+                    });
+                    assert_eq!(
+                        Some(&profile_default_expected),
+                        profiles_diff
+                            .added
+                            .get(Profile::<Credentials>::DEFAULT_NAME)
+                    );
+                    assert!(profiles_diff.changed.is_empty());
+                    assert!(profiles_diff.removed.is_empty());
+                }
+                event => panic!("Expected `Changed` event, but got `{:?}`.", event),
+            }
+
+            std::fs::write(&credentials_path, "garbage")?;
+            match watcher.next_event().await {
+                Some(CredentialsFileEvent::LoadFailed(_)) => {}
+                event => panic!("Expected `LoadFailed` event, but got `{:?}`.", event),
+            }
+
+            std::fs::remove_file(&credentials_path)?;
+            match watcher.next_event().await {
+                Some(CredentialsFileEvent::Changed(profiles_diff)) => {
+                    assert!(profiles_diff.added.is_empty());
+                    assert!(profiles_diff
+                        .removed
+                        .contains(Profile::<Credentials>::DEFAULT_NAME));
+                }
+                event => panic!("Expected `Changed` event, but got `{:?}`.", event),
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn native_watcher_emits_changes_and_load_failures() -> Result<(), Box<dyn std::error::Error>> {
+        emits_changes_and_load_failures(false)
+    }
+
+    #[test]
+    fn polling_watcher_emits_changes_and_load_failures() -> Result<(), Box<dyn std::error::Error>> {
+        emits_changes_and_load_failures(true)
+    }
+}
@@ -0,0 +1,334 @@
+use std::path::Path;
+
+use credent_fs_model::{Error, IntegrityKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{credentials_document::CredentialsDocument, CREDENTIALS_FILE_VERSION};
+
+/// Key in the credentials file whose value is the HMAC of its contents.
+pub(crate) const HMAC_KEY: &str = "hmac";
+
+/// Signs and verifies the contents of a credentials file with HMAC-SHA256.
+///
+/// The HMAC covers the layout version, metadata, and profiles as they are
+/// serialized with sorted keys, so changes to formatting, comments, or the
+/// order of profiles and fields do not invalidate it, but changes to any value
+/// do.
+///
+/// The HMAC does not cover the file's path, so a signed file that is copied to
+/// another path verifies with the same key. Use a different key per file if
+/// that matters.
+#[derive(Debug)]
+pub(crate) struct CredentialsIntegrity;
+
+impl CredentialsIntegrity {
+    /// Records the HMAC of the document's contents in the document.
+    ///
+    /// # Parameters
+    ///
+    /// * `document`: Document to sign.
+    /// * `integrity_key`: Secret to sign the document with.
+    pub(crate) async fn sign(
+        document: &mut CredentialsDocument,
+        integrity_key: &IntegrityKey,
+    ) -> Result<(), Error> {
+        let key = Self::key_bytes(integrity_key).await?;
+        let hmac = Self::mac(&key, document, CREDENTIALS_FILE_VERSION)?
+            .finalize()
+            .into_bytes();
+        document.hmac = Some(hmac.iter().map(|byte| format!("{byte:02x}")).collect());
+
+        Ok(())
+    }
+
+    /// Returns an error if the document's HMAC does not match its contents.
+    ///
+    /// # Parameters
+    ///
+    /// * `document`: Document to verify.
+    /// * `integrity_key`: Secret that the document was signed with.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) async fn verify(
+        document: &CredentialsDocument,
+        integrity_key: &IntegrityKey,
+        credentials_path: &Path,
+    ) -> Result<(), Error> {
+        let hmac = document.hmac.as_deref().ok_or_else(|| {
+            let credentials_path = credentials_path.to_owned();
+            Error::CredentialsFileIntegrityMissing { credentials_path }
+        })?;
+
+        let key = Self::key_bytes(integrity_key).await?;
+        let mac = Self::mac(&key, document, document.version_source)?;
+        Self::hex_decode(hmac)
+            .and_then(|hmac| mac.verify_slice(&hmac).ok())
+            .ok_or_else(|| {
+                let credentials_path = credentials_path.to_owned();
+                Error::CredentialsFileIntegrityMismatch { credentials_path }
+            })
+    }
+
+    fn mac(
+        key: &[u8],
+        document: &CredentialsDocument,
+        version: u32,
+    ) -> Result<Hmac<Sha256>, Error> {
+        let contents = document.contents_signed(version).map_err(|error| {
+            let profile_names = document.profiles.keys().cloned().collect();
+            Error::CredentialsFileSerialize {
+                profile_names,
+                error,
+            }
+        })?;
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length.");
+        mac.update(contents.as_bytes());
+        Ok(mac)
+    }
+
+    async fn key_bytes(integrity_key: &IntegrityKey) -> Result<Vec<u8>, Error> {
+        match integrity_key {
+            IntegrityKey::Secret(secret) => Ok(secret.clone()),
+            #[cfg(feature = "integrity-keyring")]
+            IntegrityKey::Keyring { service, user } => {
+                let entry_read = blocking::unblock({
+                    let service = service.clone();
+                    let user = user.clone();
+                    move || {
+                        keyring::Entry::new(&service, &user).and_then(|entry| entry.get_password())
+                    }
+                })
+                .await;
+
+                entry_read.map(String::into_bytes).map_err(|error| {
+                    let service = service.clone();
+                    let user = user.clone();
+                    let error = Box::new(error);
+                    Error::IntegrityKeyringRead {
+                        service,
+                        user,
+                        error,
+                    }
+                })
+            }
+        }
+    }
+
+    fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+        hex.as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .filter(|pair| pair.len() == 2)
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use credent_fs_model::{AppName, Error, IntegrityKey};
+    use credent_model::{Credentials, Password, Profile, Username};
+
+    use crate::{
+        CredentialsDir, CredentialsFileLoader, CredentialsFileOptions, CredentialsFileStorer,
+        CredentialsLayer, CredentialsLayers, CredentialsPathResolver, CredentialsTarget,
+    };
+
+    fn target(tempdir: &tempfile::TempDir, secret: &str) -> CredentialsTarget<'static> {
+        let resolver = CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>);
+        CredentialsTarget::new(resolver).with_options(
+            CredentialsFileOptions::new()
+                .with_integrity_key(IntegrityKey::Secret(secret.as_bytes().to_vec())),
+        )
+    }
+
+    #[test]
+    fn load_returns_err_integrity_mismatch_when_file_is_changed(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir, "key");
+            let profile = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store(target.clone(), &profile).await?;
+
+            let profile_loaded = CredentialsFileLoader::<Credentials>::load(target.clone()).await?;
+            assert_eq!(Some(profile), profile_loaded);
+
+            let credentials_path = target.path()?;
+            let contents = std::fs::read_to_string(&credentials_path)?;
+            assert!(contents.contains("hmac = '"));
+            std::fs::write(&credentials_path, contents.replace("'me'", "'mallory'"))?;
+
+            let load_result = CredentialsFileLoader::<Credentials>::load(target).await;
+            assert!(
+                matches!(
+                    load_result,
+                    Err(Error::CredentialsFileIntegrityMismatch { .. })
+                ),
+                "Expected `load_result` to return `CredentialsFileIntegrityMismatch` error, but got `{:?}`.",
+                load_result
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn load_returns_err_integrity_mismatch_when_key_differs(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let profile = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store(target(&tempdir, "key"), &profile).await?;
+
+            let load_result =
+                CredentialsFileLoader::<Credentials>::load(target(&tempdir, "other")).await;
+            assert!(matches!(
+                load_result,
+                Err(Error::CredentialsFileIntegrityMismatch { .. })
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn sign_accepts_unsigned_file() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir, "key");
+            let credentials_path = target.path()?;
+            std::fs::write(
+                &credentials_path,
+                "[default]\nusername = 'me'\npassword = 'c2VjcmV0'\n",
+            )?;
+
+            let load_result = CredentialsFileLoader::<Credentials>::load(target.clone()).await;
+            assert!(matches!(
+                load_result,
+                Err(Error::CredentialsFileIntegrityMissing { .. })
+            ));
+
+            CredentialsFileStorer::<Credentials>::sign(target.clone()).await?;
+            let profile_loaded = CredentialsFileLoader::<Credentials>::load(target).await?;
+            assert!(profile_loaded.is_some());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn store_file_returns_err_when_signed_file_is_written_without_key(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir, "key");
+            let profile = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store(target.clone(), &profile).await?;
+            let credentials_path = target.path()?;
+            let contents = std::fs::read_to_string(&credentials_path)?;
+
+            let store_result =
+                CredentialsFileStorer::store_file(&profile, &credentials_path, None, None).await;
+
+            assert!(
+                matches!(
+                    store_result,
+                    Err(Error::CredentialsFileIntegrityKeyRequired { .. })
+                ),
+                "Expected `store_result` to return `CredentialsFileIntegrityKeyRequired` error, but got `{:?}`.",
+                store_result
+            );
+            assert_eq!(contents, std::fs::read_to_string(&credentials_path)?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn load_file_and_load_layered_verify_with_key() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir, "key");
+            let integrity_key = IntegrityKey::Secret(b"key".to_vec());
+            let profile = Profile::new_default(Credentials {
+                username: Username(String::from("me")),
+                password: Password::new("secret"),
+            });
+            CredentialsFileStorer::store(target.clone(), &profile).await?;
+            let credentials_path = target.path()?;
+            let contents = std::fs::read_to_string(&credentials_path)?;
+            std::fs::write(&credentials_path, contents.replace("'me'", "'mallory'"))?;
+
+            let load_result = CredentialsFileLoader::<Credentials>::load_file(
+                &credentials_path,
+                Some(&integrity_key),
+                None,
+            )
+            .await;
+            assert!(matches!(
+                load_result,
+                Err(Error::CredentialsFileIntegrityMismatch { .. })
+            ));
+
+            let layers = CredentialsLayers::new().with_layer(
+                CredentialsLayer::read_only(&credentials_path).with_integrity_key(integrity_key),
+            );
+            let load_result = CredentialsFileLoader::<Credentials>::load_layered(&layers).await;
+            assert!(matches!(
+                load_result,
+                Err(Error::CredentialsFileIntegrityMismatch { .. })
+            ));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn load_verifies_when_profiles_are_reordered() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir, "key");
+            let credentials_path = target.path()?;
+            std::fs::write(
+                &credentials_path,
+                "[a]\nusername = 'a'\npassword = 'c2VjcmV0'\n\n\
+                [b]\nusername = 'b'\npassword = 'c2VjcmV0'\n",
+            )?;
+            CredentialsFileStorer::<Credentials>::sign(target.clone()).await?;
+
+            let contents = std::fs::read_to_string(&credentials_path)?;
+            let (header, profiles) = contents
+                .split_once("[a]")
+                .ok_or("Expected profile `a` in the credentials file.")?;
+            let (profile_a, profile_b) = profiles
+                .split_once("[b]")
+                .ok_or("Expected profile `b` in the credentials file.")?;
+            std::fs::write(
+                &credentials_path,
+                format!("{header}[b]{profile_b}\n[a]{profile_a}"),
+            )?;
+
+            let profiles = CredentialsFileLoader::<Credentials>::load_all(target).await?;
+            assert_eq!(Some(2), profiles.map(|profiles| profiles.len()));
+
+            Ok(())
+        })
+    }
+}
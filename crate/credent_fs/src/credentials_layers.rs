@@ -0,0 +1,209 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use credent_fs_model::{AppId, Error, IntegrityKey};
+
+use crate::{
+    credentials_secret_resolver::CredentialsSecretResolver, CredentialsAuditLog, CredentialsDir,
+    CredentialsPathResolver, SECRET_COMMAND_TIMEOUT,
+};
+
+/// A credentials file that forms one layer of [`CredentialsLayers`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsLayer {
+    /// Path to the credentials file.
+    pub path: PathBuf,
+    /// Whether profiles are stored to this layer.
+    pub writable: bool,
+    /// Whether profiles' `password_command`s in this layer may be run.
+    pub secret_commands_allowed: bool,
+    /// Key to verify the file with when loading, and to sign it with when
+    /// storing.
+    pub integrity_key: Option<IntegrityKey>,
+}
+
+impl CredentialsLayer {
+    /// Returns a layer that is only read from.
+    ///
+    /// `password_command`s in the layer are not run unless allowed with
+    /// [`with_secret_commands_allowed`].
+    ///
+    /// [`with_secret_commands_allowed`]: Self::with_secret_commands_allowed
+    pub fn read_only<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            writable: false,
+            secret_commands_allowed: false,
+            integrity_key: None,
+        }
+    }
+
+    /// Returns a layer that profiles are stored to.
+    pub fn writable<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            writable: true,
+            secret_commands_allowed: true,
+            integrity_key: None,
+        }
+    }
+
+    /// Sets whether profiles' `password_command`s in this layer may be run.
+    pub fn with_secret_commands_allowed(mut self, secret_commands_allowed: bool) -> Self {
+        self.secret_commands_allowed = secret_commands_allowed;
+        self
+    }
+
+    /// Sets the key to verify this layer's file with when loading, and to
+    /// sign it with when storing.
+    pub fn with_integrity_key(mut self, integrity_key: IntegrityKey) -> Self {
+        self.integrity_key = Some(integrity_key);
+        self
+    }
+}
+
+/// Ordered list of credentials files, where later layers override earlier
+/// ones.
+///
+/// At most one layer is writable -- adding a writable layer makes previously
+/// added layers read only.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsLayers {
+    /// Layers from lowest to highest precedence.
+    layers: Vec<CredentialsLayer>,
+    /// Maximum duration to wait for a `password_command` to complete.
+    secret_command_timeout: Duration,
+    /// Log to record each access to the layers in.
+    audit_log: Option<CredentialsAuditLog>,
+}
+
+impl CredentialsLayers {
+    /// Returns an empty list of layers.
+    pub fn new() -> Self {
+        Self {
+            layers: Vec::new(),
+            secret_command_timeout: SECRET_COMMAND_TIMEOUT,
+            audit_log: None,
+        }
+    }
+
+    /// Returns the system, user, and optionally project-local layers for an
+    /// application.
+    ///
+    /// The layers are, in order:
+    ///
+    /// 1. [`CredentialsDir::System`]: read only.
+    /// 2. [`CredentialsDir::Config`]: writable, and respects the
+    ///    `<APP>_CREDENTIALS_FILE` environment variable.
+    /// 3. [`CredentialsDir::ProjectLocal`]: read only, if `project_dir` is
+    ///    provided.
+    ///
+    /// Only the user layer may run `password_command`s.
+    ///
+    /// # Parameters
+    ///
+    /// * `app_id`: Identity of the application whose credentials to load, as an
+    ///   [`AppId`], `AppName`, or `&AppNameBuf`.
+    /// * `project_dir`: Directory of the project with local overrides.
+    pub fn standard<'s, A>(app_id: A, project_dir: Option<&Path>) -> Result<Self, Error>
+    where
+        A: Into<AppId<'s>>,
+    {
+        let resolver = CredentialsPathResolver::new(app_id);
+        let system_path = resolver
+            .clone()
+            .with_dir(CredentialsDir::System)
+            .with_env_var(None::<String>)
+            .resolve()?;
+        let user_path = resolver.clone().resolve()?;
+
+        let mut layers = Self::new()
+            .with_layer(CredentialsLayer::read_only(system_path))
+            .with_layer(CredentialsLayer::writable(user_path));
+
+        if let Some(project_dir) = project_dir {
+            let project_path = resolver
+                .with_dir(CredentialsDir::ProjectLocal(project_dir.to_path_buf()))
+                .with_env_var(None::<String>)
+                .resolve()?;
+            layers = layers.with_layer(CredentialsLayer::read_only(project_path));
+        }
+
+        Ok(layers)
+    }
+
+    /// Adds a layer that overrides the existing layers.
+    pub fn with_layer(mut self, layer: CredentialsLayer) -> Self {
+        if layer.writable {
+            self.layers
+                .iter_mut()
+                .for_each(|layer| layer.writable = false);
+        }
+        self.layers.push(layer);
+        self
+    }
+
+    /// Sets the maximum duration to wait for a `password_command` to complete.
+    ///
+    /// Defaults to [`SECRET_COMMAND_TIMEOUT`].
+    pub fn with_secret_command_timeout(mut self, secret_command_timeout: Duration) -> Self {
+        self.secret_command_timeout = secret_command_timeout;
+        self
+    }
+
+    /// Returns the maximum duration to wait for a `password_command` to
+    /// complete.
+    pub fn secret_command_timeout(&self) -> Duration {
+        self.secret_command_timeout
+    }
+
+    /// Records each load and store through these layers in the given audit
+    /// log.
+    pub fn with_audit_log(mut self, audit_log: CredentialsAuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Returns the audit log that accesses are recorded in, if any.
+    pub fn audit_log(&self) -> Option<&CredentialsAuditLog> {
+        self.audit_log.as_ref()
+    }
+
+    /// Returns the layer that profiles are stored to, if any.
+    pub fn writable_layer(&self) -> Option<&CredentialsLayer> {
+        self.layers.iter().find(|layer| layer.writable)
+    }
+
+    /// Returns an iterator over the layers, from lowest to highest precedence.
+    pub fn iter(&self) -> std::slice::Iter<'_, CredentialsLayer> {
+        self.layers.iter()
+    }
+
+    /// Returns the resolver for secret references in the given layer.
+    pub(crate) fn secret_resolver(&self, layer: &CredentialsLayer) -> CredentialsSecretResolver {
+        CredentialsSecretResolver::new(layer.secret_commands_allowed, self.secret_command_timeout)
+    }
+}
+
+impl Default for CredentialsLayers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> IntoIterator for &'a CredentialsLayers {
+    type IntoIter = std::slice::Iter<'a, CredentialsLayer>;
+    type Item = &'a CredentialsLayer;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
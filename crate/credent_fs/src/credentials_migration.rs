@@ -0,0 +1,189 @@
+use std::{convert::TryFrom, path::Path};
+
+use credent_fs_model::{CredentialsMeta, Error};
+use toml::{value::Table, Value};
+
+use crate::{
+    credentials_document::{INHERITS_KEY, PASSWORD_SOURCE_KEYS},
+    credentials_integrity::HMAC_KEY,
+};
+
+/// Key in the credentials file that records the version of its layout.
+pub(crate) const VERSION_KEY: &str = "version";
+
+/// Key in the credentials file that holds profile aliases.
+const ALIASES_KEY: &str = "aliases";
+
+/// Version of the credentials file layout that is written.
+///
+/// Files without a `version` key are version `0`.
+pub const CREDENTIALS_FILE_VERSION: u32 = 1;
+
+/// Upgrades the layout of a credentials file from the previous version.
+#[derive(Debug)]
+pub(crate) struct CredentialsMigration {
+    /// Version that this migration upgrades to.
+    pub(crate) version: u32,
+    /// What the migration changes, shown in dry runs.
+    pub(crate) description: &'static str,
+    /// Upgrades the file's top level table from the previous version.
+    migrate: fn(&mut Table),
+}
+
+impl CredentialsMigration {
+    /// Migrations for each version, in ascending order.
+    ///
+    /// The last migration's version must be [`CREDENTIALS_FILE_VERSION`].
+    const ALL: &'static [CredentialsMigration] = &[CredentialsMigration {
+        version: 1,
+        description: "Record the credentials file version. \
+            Profiles named `version`, `hmac`, `default_profile`, or `aliases` are renamed, \
+            and their names are kept as aliases.",
+        migrate: Self::keys_reserved_rename,
+    }];
+
+    /// Returns the migrations that upgrade a file from the given version.
+    ///
+    /// # Parameters
+    ///
+    /// * `version`: Version of the file's layout.
+    pub(crate) fn pending(version: u32) -> impl Iterator<Item = &'static CredentialsMigration> {
+        Self::ALL
+            .iter()
+            .filter(move |migration| migration.version > version)
+    }
+
+    /// Removes the `version` key from the table and upgrades the table to the
+    /// current version.
+    ///
+    /// Returns the version that the table was in.
+    ///
+    /// # Parameters
+    ///
+    /// * `table`: Top level table of the credentials file.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) fn migrate(table: &mut Table, credentials_path: &Path) -> Result<u32, Error> {
+        let version = Self::version_take(table, credentials_path)?;
+        Self::pending(version).for_each(|migration| (migration.migrate)(table));
+
+        Ok(version)
+    }
+
+    fn version_take(table: &mut Table, credentials_path: &Path) -> Result<u32, Error> {
+        // Rebuild the table, as `remove` would move the last key into its
+        // position.
+        //
+        // A `version` table is a profile in a version `0` file, which is
+        // renamed when migrating.
+        let mut version_value = None;
+        *table = std::mem::take(table)
+            .into_iter()
+            .filter_map(|(key, value)| {
+                if key == VERSION_KEY && !value.is_table() {
+                    version_value = Some(value);
+                    None
+                } else {
+                    Some((key, value))
+                }
+            })
+            .collect();
+
+        let version = match version_value {
+            None => return Ok(0),
+            Some(Value::Integer(version)) if version >= 0 => version,
+            Some(_) => {
+                let error = <toml::de::Error as serde::de::Error>::custom(format!(
+                    "`{VERSION_KEY}` must be a non-negative integer."
+                ));
+                let credentials_path = credentials_path.to_owned();
+                return Err(Error::CredentialsFileDeserialize {
+                    credentials_path,
+                    profile_name: None,
+                    location: None,
+                    error,
+                });
+            }
+        };
+
+        match u32::try_from(version) {
+            Ok(version) if version <= CREDENTIALS_FILE_VERSION => Ok(version),
+            _ => {
+                let credentials_path = credentials_path.to_owned();
+                Err(Error::CredentialsFileVersionUnsupported {
+                    version,
+                    version_supported: CREDENTIALS_FILE_VERSION,
+                    credentials_path,
+                })
+            }
+        }
+    }
+
+    /// Renames profiles that use keys reserved in version `1`, and records
+    /// their original names as aliases.
+    ///
+    /// In version `0`, `version` and `hmac` tables are profiles, and so are
+    /// metadata keys that hold a profile instead of metadata.
+    fn keys_reserved_rename(table: &mut Table) {
+        let renames = [VERSION_KEY, HMAC_KEY]
+            .iter()
+            .chain(CredentialsMeta::KEYS)
+            .filter(|key| {
+                table
+                    .get(**key)
+                    .is_some_and(|value| Self::value_is_profile(key, value))
+            })
+            .map(|key| {
+                let profile_name_new = (1..)
+                    .map(|n| match n {
+                        1 => format!("{key}_profile"),
+                        n => format!("{key}_profile_{n}"),
+                    })
+                    .find(|profile_name_new| !table.contains_key(profile_name_new))
+                    .expect("Expected a free profile name.");
+                (key.to_string(), profile_name_new)
+            })
+            .collect::<Vec<_>>();
+        if renames.is_empty() {
+            return;
+        }
+
+        // Rebuild the table so that renamed profiles keep their position.
+        *table = std::mem::take(table)
+            .into_iter()
+            .map(|(key, value)| {
+                match renames
+                    .iter()
+                    .find(|(profile_name, _)| *profile_name == key)
+                {
+                    Some((_, profile_name_new)) => (profile_name_new.clone(), value),
+                    None => (key, value),
+                }
+            })
+            .collect();
+        if let Value::Table(aliases) = table
+            .entry(ALIASES_KEY)
+            .or_insert_with(|| Value::Table(Table::new()))
+        {
+            renames
+                .into_iter()
+                .for_each(|(profile_name, profile_name_new)| {
+                    aliases.insert(profile_name, Value::String(profile_name_new));
+                });
+        }
+    }
+
+    /// Returns whether a top level value in a version `0` file is a profile.
+    ///
+    /// An `aliases` table is metadata unless it has a password, a secret
+    /// reference, `inherits`, or a value that is not a profile name.
+    fn value_is_profile(key: &str, value: &Value) -> bool {
+        match value {
+            Value::Table(table) if key == ALIASES_KEY => table.iter().any(|(key, value)| {
+                !value.is_str()
+                    || key == INHERITS_KEY
+                    || PASSWORD_SOURCE_KEYS.contains(&key.as_str())
+            }),
+            value => value.is_table(),
+        }
+    }
+}
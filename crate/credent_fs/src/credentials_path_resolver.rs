@@ -0,0 +1,387 @@
+use std::{env, ffi::OsString, path::PathBuf};
+
+use credent_fs_model::{AppId, AppName, AppNameBuf, Error};
+
+use crate::CREDENTIALS_FILE_NAME;
+
+/// Suffix of the environment variable that overrides the credentials file
+/// path.
+pub const CREDENTIALS_FILE_ENV_SUFFIX: &str = "_CREDENTIALS_FILE";
+
+/// Directory that holds the credentials file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CredentialsDir {
+    /// Application directory within the user's configuration directory.
+    ///
+    /// `<app>` is the application's [`AppId::dir_relative`], which includes
+    /// the organization if there is one.
+    ///
+    /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>`
+    /// * `Linux`: `$XDG_CONFIG_HOME` or `$HOME/.config/<app>`
+    /// * `OS X`: `$HOME/Library/Application Support/<app>`
+    #[default]
+    Config,
+    /// Application directory within the user's data directory.
+    ///
+    /// * `Windows`: `C:\Users\%USER%\AppData\Roaming\<app>`
+    /// * `Linux`: `$XDG_DATA_HOME` or `$HOME/.local/share/<app>`
+    /// * `OS X`: `$HOME/Library/Application Support/<app>`
+    Data,
+    /// Application directory within the system-wide configuration directory.
+    ///
+    /// * `Windows`: `%ProgramData%\<app>`
+    /// * `Linux` / `OS X`: `/etc/<app>`
+    System,
+    /// `.<application>` directory within the given project directory.
+    ///
+    /// For the `credent` application this is `<project>/.credent`. The
+    /// directory is named after the application rather than always being
+    /// `.credent`, so that the files of several applications in one project
+    /// are kept apart. The organization is not part of the name.
+    ProjectLocal(PathBuf),
+    /// The given directory, used as is.
+    Custom(PathBuf),
+}
+
+/// Resolves the path to an application's credentials file.
+///
+/// The path is resolved in the following order of precedence:
+///
+/// 1. The file in the environment variable, if set and non-empty. By default
+///    this is `<APP>_CREDENTIALS_FILE`, where `<APP>` is the upper cased
+///    application name with non-alphanumeric characters replaced by `_`. If
+///    the application has an organization, this is prefixed with `<ORG>_`.
+/// 2. The file name within the [`CredentialsDir`], which defaults to the
+///    user's configuration directory.
+///
+/// With no customization, the path is the same as [`CredentialsFile::path`].
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use credent_fs::{CredentialsDir, CredentialsPathResolver};
+///
+/// let credentials_path = CredentialsPathResolver::new(AppName::new("credent"))
+///     .with_dir(CredentialsDir::Data)
+///     .with_file_name("credentials.toml")
+///     .resolve()?;
+/// ```
+///
+/// [`CredentialsFile::path`]: crate::CredentialsFile::path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsPathResolver<'s> {
+    /// Identity of the application whose credentials file to resolve.
+    app_id: AppId<'s>,
+    /// Name of the credentials file.
+    file_name: String,
+    /// Directory that holds the credentials file.
+    dir: CredentialsDir,
+    /// Environment variable that overrides the credentials file path.
+    env_var: Option<String>,
+}
+
+impl<'s> CredentialsPathResolver<'s> {
+    /// Returns a new `CredentialsPathResolver` with the default settings.
+    ///
+    /// # Parameters
+    ///
+    /// * `app_id`: Identity of the application whose credentials file to
+    ///   resolve, as an [`AppId`], [`AppName`], or `&AppNameBuf`.
+    pub fn new<A>(app_id: A) -> Self
+    where
+        A: Into<AppId<'s>>,
+    {
+        let app_id = app_id.into();
+        Self {
+            app_id,
+            file_name: String::from(CREDENTIALS_FILE_NAME),
+            dir: CredentialsDir::default(),
+            env_var: Some(Self::env_var_default(app_id)),
+        }
+    }
+
+    /// Sets the name of the credentials file.
+    ///
+    /// Defaults to [`CREDENTIALS_FILE_NAME`].
+    pub fn with_file_name<S>(mut self, file_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Sets the directory that holds the credentials file.
+    ///
+    /// Defaults to [`CredentialsDir::Config`].
+    pub fn with_dir(mut self, dir: CredentialsDir) -> Self {
+        self.dir = dir;
+        self
+    }
+
+    /// Sets the environment variable that overrides the credentials file
+    /// path.
+    ///
+    /// Pass `None` to ignore the environment.
+    pub fn with_env_var<S>(mut self, env_var: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.env_var = env_var.map(Into::into);
+        self
+    }
+
+    /// Returns the name of the application whose credentials file to resolve.
+    pub fn app_name(&self) -> AppName<'s> {
+        self.app_id.application()
+    }
+
+    /// Returns the identity of the application whose credentials file to
+    /// resolve.
+    pub fn app_id(&self) -> AppId<'s> {
+        self.app_id
+    }
+
+    /// Returns the name of the credentials file.
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns the directory that holds the credentials file.
+    pub fn dir(&self) -> &CredentialsDir {
+        &self.dir
+    }
+
+    /// Returns the environment variable that overrides the credentials file
+    /// path.
+    pub fn env_var(&self) -> Option<&str> {
+        self.env_var.as_deref()
+    }
+
+    /// Returns the path to the credentials file.
+    ///
+    /// The file's existence is not checked -- that is the responsibility of the
+    /// caller.
+    pub fn resolve(&self) -> Result<PathBuf, Error> {
+        self.resolve_with_env(|env_var| env::var_os(env_var))
+    }
+
+    /// Returns the path to the credentials file, reading environment
+    /// variables through the given function instead of the process
+    /// environment.
+    ///
+    /// # Parameters
+    ///
+    /// * `env_var_value`: Returns the value of the named environment variable,
+    ///   if set.
+    pub fn resolve_with_env<F>(&self, env_var_value: F) -> Result<PathBuf, Error>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        if let Some(credentials_path) = self.env_var_path(env_var_value) {
+            return Ok(credentials_path);
+        }
+
+        let app_dir = self.app_id.dir_relative();
+        let credentials_dir = match &self.dir {
+            CredentialsDir::Config => dirs::config_dir()
+                .map(|config_dir| config_dir.join(&app_dir))
+                .ok_or(Error::UserConfigDirNotFound)?,
+            CredentialsDir::Data => dirs::data_dir()
+                .map(|data_dir| data_dir.join(&app_dir))
+                .ok_or(Error::UserDataDirNotFound)?,
+            CredentialsDir::System => Self::system_config_dir()
+                .map(|system_config_dir| system_config_dir.join(&app_dir))
+                .ok_or(Error::SystemConfigDirNotFound)?,
+            CredentialsDir::ProjectLocal(project_dir) => {
+                project_dir.join(format!(".{}", self.app_id.application()))
+            }
+            CredentialsDir::Custom(dir) => dir.clone(),
+        };
+
+        Ok(credentials_dir.join(&self.file_name))
+    }
+
+    #[cfg(unix)]
+    fn system_config_dir() -> Option<PathBuf> {
+        Some(PathBuf::from("/etc"))
+    }
+
+    #[cfg(windows)]
+    fn system_config_dir() -> Option<PathBuf> {
+        env::var_os("ProgramData").map(PathBuf::from)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn system_config_dir() -> Option<PathBuf> {
+        None
+    }
+
+    fn env_var_path<F>(&self, env_var_value: F) -> Option<PathBuf>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        self.env_var
+            .as_deref()
+            .and_then(env_var_value)
+            .filter(|credentials_path| !credentials_path.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Returns `<ORG>_<APP>_CREDENTIALS_FILE` for the given application.
+    fn env_var_default(app_id: AppId<'_>) -> String {
+        app_id
+            .organization()
+            .iter()
+            .flat_map(|organization| organization.chars().chain(std::iter::once('_')))
+            .chain(app_id.application().chars())
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .chain(CREDENTIALS_FILE_ENV_SUFFIX.chars())
+            .collect()
+    }
+}
+
+impl<'s> From<AppId<'s>> for CredentialsPathResolver<'s> {
+    fn from(app_id: AppId<'s>) -> Self {
+        Self::new(app_id)
+    }
+}
+
+impl<'s> From<AppName<'s>> for CredentialsPathResolver<'s> {
+    fn from(app_name: AppName<'s>) -> Self {
+        Self::new(app_name)
+    }
+}
+
+impl<'s> From<&'s AppNameBuf> for CredentialsPathResolver<'s> {
+    fn from(app_name_buf: &'s AppNameBuf) -> Self {
+        Self::new(app_name_buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsString, path::PathBuf};
+
+    use credent_fs_model::{AppId, AppName};
+
+    use super::{CredentialsDir, CredentialsPathResolver};
+
+    #[test]
+    fn resolves_config_dir_by_default() -> Result<(), Box<dyn std::error::Error>> {
+        let credentials_path =
+            CredentialsPathResolver::new(AppName::new("credent_resolver_default")).resolve()?;
+
+        let credentials_path_expected = dirs::config_dir()
+            .expect("Expected config dir to exist.")
+            .join("credent_resolver_default")
+            .join("credentials");
+        assert_eq!(credentials_path_expected, credentials_path);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_data_dir_with_file_name() -> Result<(), Box<dyn std::error::Error>> {
+        let credentials_path = CredentialsPathResolver::new(AppName::new("credent_resolver_data"))
+            .with_dir(CredentialsDir::Data)
+            .with_file_name("credentials.toml")
+            .resolve()?;
+
+        let credentials_path_expected = dirs::data_dir()
+            .expect("Expected data dir to exist.")
+            .join("credent_resolver_data")
+            .join("credentials.toml");
+        assert_eq!(credentials_path_expected, credentials_path);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_organization_dir_with_app_id() -> Result<(), Box<dyn std::error::Error>> {
+        let app_id = AppId::new(AppName::new("tool-a")).with_organization(AppName::new("acme"));
+        let resolver = CredentialsPathResolver::new(app_id);
+        let credentials_path = resolver.resolve()?;
+
+        let credentials_path_expected = dirs::config_dir()
+            .expect("Expected config dir to exist.")
+            .join(app_id.dir_relative())
+            .join("credentials");
+        assert_eq!(credentials_path_expected, credentials_path);
+        assert_eq!(Some("ACME_TOOL_A_CREDENTIALS_FILE"), resolver.env_var());
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_project_local_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let credentials_path = CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::ProjectLocal(PathBuf::from("project")))
+            .resolve()?;
+
+        assert_eq!(
+            PathBuf::from("project")
+                .join(".credent")
+                .join("credentials"),
+            credentials_path
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_project_local_dir_named_after_application() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let app_id = AppId::new(AppName::new("tool-a")).with_organization(AppName::new("acme"));
+        let credentials_path = CredentialsPathResolver::new(app_id)
+            .with_dir(CredentialsDir::ProjectLocal(PathBuf::from("project")))
+            .resolve_with_env(|_| None)?;
+
+        assert_eq!(
+            PathBuf::from("project").join(".tool-a").join("credentials"),
+            credentials_path
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn env_var_takes_precedence() -> Result<(), Box<dyn std::error::Error>> {
+        let resolver = CredentialsPathResolver::new(AppName::new("credent-resolver.env"))
+            .with_dir(CredentialsDir::Custom(PathBuf::from("custom")));
+        assert_eq!(
+            Some("CREDENT_RESOLVER_ENV_CREDENTIALS_FILE"),
+            resolver.env_var()
+        );
+
+        let env_empty = |env_var: &str| {
+            assert_eq!("CREDENT_RESOLVER_ENV_CREDENTIALS_FILE", env_var);
+            Some(OsString::new())
+        };
+        assert_eq!(
+            PathBuf::from("custom").join("credentials"),
+            resolver.resolve_with_env(env_empty)?
+        );
+
+        let env_set = |_: &str| Some(OsString::from("elsewhere/credentials"));
+        assert_eq!(
+            PathBuf::from("elsewhere/credentials"),
+            resolver.resolve_with_env(env_set)?
+        );
+        assert_eq!(
+            PathBuf::from("custom").join("credentials"),
+            resolver
+                .clone()
+                .with_env_var(None::<String>)
+                .resolve_with_env(env_set)?
+        );
+
+        Ok(())
+    }
+}
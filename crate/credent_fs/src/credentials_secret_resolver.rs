@@ -0,0 +1,508 @@
+use std::{
+    io::Read,
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use credent_fs_model::Error;
+use credent_model::Password;
+use toml::{value::Table, Value};
+
+/// Default maximum duration to wait for a `password_command` to complete.
+pub const SECRET_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Key in a profile whose value is the password.
+pub(crate) const PASSWORD_KEY: &str = "password";
+/// Key in a profile whose value is a command that outputs the password.
+pub(crate) const PASSWORD_COMMAND_KEY: &str = "password_command";
+/// Key in a profile whose value is the path to a file containing the password.
+pub(crate) const PASSWORD_FILE_KEY: &str = "password_file";
+/// Keys in a profile that refer to a password stored elsewhere.
+pub(crate) const SECRET_REF_KEYS: &[&str] = &[PASSWORD_COMMAND_KEY, PASSWORD_FILE_KEY];
+
+/// Interval between checks of whether a `password_command` has completed.
+const SECRET_COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Replaces secret references in a profile with the secret they refer to.
+///
+/// A profile may specify `password_command = "pass show work/db"` to use the
+/// output of a command as its password, or `password_file = "/run/secrets/db"`
+/// to use the contents of a file. Trailing newlines are trimmed from the
+/// secret.
+///
+/// Secrets are only resolved for a profile that is loaded by name, so that
+/// loading a whole file does not run every profile's command.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CredentialsSecretResolver {
+    /// Whether `password_command`s may be run.
+    commands_allowed: bool,
+    /// Maximum duration to wait for a `password_command` to complete.
+    command_timeout: Duration,
+}
+
+impl CredentialsSecretResolver {
+    /// Returns a new `CredentialsSecretResolver`.
+    ///
+    /// # Parameters
+    ///
+    /// * `commands_allowed`: Whether `password_command`s may be run.
+    /// * `command_timeout`: Maximum duration to wait for a `password_command`
+    ///   to complete.
+    pub(crate) fn new(commands_allowed: bool, command_timeout: Duration) -> Self {
+        Self {
+            commands_allowed,
+            command_timeout,
+        }
+    }
+
+    /// Returns whether the profile's credentials refer to a secret stored
+    /// elsewhere.
+    pub(crate) fn is_secret_referenced(credentials: &Value) -> bool {
+        match credentials {
+            Value::Table(table) => SECRET_REF_KEYS.iter().any(|key| table.contains_key(*key)),
+            _ => false,
+        }
+    }
+
+    /// Replaces the secret reference in the profile's credentials with the
+    /// secret.
+    ///
+    /// Credentials without a secret reference are returned unchanged.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile, used in errors.
+    /// * `credentials`: TOML value of the profile's credentials.
+    /// * `credentials_path`: Path to the credentials file, used in errors.
+    pub(crate) async fn resolve(
+        &self,
+        profile_name: &str,
+        credentials: Value,
+        credentials_path: &Path,
+    ) -> Result<Value, Error> {
+        let table = match credentials {
+            Value::Table(table) if SECRET_REF_KEYS.iter().any(|key| table.contains_key(*key)) => {
+                table
+            }
+            credentials => return Ok(credentials),
+        };
+
+        let secret = if let Some(Value::String(command)) = table.get(PASSWORD_COMMAND_KEY) {
+            if !self.commands_allowed {
+                let profile_name = profile_name.to_string();
+                let command = command.clone();
+                let credentials_path = credentials_path.to_owned();
+                return Err(Error::SecretCommandRefused {
+                    profile_name,
+                    command,
+                    credentials_path,
+                });
+            }
+            self.command_output(profile_name, command, credentials_path)
+                .await?
+        } else if let Some(Value::String(secret_path)) = table.get(PASSWORD_FILE_KEY) {
+            Self::file_read(profile_name, Path::new(secret_path), credentials_path).await?
+        } else {
+            // Secret references that are not strings are left for the
+            // credentials deserializer to report.
+            return Ok(Value::Table(table));
+        };
+
+        let password = Value::String(Password::new(secret).encoded().to_string());
+        let mut password = Some(password);
+        let table = table
+            .into_iter()
+            .filter_map(|(key, value)| {
+                if SECRET_REF_KEYS.contains(&key.as_str()) {
+                    password
+                        .take()
+                        .map(|password| (PASSWORD_KEY.to_string(), password))
+                } else if key == PASSWORD_KEY {
+                    None
+                } else {
+                    Some((key, value))
+                }
+            })
+            .collect::<Table>();
+
+        Ok(Value::Table(table))
+    }
+
+    async fn command_output(
+        &self,
+        profile_name: &str,
+        command: &str,
+        credentials_path: &Path,
+    ) -> Result<String, Error> {
+        let command_timeout = self.command_timeout;
+        let output = blocking::unblock({
+            let command = command.to_string();
+            move || CommandOutcome::run(&command, command_timeout)
+        })
+        .await;
+
+        let profile_name = profile_name.to_string();
+        let command = command.to_string();
+        let credentials_path = credentials_path.to_owned();
+        match output {
+            CommandOutcome::Completed {
+                exit_code: Some(0),
+                stdout,
+                ..
+            } => String::from_utf8(stdout)
+                .map(Self::trim_newline)
+                .map_err(|error| Error::SecretCommandOutputNotUtf8 {
+                    profile_name,
+                    command,
+                    credentials_path,
+                    error: error.utf8_error(),
+                }),
+            CommandOutcome::Completed {
+                exit_code, stderr, ..
+            } => {
+                let stderr = String::from_utf8_lossy(&stderr).trim_end().to_string();
+                Err(Error::SecretCommandFailed {
+                    profile_name,
+                    command,
+                    credentials_path,
+                    exit_code,
+                    stderr,
+                })
+            }
+            CommandOutcome::TimedOut => Err(Error::SecretCommandTimeout {
+                profile_name,
+                command,
+                credentials_path,
+                timeout: self.command_timeout,
+            }),
+            CommandOutcome::Failed(error) => Err(Error::SecretCommandSpawn {
+                profile_name,
+                command,
+                credentials_path,
+                error,
+            }),
+        }
+    }
+
+    async fn file_read(
+        profile_name: &str,
+        secret_path: &Path,
+        credentials_path: &Path,
+    ) -> Result<String, Error> {
+        async_fs::read_to_string(secret_path)
+            .await
+            .map(Self::trim_newline)
+            .map_err(|error| {
+                let profile_name = profile_name.to_string();
+                let secret_path = secret_path.to_owned();
+                let credentials_path = credentials_path.to_owned();
+                Error::SecretFileRead {
+                    profile_name,
+                    secret_path,
+                    credentials_path,
+                    error,
+                }
+            })
+    }
+
+    fn trim_newline(mut secret: String) -> String {
+        let len = secret.trim_end_matches(&['\r', '\n'][..]).len();
+        secret.truncate(len);
+        secret
+    }
+}
+
+/// Result of running a `password_command`.
+enum CommandOutcome {
+    /// The command ran to completion.
+    Completed {
+        /// Exit code of the command, `None` if it was terminated by a signal.
+        exit_code: Option<i32>,
+        /// Bytes written to stdout.
+        stdout: Vec<u8>,
+        /// Bytes written to stderr.
+        stderr: Vec<u8>,
+    },
+    /// The command did not complete within the timeout.
+    TimedOut,
+    /// The command could not be run.
+    Failed(std::io::Error),
+}
+
+impl CommandOutcome {
+    /// Runs the command through the platform shell, killing it if it does
+    /// not complete within the timeout.
+    fn run(command: &str, timeout: Duration) -> Self {
+        let mut child = match Self::shell_command(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(error) => return CommandOutcome::Failed(error),
+        };
+
+        // Read output on separate threads so that the command does not block
+        // on a full pipe while we wait for it.
+        let stdout_reader = child.stdout.take().map(Self::pipe_read);
+        let stderr_reader = child.stderr.take().map(Self::pipe_read);
+
+        let pipe_join = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+            reader
+                .and_then(|reader| reader.join().ok())
+                .unwrap_or_default()
+        };
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() >= timeout => {
+                    Self::kill(&mut child);
+                    let _wait_result = child.wait();
+
+                    // Processes started by the command are killed with it on
+                    // Unix, which closes the pipes so that the readers finish.
+                    #[cfg(unix)]
+                    {
+                        pipe_join(stdout_reader);
+                        pipe_join(stderr_reader);
+                    }
+                    return CommandOutcome::TimedOut;
+                }
+                Ok(None) => thread::sleep(SECRET_COMMAND_POLL_INTERVAL),
+                Err(error) => return CommandOutcome::Failed(error),
+            }
+        };
+
+        CommandOutcome::Completed {
+            exit_code: status.code(),
+            stdout: pipe_join(stdout_reader),
+            stderr: pipe_join(stderr_reader),
+        }
+    }
+
+    #[cfg(windows)]
+    fn shell_command(command: &str) -> Command {
+        let mut shell_command = Command::new("cmd");
+        shell_command.arg("/C").arg(command);
+        shell_command
+    }
+
+    #[cfg(not(windows))]
+    fn shell_command(command: &str) -> Command {
+        let mut shell_command = Command::new("sh");
+        shell_command.arg("-c").arg(command);
+
+        // Run the command in its own process group, so that the processes it
+        // starts can be killed with it.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell_command, 0);
+
+        shell_command
+    }
+
+    /// Kills the command's process group.
+    #[cfg(unix)]
+    fn kill(child: &mut Child) {
+        use std::convert::TryFrom;
+
+        match libc::pid_t::try_from(child.id()) {
+            // SAFETY: `kill` has no memory safety requirements. The negated
+            // pid refers to the process group created for the command.
+            Ok(pid) => unsafe {
+                libc::kill(-pid, libc::SIGKILL);
+            },
+            Err(_) => {
+                let _kill_result = child.kill();
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn kill(child: &mut Child) {
+        let _kill_result = child.kill();
+    }
+
+    fn pipe_read<R>(mut pipe: R) -> thread::JoinHandle<Vec<u8>>
+    where
+        R: Read + Send + 'static,
+    {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _read_result = pipe.read_to_end(&mut bytes);
+            bytes
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    use credent_fs_model::Error;
+    use credent_model::{Credentials, Password};
+    use toml::Value;
+
+    use super::{CredentialsSecretResolver, SECRET_COMMAND_TIMEOUT};
+
+    #[test]
+    fn resolves_password_file() -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let secret_path = tempdir.path().join("secret");
+        std::fs::write(&secret_path, "secret\n")?;
+        let credentials = toml::from_str::<Value>(&format!(
+            "username = 'me'\npassword_file = '{}'\n",
+            secret_path.display()
+        ))?;
+
+        let credentials = smol::block_on(secret_resolver().resolve(
+            "default",
+            credentials,
+            Path::new("credentials"),
+        ))?;
+        let credentials = credentials.try_into::<Credentials>()?;
+
+        assert_eq!(Password::new("secret"), credentials.password);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_password_command() -> Result<(), Box<dyn std::error::Error>> {
+        let credentials =
+            toml::from_str::<Value>("username = 'me'\npassword_command = 'echo secret'\n")?;
+
+        let credentials = smol::block_on(secret_resolver().resolve(
+            "default",
+            credentials,
+            Path::new("credentials"),
+        ))?;
+        let credentials = credentials.try_into::<Credentials>()?;
+
+        assert_eq!(Password::new("secret"), credentials.password);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn returns_err_secret_command_failed_with_stderr() -> Result<(), Box<dyn std::error::Error>> {
+        let credentials = toml::from_str::<Value>(
+            "username = 'me'\npassword_command = 'echo locked >&2; exit 3'\n",
+        )?;
+
+        let resolve_result = smol::block_on(secret_resolver().resolve(
+            "default",
+            credentials,
+            Path::new("credentials"),
+        ));
+
+        if let Err(Error::SecretCommandFailed {
+            exit_code, stderr, ..
+        }) = &resolve_result
+        {
+            assert_eq!(Some(3), *exit_code);
+            assert_eq!("locked", stderr);
+        } else {
+            panic!(
+                "Expected `resolve_result` to return `SecretCommandFailed` error, but got `{:?}`.",
+                resolve_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn returns_err_secret_command_output_not_utf8_without_output(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Writes `hunter` followed by an invalid byte.
+        let credentials = toml::from_str::<Value>(
+            "username = 'me'\npassword_command = \"printf '\\\\150\\\\165\\\\156\\\\164\\\\145\\\\162\\\\377'\"\n",
+        )?;
+
+        let resolve_result = smol::block_on(secret_resolver().resolve(
+            "default",
+            credentials,
+            Path::new("credentials"),
+        ));
+
+        if let Err(error @ Error::SecretCommandOutputNotUtf8 { .. }) = &resolve_result {
+            let error_debug = format!("{:?}", error);
+            assert!(error_debug.contains("valid_up_to: 6"));
+            assert!(!error_debug.contains("104, 117"));
+        } else {
+            panic!(
+                "Expected `resolve_result` to return `SecretCommandOutputNotUtf8` error, but got `{:?}`.",
+                resolve_result
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn returns_err_secret_command_refused_when_commands_not_allowed(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let credentials =
+            toml::from_str::<Value>("username = 'me'\npassword_command = 'echo secret'\n")?;
+
+        let resolve_result = smol::block_on(
+            CredentialsSecretResolver::new(false, SECRET_COMMAND_TIMEOUT).resolve(
+                "default",
+                credentials,
+                Path::new("credentials"),
+            ),
+        );
+
+        assert!(matches!(
+            resolve_result,
+            Err(Error::SecretCommandRefused { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn returns_err_secret_command_timeout_and_kills_child_processes(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // The background `sleep` keeps stdout open unless it is killed too.
+        let credentials =
+            toml::from_str::<Value>("username = 'me'\npassword_command = 'sleep 10 & sleep 10'\n")?;
+
+        let started = Instant::now();
+        let resolve_result = smol::block_on(
+            CredentialsSecretResolver::new(true, Duration::from_millis(100)).resolve(
+                "default",
+                credentials,
+                Path::new("credentials"),
+            ),
+        );
+
+        if let Err(Error::SecretCommandTimeout { timeout, .. }) = &resolve_result {
+            assert_eq!(Duration::from_millis(100), *timeout);
+        } else {
+            panic!(
+                "Expected `resolve_result` to return `SecretCommandTimeout` error, but got `{:?}`.",
+                resolve_result
+            );
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        Ok(())
+    }
+
+    fn secret_resolver() -> CredentialsSecretResolver {
+        CredentialsSecretResolver::new(true, SECRET_COMMAND_TIMEOUT)
+    }
+}
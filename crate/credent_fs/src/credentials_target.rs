@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use credent_fs_model::{AppId, AppName, AppNameBuf, Error};
+
+use crate::{
+    credentials_secret_resolver::CredentialsSecretResolver, CredentialsDir, CredentialsFile,
+    CredentialsFileOptions, CredentialsPathResolver,
+};
+
+/// Credentials file to load from or store to: where it is, and how to access
+/// it.
+///
+/// The [`CredentialsFileLoader`] and [`CredentialsFileStorer`] functions that
+/// take an application name also take a `CredentialsTarget`, which pairs a
+/// [`CredentialsPathResolver`] with [`CredentialsFileOptions`]. An
+/// application name or resolver converts into a target with the default
+/// options.
+///
+/// [`CredentialsFileLoader`]: crate::CredentialsFileLoader
+/// [`CredentialsFileStorer`]: crate::CredentialsFileStorer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsTarget<'s> {
+    /// Resolves the path to the credentials file.
+    resolver: CredentialsPathResolver<'s>,
+    /// Options for accessing the credentials file.
+    options: CredentialsFileOptions,
+}
+
+impl<'s> CredentialsTarget<'s> {
+    /// Returns a new `CredentialsTarget` with the default options.
+    ///
+    /// # Parameters
+    ///
+    /// * `resolver`: Name of the application whose credentials file to access,
+    ///   or [`CredentialsPathResolver`].
+    pub fn new<R>(resolver: R) -> Self
+    where
+        R: Into<CredentialsPathResolver<'s>>,
+    {
+        Self {
+            resolver: resolver.into(),
+            options: CredentialsFileOptions::default(),
+        }
+    }
+
+    /// Sets the options for accessing the credentials file.
+    pub fn with_options(mut self, options: CredentialsFileOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the resolver of the path to the credentials file.
+    pub fn resolver(&self) -> &CredentialsPathResolver<'s> {
+        &self.resolver
+    }
+
+    /// Returns the options for accessing the credentials file.
+    pub fn options(&self) -> &CredentialsFileOptions {
+        &self.options
+    }
+
+    /// Returns the path to the credentials file.
+    ///
+    /// See [`CredentialsFile::path`].
+    pub fn path(&self) -> Result<PathBuf, Error> {
+        CredentialsFile::path(self.resolver.clone())
+    }
+
+    /// Returns whether profiles' `password_command`s may be run.
+    ///
+    /// Unless set in the options, commands are refused for
+    /// [`CredentialsDir::ProjectLocal`] files.
+    pub fn secret_commands_allowed(&self) -> bool {
+        self.options.secret_commands_allowed().unwrap_or(!matches!(
+            self.resolver.dir(),
+            CredentialsDir::ProjectLocal(_)
+        ))
+    }
+
+    /// Returns the resolver for secret references in this credentials file.
+    pub(crate) fn secret_resolver(&self) -> CredentialsSecretResolver {
+        CredentialsSecretResolver::new(
+            self.secret_commands_allowed(),
+            self.options.secret_command_timeout(),
+        )
+    }
+}
+
+impl<'s> From<CredentialsPathResolver<'s>> for CredentialsTarget<'s> {
+    fn from(resolver: CredentialsPathResolver<'s>) -> Self {
+        Self::new(resolver)
+    }
+}
+
+impl<'s> From<AppId<'s>> for CredentialsTarget<'s> {
+    fn from(app_id: AppId<'s>) -> Self {
+        Self::new(app_id)
+    }
+}
+
+impl<'s> From<AppName<'s>> for CredentialsTarget<'s> {
+    fn from(app_name: AppName<'s>) -> Self {
+        Self::new(app_name)
+    }
+}
+
+impl<'s> From<&'s AppNameBuf> for CredentialsTarget<'s> {
+    fn from(app_name_buf: &'s AppNameBuf) -> Self {
+        Self::new(app_name_buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use credent_fs_model::AppName;
+
+    use super::CredentialsTarget;
+    use crate::{CredentialsDir, CredentialsFileOptions, CredentialsPathResolver};
+
+    #[test]
+    fn secret_commands_refused_for_project_local_dir_by_default() {
+        let resolver = CredentialsPathResolver::new(AppName::new("credent"));
+        let resolver_project_local = resolver
+            .clone()
+            .with_dir(CredentialsDir::ProjectLocal(PathBuf::from("project")));
+
+        assert!(CredentialsTarget::new(resolver).secret_commands_allowed());
+        assert!(!CredentialsTarget::new(resolver_project_local.clone()).secret_commands_allowed());
+        assert!(CredentialsTarget::new(resolver_project_local)
+            .with_options(CredentialsFileOptions::new().with_secret_commands_allowed(true))
+            .secret_commands_allowed());
+    }
+}
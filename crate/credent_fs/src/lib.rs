@@ -3,13 +3,40 @@
 //! Reads / Writes credentials from / to disk.
 
 pub use crate::{
+    cached_credentials_loader::CachedCredentialsLoader,
+    credentials_audit_log::CredentialsAuditLog,
     credentials_file::{CredentialsFile, CREDENTIALS_FILE_NAME},
     credentials_file_loader::CredentialsFileLoader,
+    credentials_file_migrator::CredentialsFileMigrator,
+    credentials_file_options::CredentialsFileOptions,
     credentials_file_storer::CredentialsFileStorer,
+    credentials_layers::{CredentialsLayer, CredentialsLayers},
+    credentials_migration::CREDENTIALS_FILE_VERSION,
+    credentials_path_resolver::{
+        CredentialsDir, CredentialsPathResolver, CREDENTIALS_FILE_ENV_SUFFIX,
+    },
+    credentials_secret_resolver::SECRET_COMMAND_TIMEOUT,
+    credentials_target::CredentialsTarget,
 };
 
+#[cfg(feature = "watch")]
+pub use crate::credentials_file_watcher::{CredentialsFileWatchOptions, CredentialsFileWatcher};
+
 pub use credent_fs_model as model;
 
+mod cached_credentials_loader;
+mod credentials_audit_log;
+mod credentials_document;
 mod credentials_file;
 mod credentials_file_loader;
+mod credentials_file_migrator;
+mod credentials_file_options;
 mod credentials_file_storer;
+#[cfg(feature = "watch")]
+mod credentials_file_watcher;
+mod credentials_integrity;
+mod credentials_layers;
+mod credentials_migration;
+mod credentials_path_resolver;
+mod credentials_secret_resolver;
+mod credentials_target;
@@ -0,0 +1,168 @@
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+};
+
+use crate::{AppName, AppNameBuf};
+
+/// Identity of an application, optionally qualified by its organization.
+///
+/// Applications from the same organization share a vendor directory. The
+/// directory of the application relative to the platform's configuration
+/// directory is:
+///
+/// * `Windows`: `<organization>\<application>`
+/// * `Linux`: `<organization>/<application>`
+/// * `OS X`: `<qualifier>.<organization>.<application>`
+///
+/// When there is no organization, this is just `<application>`. The qualifier
+/// is only used on OS X, and is typically a top level domain such as `com`.
+///
+/// ```rust
+/// use credent_fs_model::{AppId, AppName};
+///
+/// const TOOL_A: AppId<'_> = AppId::new(AppName::new("tool-a"))
+///     .with_organization(AppName::new("acme"))
+///     .with_qualifier(AppName::new("com"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AppId<'s> {
+    /// Qualifier of the organization, such as `com`.
+    qualifier: Option<AppName<'s>>,
+    /// Organization that ships the application.
+    organization: Option<AppName<'s>>,
+    /// Name of the application.
+    application: AppName<'s>,
+}
+
+impl<'s> AppId<'s> {
+    /// Returns a new `AppId` for an application without an organization.
+    ///
+    /// # Parameters
+    ///
+    /// * `application`: Name of the application.
+    pub const fn new(application: AppName<'s>) -> Self {
+        Self {
+            qualifier: None,
+            organization: None,
+            application,
+        }
+    }
+
+    /// Sets the organization that ships the application.
+    pub const fn with_organization(mut self, organization: AppName<'s>) -> Self {
+        self.organization = Some(organization);
+        self
+    }
+
+    /// Sets the qualifier of the organization, such as `com`.
+    pub const fn with_qualifier(mut self, qualifier: AppName<'s>) -> Self {
+        self.qualifier = Some(qualifier);
+        self
+    }
+
+    /// Returns the qualifier of the organization.
+    pub const fn qualifier(&self) -> Option<AppName<'s>> {
+        self.qualifier
+    }
+
+    /// Returns the organization that ships the application.
+    pub const fn organization(&self) -> Option<AppName<'s>> {
+        self.organization
+    }
+
+    /// Returns the name of the application.
+    pub const fn application(&self) -> AppName<'s> {
+        self.application
+    }
+
+    /// Returns the application's directory, relative to the platform's
+    /// configuration or data directory.
+    #[cfg(target_os = "macos")]
+    pub fn dir_relative(&self) -> PathBuf {
+        match self.organization {
+            Some(organization) => {
+                let bundle_id = self
+                    .qualifier
+                    .iter()
+                    .chain(std::iter::once(&organization))
+                    .chain(std::iter::once(&self.application))
+                    .map(AppName::as_str)
+                    .collect::<Vec<_>>()
+                    .join(".");
+                PathBuf::from(bundle_id)
+            }
+            None => PathBuf::from(self.application.as_str()),
+        }
+    }
+
+    /// Returns the application's directory, relative to the platform's
+    /// configuration or data directory.
+    #[cfg(not(target_os = "macos"))]
+    pub fn dir_relative(&self) -> PathBuf {
+        self.organization
+            .iter()
+            .chain(std::iter::once(&self.application))
+            .map(AppName::as_str)
+            .collect()
+    }
+}
+
+impl<'s> Display for AppId<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(qualifier) = self.qualifier {
+            write!(f, "{qualifier}.")?;
+        }
+        if let Some(organization) = self.organization {
+            write!(f, "{organization}.")?;
+        }
+        write!(f, "{}", self.application)
+    }
+}
+
+impl<'s> From<AppName<'s>> for AppId<'s> {
+    fn from(application: AppName<'s>) -> Self {
+        Self::new(application)
+    }
+}
+
+impl<'s> From<&'s AppNameBuf> for AppId<'s> {
+    fn from(application: &'s AppNameBuf) -> Self {
+        Self::new(application.as_app_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::AppId;
+    use crate::AppName;
+
+    #[test]
+    fn dir_relative_is_application_without_organization() {
+        let app_id = AppId::new(AppName::new("tool-a")).with_qualifier(AppName::new("com"));
+
+        assert_eq!(PathBuf::from("tool-a"), app_id.dir_relative());
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn dir_relative_nests_application_in_organization() {
+        let app_id = AppId::new(AppName::new("tool-a"))
+            .with_organization(AppName::new("acme"))
+            .with_qualifier(AppName::new("com"));
+
+        assert_eq!(PathBuf::from("acme").join("tool-a"), app_id.dir_relative());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn dir_relative_is_bundle_id() {
+        let app_id = AppId::new(AppName::new("tool-a"))
+            .with_organization(AppName::new("acme"))
+            .with_qualifier(AppName::new("com"));
+
+        assert_eq!(PathBuf::from("com.acme.tool-a"), app_id.dir_relative());
+    }
+}
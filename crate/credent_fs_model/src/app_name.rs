@@ -1,13 +1,117 @@
 use std::{
-    convert::{Infallible, TryFrom},
+    convert::TryFrom,
     fmt::{self, Display},
-    ops::{Deref, DerefMut},
+    ops::Deref,
 };
 
+use crate::{AppNameBuf, AppNameError};
+
 /// Name of an application. `&str` newtype.
-#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+///
+/// The name is used as a directory name, so it must not be empty, contain path
+/// separators or characters that are invalid in file names, be `.` or `..`, or
+/// be a name reserved by Windows such as `CON` or `NUL`.
+///
+/// ```rust
+/// use credent_fs_model::AppName;
+///
+/// const CREDENT: AppName<'_> = AppName::new("credent");
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct AppName<'s>(pub &'s str);
+pub struct AppName<'s>(pub(crate) &'s str);
+
+impl<'s> AppName<'s> {
+    /// Names reserved by Windows, which cannot be used as file names even with
+    /// an extension.
+    const RESERVED: &'static [&'static str] = &[
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+        "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+
+    /// Returns a new `AppName`.
+    ///
+    /// This is intended for names known at compile time, and fails compilation
+    /// when used in a `const` with an invalid name. Use `AppName::try_from`
+    /// for names that are not known at compile time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is invalid.
+    pub const fn new(name: &'s str) -> Self {
+        match Self::validate(name) {
+            Ok(()) => Self(name),
+            Err(_) => panic!("Application name is invalid."),
+        }
+    }
+
+    /// Returns the application name as a `&str`.
+    pub const fn as_str(&self) -> &'s str {
+        self.0
+    }
+
+    /// Returns an owned copy of the application name.
+    pub fn to_app_name_buf(&self) -> AppNameBuf {
+        AppNameBuf::from(*self)
+    }
+
+    /// Returns whether the name can be used as a directory name.
+    pub(crate) const fn validate(name: &str) -> Result<(), AppNameError> {
+        let bytes = name.as_bytes();
+        if bytes.is_empty() {
+            return Err(AppNameError::Empty);
+        }
+
+        let mut i = 0;
+        let mut stem_len = bytes.len();
+        while i < bytes.len() {
+            let byte = bytes[i];
+            match byte {
+                b'/' | b'\\' | b':' | b'<' | b'>' | b'"' | b'|' | b'?' | b'*' | 0x00..=0x1f => {
+                    return Err(AppNameError::InvalidChar(byte as char));
+                }
+                b'.' if stem_len == bytes.len() => stem_len = i,
+                _ => {}
+            }
+            i += 1;
+        }
+
+        if Self::eq_ignore_ascii_case(bytes, bytes.len(), b".")
+            || Self::eq_ignore_ascii_case(bytes, bytes.len(), b"..")
+        {
+            return Err(AppNameError::RelativeDir);
+        }
+
+        let mut reserved_index = 0;
+        while reserved_index < Self::RESERVED.len() {
+            let reserved = Self::RESERVED[reserved_index].as_bytes();
+            if Self::eq_ignore_ascii_case(bytes, stem_len, reserved) {
+                return Err(AppNameError::Reserved);
+            }
+            reserved_index += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the first `len` bytes of `bytes` equal `other`, ignoring
+    /// ASCII case.
+    const fn eq_ignore_ascii_case(bytes: &[u8], len: usize, other: &[u8]) -> bool {
+        if len != other.len() {
+            return false;
+        }
+
+        let mut i = 0;
+        while i < len {
+            if !bytes[i].eq_ignore_ascii_case(&other[i]) {
+                return false;
+            }
+            i += 1;
+        }
+
+        true
+    }
+}
 
 impl<'s> Deref for AppName<'s> {
     type Target = &'s str;
@@ -17,9 +121,9 @@ impl<'s> Deref for AppName<'s> {
     }
 }
 
-impl<'s> DerefMut for AppName<'s> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl<'s> AsRef<str> for AppName<'s> {
+    fn as_ref(&self) -> &str {
+        self.0
     }
 }
 
@@ -30,9 +134,88 @@ impl<'s> Display for AppName<'s> {
 }
 
 impl<'s> TryFrom<&'s str> for AppName<'s> {
-    type Error = Infallible;
+    type Error = AppNameError;
+
+    fn try_from(s: &'s str) -> Result<AppName<'s>, AppNameError> {
+        Self::validate(s).map(|()| AppName(s))
+    }
+}
+
+impl<'s> From<&'s AppNameBuf> for AppName<'s> {
+    fn from(app_name_buf: &'s AppNameBuf) -> Self {
+        app_name_buf.as_app_name()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de: 's, 's> serde::Deserialize<'de> for AppName<'s> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = <&'de str>::deserialize(deserializer)?;
+        AppName::try_from(name).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::AppName;
+    use crate::{AppNameBuf, AppNameError};
+
+    #[test]
+    fn try_from_accepts_valid_names() {
+        assert_eq!(
+            Ok("credent"),
+            AppName::try_from("credent").map(|n| n.as_str())
+        );
+        assert_eq!(
+            Ok(".credent"),
+            AppName::try_from(".credent").map(|n| n.as_str())
+        );
+        assert_eq!(
+            Ok("console"),
+            AppName::try_from("console").map(|n| n.as_str())
+        );
+    }
+
+    #[test]
+    fn try_from_rejects_names_that_escape_the_directory() {
+        assert_eq!(Err(AppNameError::Empty), AppName::try_from(""));
+        assert_eq!(Err(AppNameError::RelativeDir), AppName::try_from(".."));
+        assert_eq!(Err(AppNameError::RelativeDir), AppName::try_from("."));
+        assert_eq!(
+            Err(AppNameError::InvalidChar('/')),
+            AppName::try_from("../../etc")
+        );
+        assert_eq!(
+            Err(AppNameError::InvalidChar('\\')),
+            AppName::try_from("..\\etc")
+        );
+        assert_eq!(
+            Err(AppNameError::InvalidChar('\0')),
+            AppName::try_from("cred\0ent")
+        );
+    }
+
+    #[test]
+    fn try_from_rejects_reserved_names() {
+        assert_eq!(Err(AppNameError::Reserved), AppName::try_from("nul"));
+        assert_eq!(Err(AppNameError::Reserved), AppName::try_from("CON.app"));
+        assert_eq!(Err(AppNameError::Reserved), AppName::try_from("Lpt1"));
+    }
 
-    fn try_from(s: &str) -> Result<AppName, Infallible> {
-        Ok(AppName(s))
+    #[test]
+    fn app_name_buf_from_str_validates() {
+        assert_eq!(
+            Ok(AppName::new("credent").to_app_name_buf()),
+            "credent".parse::<AppNameBuf>()
+        );
+        assert_eq!(
+            Err(AppNameError::InvalidChar('/')),
+            "a/b".parse::<AppNameBuf>()
+        );
     }
 }
@@ -0,0 +1,95 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    ops::Deref,
+    str::FromStr,
+};
+
+use crate::{AppName, AppNameError};
+
+/// Name of an application. `String` newtype.
+///
+/// This is the owned variant of [`AppName`], for names that are computed at
+/// runtime. The same validation rules apply.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AppNameBuf(String);
+
+impl AppNameBuf {
+    /// Returns a borrowed [`AppName`] for this name.
+    pub fn as_app_name(&self) -> AppName<'_> {
+        // The name was validated when this `AppNameBuf` was constructed.
+        AppName(&self.0)
+    }
+
+    /// Returns the application name as a `&str`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the inner `String`.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for AppNameBuf {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for AppNameBuf {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for AppNameBuf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'s> From<AppName<'s>> for AppNameBuf {
+    fn from(app_name: AppName<'s>) -> Self {
+        Self(app_name.as_str().to_string())
+    }
+}
+
+impl TryFrom<String> for AppNameBuf {
+    type Error = AppNameError;
+
+    fn try_from(s: String) -> Result<AppNameBuf, AppNameError> {
+        AppName::validate(&s).map(|()| AppNameBuf(s))
+    }
+}
+
+impl TryFrom<&str> for AppNameBuf {
+    type Error = AppNameError;
+
+    fn try_from(s: &str) -> Result<AppNameBuf, AppNameError> {
+        AppName::try_from(s).map(AppNameBuf::from)
+    }
+}
+
+impl FromStr for AppNameBuf {
+    type Err = AppNameError;
+
+    fn from_str(s: &str) -> Result<AppNameBuf, AppNameError> {
+        AppNameBuf::try_from(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AppNameBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        AppNameBuf::try_from(name).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+
+/// Reasons an application name cannot be used as a directory name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppNameError {
+    /// Application name is empty.
+    Empty,
+    /// Application name contains a path separator, or a character that is
+    /// invalid in file names.
+    InvalidChar(char),
+    /// Application name is `.` or `..`.
+    RelativeDir,
+    /// Application name is reserved by Windows, such as `CON` or `NUL`.
+    Reserved,
+}
+
+impl fmt::Display for AppNameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Application name must not be empty."),
+            Self::InvalidChar(invalid_char) => write!(
+                f,
+                "Application name must not contain `{}`.",
+                invalid_char.escape_default()
+            ),
+            Self::RelativeDir => write!(f, "Application name must not be `.` or `..`."),
+            Self::Reserved => write!(
+                f,
+                "Application name must not be a reserved file name, such as `CON` or `NUL`."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AppNameError {}
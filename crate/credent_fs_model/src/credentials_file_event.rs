@@ -0,0 +1,20 @@
+use credent_model::Credentials;
+
+use crate::{Error, ProfilesDiff};
+
+/// Change to a watched credentials file.
+#[derive(Debug)]
+pub enum CredentialsFileEvent<C = Credentials>
+where
+    C: Clone + Eq,
+{
+    /// The file was reloaded, and its profiles differ from before.
+    ///
+    /// A removed file is treated as having no profiles.
+    Changed(ProfilesDiff<C>),
+    /// The file failed to be reloaded.
+    ///
+    /// The watcher continues to watch the file, and later changes are compared
+    /// against the last successfully loaded profiles.
+    LoadFailed(Error),
+}
@@ -0,0 +1,21 @@
+/// Changes made when upgrading a credentials file to the current version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialsFileMigration {
+    /// Version of the file before the migration.
+    pub version_from: u32,
+    /// Version of the file after the migration.
+    pub version_to: u32,
+    /// Description of each migration step, in the order they are applied.
+    pub steps: Vec<String>,
+    /// Contents of the file before the migration.
+    pub contents_before: String,
+    /// Contents of the file after the migration.
+    pub contents_after: String,
+}
+
+impl CredentialsFileMigration {
+    /// Returns whether the file is older than the current version.
+    pub fn is_needed(&self) -> bool {
+        self.version_from < self.version_to
+    }
+}
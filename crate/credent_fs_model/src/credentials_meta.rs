@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use credent_model::{Credentials, Profile};
+
+use crate::Error;
+
+/// Metadata stored alongside the profiles in a credentials file.
+///
+/// These are top level keys in the file, so profiles cannot use these names:
+///
+/// ```toml
+/// default_profile = "prod"
+///
+/// [aliases]
+/// production = "prod"
+///
+/// [prod]
+/// username = "me"
+/// password = "c2VjcmV0"
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CredentialsMeta {
+    /// Name of the profile to use when the `"default"` profile is requested.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub default_profile: Option<String>,
+    /// Alternative names for profiles, mapped to the profile name.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl CredentialsMeta {
+    /// Top level keys in the credentials file that hold metadata.
+    pub const KEYS: &'static [&'static str] = &["default_profile", "aliases"];
+
+    /// Returns whether there is no metadata.
+    pub fn is_empty(&self) -> bool {
+        self.default_profile.is_none() && self.aliases.is_empty()
+    }
+
+    /// Returns the name of the profile that the given name refers to.
+    ///
+    /// `"default"` refers to the [`default_profile`], and aliases are followed
+    /// until a name that is not an alias is reached.
+    ///
+    /// [`default_profile`]: Self::default_profile
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile, or an alias.
+    pub fn profile_name_resolve<'n>(&'n self, profile_name: &'n str) -> Result<&'n str, Error> {
+        match self.default_profile.as_deref() {
+            Some(default_profile) if profile_name == Profile::<Credentials>::DEFAULT_NAME => {
+                self.alias_resolve(default_profile)
+            }
+            _ => self.alias_resolve(profile_name),
+        }
+    }
+
+    /// Returns the name of the profile that the given alias refers to.
+    ///
+    /// Aliases are followed until a name that is not an alias is reached. If
+    /// the name is not an alias, it is returned as is.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile, or an alias.
+    pub fn alias_resolve<'n>(&'n self, profile_name: &'n str) -> Result<&'n str, Error> {
+        // Each alias can be followed at most once without revisiting an alias.
+        let mut profile_name_resolved = profile_name;
+        for _ in 0..=self.aliases.len() {
+            match self.aliases.get(profile_name_resolved) {
+                Some(alias_target) => profile_name_resolved = alias_target,
+                None => return Ok(profile_name_resolved),
+            }
+        }
+
+        let profile_name = profile_name.to_string();
+        Err(Error::ProfileAliasCycle { profile_name })
+    }
+
+    /// Merges metadata from a later credentials file into this one.
+    ///
+    /// The later file's default profile takes precedence, and its aliases
+    /// replace aliases with the same name.
+    ///
+    /// # Parameters
+    ///
+    /// * `meta`: Metadata read from the later file.
+    pub fn merge(&mut self, meta: CredentialsMeta) {
+        if meta.default_profile.is_some() {
+            self.default_profile = meta.default_profile;
+        }
+        self.aliases.extend(meta.aliases);
+    }
+
+    /// Updates the metadata for a profile that is renamed.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the profile before it is renamed.
+    /// * `profile_name_new`: Name of the profile after it is renamed.
+    pub fn profile_renamed(&mut self, profile_name: &str, profile_name_new: &str) {
+        if self.default_profile.as_deref() == Some(profile_name) {
+            self.default_profile = Some(profile_name_new.to_string());
+        }
+        self.aliases
+            .values_mut()
+            .filter(|alias_target| alias_target.as_str() == profile_name)
+            .for_each(|alias_target| *alias_target = profile_name_new.to_string());
+    }
+
+    /// Removes the metadata that refers to a removed profile.
+    ///
+    /// # Parameters
+    ///
+    /// * `profile_name`: Name of the removed profile.
+    pub fn profile_removed(&mut self, profile_name: &str) {
+        if self.default_profile.as_deref() == Some(profile_name) {
+            self.default_profile = None;
+        }
+        self.aliases
+            .retain(|_alias, alias_target| alias_target != profile_name);
+    }
+}
@@ -1,15 +1,23 @@
-use std::{fmt, path::PathBuf};
+use std::{fmt, path::PathBuf, str::Utf8Error, time::Duration};
 
-use credent_model::{Credentials, Profiles};
+use crate::{ErrorKind, SourceLocation};
 
-/// Errors when reading the user credentials file.
+/// Errors when reading or writing the user credentials file.
+///
+/// Errors only carry profile names and paths, never credentials. Use
+/// [`Error::kind`] to match on the kind of error without depending on the
+/// fields of each variant.
 #[derive(Debug)]
-pub enum Error<C = Credentials>
-where
-    C: Clone + Eq,
-{
+#[non_exhaustive]
+pub enum Error {
     /// Unable to determine user configuration directory.
     UserConfigDirNotFound,
+    /// Unable to determine user data directory.
+    UserDataDirNotFound,
+    /// Unable to determine system configuration directory.
+    SystemConfigDirNotFound,
+    /// None of the credentials layers are writable.
+    CredentialsLayerWritableNotFound,
     /// Failed to create the parent directory of the credentials file.
     CredentialsParentDirCreate {
         /// Path to the user credentials file.
@@ -45,27 +53,292 @@ where
     CredentialsFileDeserialize {
         /// Path to the user credentials file.
         credentials_path: PathBuf,
+        /// Name of the profile that failed to be deserialized, if the error
+        /// is within a profile.
+        profile_name: Option<String>,
+        /// Location of the error in the file, if known.
+        location: Option<SourceLocation>,
         /// The underlying TOML error.
         error: toml::de::Error,
     },
+    /// User credentials file was written by a newer version of the
+    /// application.
+    CredentialsFileVersionUnsupported {
+        /// Version recorded in the user credentials file.
+        version: i64,
+        /// Latest version that can be read.
+        version_supported: u32,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// User credentials file is not signed with an integrity key.
+    CredentialsFileIntegrityMissing {
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// User credentials file was changed without the integrity key.
+    CredentialsFileIntegrityMismatch {
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// User credentials file is signed, but no integrity key was given to
+    /// sign it again when writing.
+    CredentialsFileIntegrityKeyRequired {
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Failed to read the integrity key from the operating system's keyring.
+    #[cfg(feature = "integrity-keyring")]
+    IntegrityKeyringRead {
+        /// Service that the keyring entry belongs to.
+        service: String,
+        /// User that the keyring entry belongs to.
+        user: String,
+        /// The underlying keyring error.
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Failed to write to the credentials audit log.
+    AuditLogWrite {
+        /// Path to the audit log file.
+        audit_path: PathBuf,
+        /// The underlying IO error.
+        error: std::io::Error,
+    },
+    /// Failed to spawn the thread that watches the credentials file.
+    CredentialsFileWatch {
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// The underlying IO error.
+        error: std::io::Error,
+    },
+    /// Profile does not exist in the user credentials file.
+    ProfileNotFound {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Profile already exists in the user credentials file.
+    ProfileExists {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Profile name is reserved for a top level key of the credentials file,
+    /// such as `default_profile` or `version`.
+    ProfileNameReserved {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Profile in the user credentials file is invalid, and storing would
+    /// overwrite it.
+    ///
+    /// Use the `*_force` store functions to overwrite it.
+    ProfileOverwriteInvalid {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// The error when loading the existing profile.
+        error: Box<Error>,
+    },
+    /// Profile aliases refer to each other in a cycle.
+    ProfileAliasCycle {
+        /// Name of the profile that was requested.
+        profile_name: String,
+    },
+    /// Profiles inherit from each other in a cycle.
+    ProfileInheritanceCycle {
+        /// Name of the profile that was being resolved.
+        profile_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Profile inherits from a profile that does not exist.
+    ProfileParentNotFound {
+        /// Name of the profile that declares the parent.
+        profile_name: String,
+        /// Name of the parent profile.
+        parent_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Failed to run a profile's `password_command`.
+    SecretCommandSpawn {
+        /// Name of the profile.
+        profile_name: String,
+        /// The `password_command`.
+        command: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// The underlying IO error.
+        error: std::io::Error,
+    },
+    /// A profile's `password_command` did not complete in time.
+    SecretCommandTimeout {
+        /// Name of the profile.
+        profile_name: String,
+        /// The `password_command`.
+        command: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// Duration the command was allowed to run for.
+        timeout: Duration,
+    },
+    /// A profile's `password_command` exited unsuccessfully.
+    SecretCommandFailed {
+        /// Name of the profile.
+        profile_name: String,
+        /// The `password_command`.
+        command: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// Exit code of the command, `None` if it was terminated by a signal.
+        exit_code: Option<i32>,
+        /// What the command wrote to stderr.
+        stderr: String,
+    },
+    /// A profile's `password_command` output is not valid UTF-8.
+    SecretCommandOutputNotUtf8 {
+        /// Name of the profile.
+        profile_name: String,
+        /// The `password_command`.
+        command: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// The underlying UTF-8 error.
+        ///
+        /// This does not hold the output, so that the secret is not leaked.
+        error: Utf8Error,
+    },
+    /// Failed to read a profile's `password_file`.
+    SecretFileRead {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the `password_file`.
+        secret_path: PathBuf,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+        /// The underlying IO error.
+        error: std::io::Error,
+    },
+    /// A profile's `password_command` is in a file that is not allowed to run
+    /// commands.
+    ///
+    /// Commands are refused in project-local and read-only credentials files
+    /// unless the caller allows them.
+    SecretCommandRefused {
+        /// Name of the profile.
+        profile_name: String,
+        /// The `password_command`.
+        command: String,
+        /// Path to the credentials file.
+        credentials_path: PathBuf,
+    },
+    /// A profile reads its password from a `password_command` or
+    /// `password_file`, which is only resolved when the profile is loaded by
+    /// name.
+    ProfileSecretUnresolved {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the credentials file.
+        credentials_path: PathBuf,
+    },
+    /// Profile in the user credentials file reads its password from a
+    /// `password_command` or `password_file`, and a forced store would replace
+    /// the reference.
+    ///
+    /// The other store functions keep the reference. Remove the profile first
+    /// to replace it.
+    ProfileSecretReferenced {
+        /// Name of the profile.
+        profile_name: String,
+        /// Path to the user credentials file.
+        credentials_path: PathBuf,
+    },
     /// Failed to serialize user credentials.
     CredentialsFileSerialize {
-        /// Profiles which failed to be serialized.
-        profiles: Profiles<C>,
+        /// Names of the profiles which failed to be serialized.
+        profile_names: Vec<String>,
         /// The underlying TOML error.
         error: toml::ser::Error,
     },
 }
 
-impl<C> fmt::Display for Error<C>
-where
-    C: Clone + Eq + fmt::Debug,
-{
+impl Error {
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UserConfigDirNotFound => ErrorKind::UserConfigDirNotFound,
+            Self::UserDataDirNotFound => ErrorKind::UserDataDirNotFound,
+            Self::SystemConfigDirNotFound => ErrorKind::SystemConfigDirNotFound,
+            Self::CredentialsLayerWritableNotFound => ErrorKind::CredentialsLayerWritableNotFound,
+            Self::CredentialsParentDirCreate { .. } => ErrorKind::CredentialsParentDirCreate,
+            Self::CredentialsFileNonExistent { .. } => ErrorKind::CredentialsFileNonExistent,
+            Self::CredentialsFileIsDir { .. } => ErrorKind::CredentialsFileIsDir,
+            Self::CredentialsFileRead { .. } => ErrorKind::CredentialsFileRead,
+            Self::CredentialsFileWrite { .. } => ErrorKind::CredentialsFileWrite,
+            Self::CredentialsFileDeserialize { .. } => ErrorKind::CredentialsFileDeserialize,
+            Self::CredentialsFileVersionUnsupported { .. } => {
+                ErrorKind::CredentialsFileVersionUnsupported
+            }
+            Self::CredentialsFileIntegrityMissing { .. } => {
+                ErrorKind::CredentialsFileIntegrityMissing
+            }
+            Self::CredentialsFileIntegrityMismatch { .. } => {
+                ErrorKind::CredentialsFileIntegrityMismatch
+            }
+            Self::CredentialsFileIntegrityKeyRequired { .. } => {
+                ErrorKind::CredentialsFileIntegrityKeyRequired
+            }
+            #[cfg(feature = "integrity-keyring")]
+            Self::IntegrityKeyringRead { .. } => ErrorKind::IntegrityKeyringRead,
+            Self::AuditLogWrite { .. } => ErrorKind::AuditLogWrite,
+            Self::CredentialsFileWatch { .. } => ErrorKind::CredentialsFileWatch,
+            Self::ProfileNotFound { .. } => ErrorKind::ProfileNotFound,
+            Self::ProfileExists { .. } => ErrorKind::ProfileExists,
+            Self::ProfileNameReserved { .. } => ErrorKind::ProfileNameReserved,
+            Self::ProfileOverwriteInvalid { .. } => ErrorKind::ProfileOverwriteInvalid,
+            Self::ProfileAliasCycle { .. } => ErrorKind::ProfileAliasCycle,
+            Self::ProfileInheritanceCycle { .. } => ErrorKind::ProfileInheritanceCycle,
+            Self::ProfileParentNotFound { .. } => ErrorKind::ProfileParentNotFound,
+            Self::SecretCommandSpawn { .. } => ErrorKind::SecretCommandSpawn,
+            Self::SecretCommandTimeout { .. } => ErrorKind::SecretCommandTimeout,
+            Self::SecretCommandFailed { .. } => ErrorKind::SecretCommandFailed,
+            Self::SecretCommandOutputNotUtf8 { .. } => ErrorKind::SecretCommandOutputNotUtf8,
+            Self::SecretFileRead { .. } => ErrorKind::SecretFileRead,
+            Self::SecretCommandRefused { .. } => ErrorKind::SecretCommandRefused,
+            Self::ProfileSecretUnresolved { .. } => ErrorKind::ProfileSecretUnresolved,
+            Self::ProfileSecretReferenced { .. } => ErrorKind::ProfileSecretReferenced,
+            Self::CredentialsFileSerialize { .. } => ErrorKind::CredentialsFileSerialize,
+        }
+    }
+
+    /// Returns the stable code of this error's kind, such as
+    /// `"profile_not_found"`.
+    ///
+    /// See [`ErrorKind::code`].
+    pub fn code(&self) -> &'static str {
+        self.kind().code()
+    }
+}
+
+impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::UserConfigDirNotFound => {
                 write!(f, "Unable to determine user configuration directory.")
             }
+            Self::UserDataDirNotFound => write!(f, "Unable to determine user data directory."),
+            Self::SystemConfigDirNotFound => {
+                write!(f, "Unable to determine system configuration directory.")
+            }
+            Self::CredentialsLayerWritableNotFound => {
+                write!(f, "None of the credentials layers are writable.")
+            }
             Self::CredentialsParentDirCreate { parent_path, .. } => write!(
                 f,
                 "Failed to create credentials file parent directory. Path: `{}`",
@@ -73,7 +346,7 @@ where
             ),
             Self::CredentialsFileNonExistent { credentials_path } => write!(
                 f,
-                "User credentials does not exist or cannot be accessed. Path: `{}`",
+                "User credentials file does not exist or cannot be accessed. Path: `{}`",
                 credentials_path.display()
             ),
             Self::CredentialsFileIsDir { credentials_path } => write!(
@@ -92,38 +365,315 @@ where
                 credentials_path, ..
             } => write!(
                 f,
-                "User credentials file failed to be read. Path: `{}`",
+                "User credentials file failed to be written. Path: `{}`",
                 credentials_path.display()
             ),
             Self::CredentialsFileDeserialize {
+                credentials_path,
+                profile_name,
+                location,
+                error,
+            } => {
+                write!(f, "User credentials file failed to be deserialized")?;
+                if let Some(profile_name) = profile_name {
+                    write!(f, " in profile `{}`", profile_name)?;
+                }
+                match location {
+                    Some(location) => write!(
+                        f,
+                        ": {}\n --> {}:{}:{}\n{}",
+                        error,
+                        credentials_path.display(),
+                        location.line,
+                        location.column,
+                        location
+                    ),
+                    None => write!(f, ": {}. Path: `{}`", error, credentials_path.display()),
+                }
+            }
+            Self::CredentialsFileVersionUnsupported {
+                version,
+                version_supported,
+                credentials_path,
+            } => write!(
+                f,
+                "User credentials file version `{version}` is newer than the supported version `{version_supported}`. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::CredentialsFileIntegrityMissing { credentials_path } => write!(
+                f,
+                "User credentials file is not signed with the integrity key. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::CredentialsFileIntegrityMismatch { credentials_path } => write!(
+                f,
+                "User credentials file was changed without the integrity key. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::CredentialsFileIntegrityKeyRequired { credentials_path } => write!(
+                f,
+                "User credentials file is signed, and must be written with its integrity key. Path: `{}`",
+                credentials_path.display()
+            ),
+            #[cfg(feature = "integrity-keyring")]
+            Self::IntegrityKeyringRead { service, user, .. } => write!(
+                f,
+                "Failed to read integrity key from keyring. Service: `{service}`, user: `{user}`"
+            ),
+            Self::AuditLogWrite { audit_path, .. } => write!(
+                f,
+                "Failed to write to credentials audit log. Path: `{}`",
+                audit_path.display()
+            ),
+            Self::CredentialsFileWatch {
                 credentials_path, ..
             } => write!(
                 f,
-                "User credentials file failed to be deserialized. Path: `{}`",
+                "Failed to watch user credentials file. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileNotFound {
+                profile_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile `{profile_name}` does not exist in user credentials file. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileExists {
+                profile_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile `{profile_name}` already exists in user credentials file. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileNameReserved {
+                profile_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile name `{profile_name}` is reserved in user credentials file. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileOverwriteInvalid {
+                profile_name,
+                credentials_path,
+                error,
+            } => write!(
+                f,
+                "Profile `{profile_name}` in user credentials file is invalid and would be overwritten. Path: `{}`\n{error}",
+                credentials_path.display()
+            ),
+            Self::ProfileAliasCycle { profile_name } => write!(
+                f,
+                "Profile aliases form a cycle when resolving `{profile_name}`."
+            ),
+            Self::ProfileInheritanceCycle {
+                profile_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile `{profile_name}` inherits from itself through its parent profiles. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileParentNotFound {
+                profile_name,
+                parent_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile `{profile_name}` inherits from `{parent_name}`, which does not exist in user credentials file. Path: `{}`",
                 credentials_path.display()
             ),
-            Self::CredentialsFileSerialize { profiles, .. } => write!(
+            Self::SecretCommandSpawn {
+                profile_name,
+                command,
+                credentials_path,
+                ..
+            } => write!(
                 f,
-                "User credentials failed to be serialized. Profiles: `{profiles:?}`",
+                "Failed to run `password_command` for profile `{profile_name}`: `{command}`. Path: `{}`",
+                credentials_path.display()
             ),
+            Self::SecretCommandTimeout {
+                profile_name,
+                command,
+                credentials_path,
+                timeout,
+            } => write!(
+                f,
+                "`password_command` for profile `{profile_name}` did not complete within {} seconds: `{command}`. Path: `{}`",
+                timeout.as_secs(),
+                credentials_path.display()
+            ),
+            Self::SecretCommandFailed {
+                profile_name,
+                command,
+                credentials_path,
+                exit_code,
+                stderr,
+            } => {
+                write!(
+                    f,
+                    "`password_command` for profile `{profile_name}` failed: `{command}`."
+                )?;
+                if let Some(exit_code) = exit_code {
+                    write!(f, " Exit code: {exit_code}.")?;
+                }
+                write!(f, " Path: `{}`", credentials_path.display())?;
+                if !stderr.is_empty() {
+                    write!(f, "\nstderr:\n{stderr}")?;
+                }
+                Ok(())
+            }
+            Self::SecretCommandOutputNotUtf8 {
+                profile_name,
+                command,
+                credentials_path,
+                ..
+            } => write!(
+                f,
+                "`password_command` for profile `{profile_name}` output is not valid UTF-8: `{command}`. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::SecretFileRead {
+                profile_name,
+                secret_path,
+                credentials_path,
+                ..
+            } => write!(
+                f,
+                "Failed to read `password_file` for profile `{profile_name}`: `{}`. Path: `{}`",
+                secret_path.display(),
+                credentials_path.display()
+            ),
+            Self::SecretCommandRefused {
+                profile_name,
+                command,
+                credentials_path,
+            } => write!(
+                f,
+                "`password_command` for profile `{profile_name}` is not allowed to run from this credentials file: `{command}`. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileSecretUnresolved {
+                profile_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile `{profile_name}` reads its password from a `password_command` or `password_file`, which is only resolved when the profile is loaded by name. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::ProfileSecretReferenced {
+                profile_name,
+                credentials_path,
+            } => write!(
+                f,
+                "Profile `{profile_name}` reads its password from a `password_command` or `password_file`, which storing a password would replace. Path: `{}`",
+                credentials_path.display()
+            ),
+            Self::CredentialsFileSerialize { profile_names, .. } => {
+                write!(f, "User credentials failed to be serialized. Profiles: [")?;
+                profile_names
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, profile_name)| {
+                        let separator = if index == 0 { "" } else { ", " };
+                        write!(f, "{separator}`{profile_name}`")
+                    })?;
+                write!(f, "]")
+            }
         }
     }
 }
 
-impl<C> std::error::Error for Error<C>
-where
-    C: Clone + Eq + fmt::Debug,
-{
+impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::UserConfigDirNotFound => None,
+            Self::UserDataDirNotFound => None,
+            Self::SystemConfigDirNotFound => None,
+            Self::CredentialsLayerWritableNotFound => None,
             Self::CredentialsParentDirCreate { error, .. } => Some(error),
             Self::CredentialsFileNonExistent { .. } => None,
             Self::CredentialsFileIsDir { .. } => None,
             Self::CredentialsFileRead { error, .. } => Some(error),
             Self::CredentialsFileWrite { error, .. } => Some(error),
             Self::CredentialsFileDeserialize { error, .. } => Some(error),
+            Self::CredentialsFileVersionUnsupported { .. } => None,
+            Self::CredentialsFileIntegrityMissing { .. } => None,
+            Self::CredentialsFileIntegrityMismatch { .. } => None,
+            Self::CredentialsFileIntegrityKeyRequired { .. } => None,
+            #[cfg(feature = "integrity-keyring")]
+            Self::IntegrityKeyringRead { error, .. } => Some(error.as_ref()),
+            Self::AuditLogWrite { error, .. } => Some(error),
+            Self::CredentialsFileWatch { error, .. } => Some(error),
+            Self::ProfileNotFound { .. } => None,
+            Self::ProfileExists { .. } => None,
+            Self::ProfileNameReserved { .. } => None,
+            Self::ProfileOverwriteInvalid { error, .. } => Some(error.as_ref()),
+            Self::ProfileAliasCycle { .. } => None,
+            Self::ProfileInheritanceCycle { .. } => None,
+            Self::ProfileParentNotFound { .. } => None,
+            Self::SecretCommandSpawn { error, .. } => Some(error),
+            Self::SecretCommandTimeout { .. } => None,
+            Self::SecretCommandFailed { .. } => None,
+            Self::SecretCommandOutputNotUtf8 { error, .. } => Some(error),
+            Self::SecretFileRead { error, .. } => Some(error),
+            Self::SecretCommandRefused { .. } => None,
+            Self::ProfileSecretUnresolved { .. } => None,
+            Self::ProfileSecretReferenced { .. } => None,
             Self::CredentialsFileSerialize { error, .. } => Some(error),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{io, path::PathBuf};
+
+    use super::Error;
+    use crate::ErrorKind;
+
+    #[test]
+    fn kind_code_is_stable() {
+        let error = Error::ProfileNotFound {
+            profile_name: String::from("work"),
+            credentials_path: PathBuf::from("credentials"),
+        };
+
+        assert_eq!(ErrorKind::ProfileNotFound, error.kind());
+        assert_eq!("profile_not_found", error.code());
+        assert_eq!(
+            "credentials_file_integrity_mismatch",
+            ErrorKind::CredentialsFileIntegrityMismatch.code()
+        );
+    }
+
+    #[test]
+    fn credentials_file_write_message_says_written() {
+        let error = Error::CredentialsFileWrite {
+            credentials_path: PathBuf::from("credentials"),
+            error: io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        };
+
+        assert_eq!(
+            "User credentials file failed to be written. Path: `credentials`",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn credentials_file_serialize_message_lists_profile_names() {
+        let error = Error::CredentialsFileSerialize {
+            profile_names: vec![String::from("work"), String::from("home")],
+            error: toml::ser::Error::UnsupportedType,
+        };
+
+        assert_eq!(
+            "User credentials failed to be serialized. Profiles: [`work`, `home`]",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::{self, Display};
+
+/// Kind of [`Error`], for matching on errors without depending on their
+/// fields.
+///
+/// Each kind has a [`code`] that does not change between releases, which may
+/// be used in logs or to communicate errors to other programs. Kinds
+/// are never removed, but new kinds may be added.
+///
+/// [`Error`]: crate::Error
+/// [`code`]: Self::code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Unable to determine user configuration directory.
+    UserConfigDirNotFound,
+    /// Unable to determine user data directory.
+    UserDataDirNotFound,
+    /// Unable to determine system configuration directory.
+    SystemConfigDirNotFound,
+    /// None of the credentials layers are writable.
+    CredentialsLayerWritableNotFound,
+    /// Failed to create the parent directory of the credentials file.
+    CredentialsParentDirCreate,
+    /// User credentials file does not exist.
+    CredentialsFileNonExistent,
+    /// User credentials file is a directory.
+    CredentialsFileIsDir,
+    /// Failed to read from the user credentials file.
+    CredentialsFileRead,
+    /// Failed to write to the user credentials file.
+    CredentialsFileWrite,
+    /// Failed to deserialize user credentials file contents.
+    CredentialsFileDeserialize,
+    /// User credentials file was written by a newer version of the
+    /// application.
+    CredentialsFileVersionUnsupported,
+    /// User credentials file is not signed with an integrity key.
+    CredentialsFileIntegrityMissing,
+    /// User credentials file was changed without the integrity key.
+    CredentialsFileIntegrityMismatch,
+    /// User credentials file is signed, but no integrity key was given to
+    /// sign it again when writing.
+    CredentialsFileIntegrityKeyRequired,
+    /// Failed to read the integrity key from the operating system's keyring.
+    IntegrityKeyringRead,
+    /// Failed to write to the credentials audit log.
+    AuditLogWrite,
+    /// Failed to spawn the thread that watches the credentials file.
+    CredentialsFileWatch,
+    /// Profile does not exist in the user credentials file.
+    ProfileNotFound,
+    /// Profile already exists in the user credentials file.
+    ProfileExists,
+    /// Profile name is reserved for a top level key of the credentials file.
+    ProfileNameReserved,
+    /// Profile in the user credentials file is invalid, and storing would
+    /// overwrite it.
+    ProfileOverwriteInvalid,
+    /// Profile aliases refer to each other in a cycle.
+    ProfileAliasCycle,
+    /// Profiles inherit from each other in a cycle.
+    ProfileInheritanceCycle,
+    /// Profile inherits from a profile that does not exist.
+    ProfileParentNotFound,
+    /// Failed to run a profile's `password_command`.
+    SecretCommandSpawn,
+    /// A profile's `password_command` did not complete in time.
+    SecretCommandTimeout,
+    /// A profile's `password_command` exited unsuccessfully.
+    SecretCommandFailed,
+    /// A profile's `password_command` output is not valid UTF-8.
+    SecretCommandOutputNotUtf8,
+    /// Failed to read a profile's `password_file`.
+    SecretFileRead,
+    /// A profile's `password_command` is in a file that is not allowed to run
+    /// commands.
+    SecretCommandRefused,
+    /// A profile's secret reference is only resolved when it is loaded by
+    /// name.
+    ProfileSecretUnresolved,
+    /// Storing a password would replace a profile's secret reference.
+    ProfileSecretReferenced,
+    /// Failed to serialize user credentials.
+    CredentialsFileSerialize,
+}
+
+impl ErrorKind {
+    /// Returns the stable code of this kind, such as `"profile_not_found"`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::UserConfigDirNotFound => "user_config_dir_not_found",
+            Self::UserDataDirNotFound => "user_data_dir_not_found",
+            Self::SystemConfigDirNotFound => "system_config_dir_not_found",
+            Self::CredentialsLayerWritableNotFound => "credentials_layer_writable_not_found",
+            Self::CredentialsParentDirCreate => "credentials_parent_dir_create",
+            Self::CredentialsFileNonExistent => "credentials_file_non_existent",
+            Self::CredentialsFileIsDir => "credentials_file_is_dir",
+            Self::CredentialsFileRead => "credentials_file_read",
+            Self::CredentialsFileWrite => "credentials_file_write",
+            Self::CredentialsFileDeserialize => "credentials_file_deserialize",
+            Self::CredentialsFileVersionUnsupported => "credentials_file_version_unsupported",
+            Self::CredentialsFileIntegrityMissing => "credentials_file_integrity_missing",
+            Self::CredentialsFileIntegrityMismatch => "credentials_file_integrity_mismatch",
+            Self::CredentialsFileIntegrityKeyRequired => "credentials_file_integrity_key_required",
+            Self::IntegrityKeyringRead => "integrity_keyring_read",
+            Self::AuditLogWrite => "audit_log_write",
+            Self::CredentialsFileWatch => "credentials_file_watch",
+            Self::ProfileNotFound => "profile_not_found",
+            Self::ProfileExists => "profile_exists",
+            Self::ProfileNameReserved => "profile_name_reserved",
+            Self::ProfileOverwriteInvalid => "profile_overwrite_invalid",
+            Self::ProfileAliasCycle => "profile_alias_cycle",
+            Self::ProfileInheritanceCycle => "profile_inheritance_cycle",
+            Self::ProfileParentNotFound => "profile_parent_not_found",
+            Self::SecretCommandSpawn => "secret_command_spawn",
+            Self::SecretCommandTimeout => "secret_command_timeout",
+            Self::SecretCommandFailed => "secret_command_failed",
+            Self::SecretCommandOutputNotUtf8 => "secret_command_output_not_utf8",
+            Self::SecretFileRead => "secret_file_read",
+            Self::SecretCommandRefused => "secret_command_refused",
+            Self::ProfileSecretUnresolved => "profile_secret_unresolved",
+            Self::ProfileSecretReferenced => "profile_secret_referenced",
+            Self::CredentialsFileSerialize => "credentials_file_serialize",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
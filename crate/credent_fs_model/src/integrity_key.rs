@@ -0,0 +1,31 @@
+use std::fmt;
+
+/// Secret used to sign the credentials file, so that changes made without it
+/// are detected.
+#[derive(Clone, PartialEq, Eq)]
+pub enum IntegrityKey {
+    /// Secret bytes held by the application.
+    Secret(Vec<u8>),
+    /// Entry in the operating system's keyring, whose password is the secret.
+    #[cfg(feature = "integrity-keyring")]
+    Keyring {
+        /// Service that the entry belongs to.
+        service: String,
+        /// User that the entry belongs to.
+        user: String,
+    },
+}
+
+impl fmt::Debug for IntegrityKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Secret(_) => f.debug_tuple("Secret").field(&"******").finish(),
+            #[cfg(feature = "integrity-keyring")]
+            Self::Keyring { service, user } => f
+                .debug_struct("Keyring")
+                .field("service", service)
+                .field("user", user)
+                .finish(),
+        }
+    }
+}
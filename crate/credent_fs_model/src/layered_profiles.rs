@@ -0,0 +1,80 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use credent_model::{Credentials, Profile, Profiles};
+
+use crate::CredentialsMeta;
+
+/// Profiles merged from multiple credentials files, with the file each profile
+/// was read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayeredProfiles<C = Credentials>
+where
+    C: Clone + Eq,
+{
+    /// The merged profiles.
+    pub profiles: Profiles<C>,
+    /// Path to the file that each profile was read from, keyed by profile
+    /// name.
+    pub sources: BTreeMap<String, PathBuf>,
+    /// Metadata merged from the files, such as the default profile.
+    pub meta: CredentialsMeta,
+}
+
+impl<C> LayeredProfiles<C>
+where
+    C: Clone + Eq,
+{
+    /// Returns an empty set of layered profiles.
+    pub fn new() -> Self {
+        Self {
+            profiles: Profiles::new(),
+            sources: BTreeMap::new(),
+            meta: CredentialsMeta::default(),
+        }
+    }
+
+    /// Merges the profiles from a file, replacing profiles with the same name.
+    ///
+    /// # Parameters
+    ///
+    /// * `profiles`: Profiles read from the file.
+    /// * `source`: Path to the file.
+    pub fn merge(&mut self, profiles: Profiles<C>, source: &Path) {
+        profiles.0.into_iter().for_each(|profile| {
+            self.sources
+                .insert(profile.name.clone(), source.to_path_buf());
+            self.profiles.replace(profile);
+        });
+    }
+
+    /// Returns the profile with the given name.
+    pub fn get(&self, profile_name: &str) -> Option<&Profile<C>> {
+        self.profiles.get(profile_name)
+    }
+
+    /// Returns the path to the file that the profile was read from.
+    pub fn source(&self, profile_name: &str) -> Option<&Path> {
+        self.sources.get(profile_name).map(PathBuf::as_path)
+    }
+}
+
+impl<C> Default for LayeredProfiles<C>
+where
+    C: Clone + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> From<LayeredProfiles<C>> for Profiles<C>
+where
+    C: Clone + Eq,
+{
+    fn from(layered_profiles: LayeredProfiles<C>) -> Self {
+        layered_profiles.profiles
+    }
+}
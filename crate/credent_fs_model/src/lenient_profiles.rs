@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+
+use credent_model::{Credentials, Profile, Profiles};
+
+use crate::Error;
+
+/// Profiles loaded from a credentials file, skipping those that are invalid.
+///
+/// Profiles that fail to be loaded, such as one that is missing its
+/// `password`, are recorded in `errors` instead of failing the whole file.
+///
+/// Profiles whose password is read from a `password_command` or
+/// `password_file` are loaded with an empty password, and their names are
+/// recorded in `secret_referenced`.
+#[derive(Debug)]
+pub struct LenientProfiles<C = Credentials>
+where
+    C: Clone + Eq,
+{
+    /// Profiles that were loaded successfully.
+    pub profiles: Profiles<C>,
+    /// Errors for each profile that failed to be loaded, in file order.
+    pub errors: Vec<Error>,
+    /// Names of the loaded profiles whose secret reference was not resolved.
+    pub secret_referenced: BTreeSet<String>,
+}
+
+impl<C> LenientProfiles<C>
+where
+    C: Clone + Eq,
+{
+    /// Returns an empty set of lenient profiles.
+    pub fn new() -> Self {
+        Self {
+            profiles: Profiles::new(),
+            errors: Vec::new(),
+            secret_referenced: BTreeSet::new(),
+        }
+    }
+
+    /// Returns whether every profile was loaded successfully.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the profile with the given name.
+    pub fn get(&self, profile_name: &str) -> Option<&Profile<C>> {
+        self.profiles.get(profile_name)
+    }
+}
+
+impl<C> Default for LenientProfiles<C>
+where
+    C: Clone + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> From<LenientProfiles<C>> for Profiles<C>
+where
+    C: Clone + Eq,
+{
+    fn from(lenient_profiles: LenientProfiles<C>) -> Self {
+        lenient_profiles.profiles
+    }
+}
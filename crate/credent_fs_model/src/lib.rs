@@ -2,7 +2,26 @@
 
 //! Data types used when reading credentials from the file system.
 
-pub use crate::{app_name::AppName, error::Error};
+pub use crate::{
+    app_id::AppId, app_name::AppName, app_name_buf::AppNameBuf, app_name_error::AppNameError,
+    credentials_file_event::CredentialsFileEvent,
+    credentials_file_migration::CredentialsFileMigration, credentials_meta::CredentialsMeta,
+    error::Error, error_kind::ErrorKind, integrity_key::IntegrityKey,
+    layered_profiles::LayeredProfiles, lenient_profiles::LenientProfiles,
+    profiles_diff::ProfilesDiff, source_location::SourceLocation,
+};
 
+mod app_id;
 mod app_name;
+mod app_name_buf;
+mod app_name_error;
+mod credentials_file_event;
+mod credentials_file_migration;
+mod credentials_meta;
 mod error;
+mod error_kind;
+mod integrity_key;
+mod layered_profiles;
+mod lenient_profiles;
+mod profiles_diff;
+mod source_location;
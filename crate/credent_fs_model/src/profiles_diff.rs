@@ -0,0 +1,59 @@
+use credent_model::{Credentials, Profiles};
+
+/// Differences between two sets of [`Profiles`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfilesDiff<C = Credentials>
+where
+    C: Clone + Eq,
+{
+    /// Profiles whose names only exist in the new profiles.
+    pub added: Profiles<C>,
+    /// Profiles whose credentials differ, with the new credentials.
+    pub changed: Profiles<C>,
+    /// Profiles whose names only exist in the old profiles.
+    pub removed: Profiles<C>,
+}
+
+impl<C> ProfilesDiff<C>
+where
+    C: Clone + Eq,
+{
+    /// Returns the differences between the old and new profiles.
+    ///
+    /// # Parameters
+    ///
+    /// * `profiles_old`: Profiles before the change.
+    /// * `profiles_new`: Profiles after the change.
+    pub fn between(profiles_old: &Profiles<C>, profiles_new: &Profiles<C>) -> Self {
+        let mut profiles_diff = Self {
+            added: Profiles::new(),
+            changed: Profiles::new(),
+            removed: Profiles::new(),
+        };
+
+        profiles_new.iter().for_each(|profile_new| {
+            match profiles_old.get(profile_new.name.as_str()) {
+                Some(profile_old) if profile_old.credentials == profile_new.credentials => {}
+                Some(_) => {
+                    profiles_diff.changed.insert(profile_new.clone());
+                }
+                None => {
+                    profiles_diff.added.insert(profile_new.clone());
+                }
+            }
+        });
+        profiles_old
+            .iter()
+            .filter(|profile_old| !profiles_new.contains(profile_old.name.as_str()))
+            .for_each(|profile_old| {
+                profiles_diff.removed.insert(profile_old.clone());
+            });
+
+        profiles_diff
+    }
+
+    /// Returns whether there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
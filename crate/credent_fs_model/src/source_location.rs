@@ -0,0 +1,37 @@
+use std::fmt::{self, Display};
+
+/// Position of an error within a credentials file.
+///
+/// When displayed, the line is shown with a caret under the column:
+///
+/// ```text
+///   |
+/// 4 | [prod]
+///   | ^
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Line number, starting from 1.
+    pub line: usize,
+    /// Column number in characters, starting from 1.
+    pub column: usize,
+    /// Contents of the line, without the line ending.
+    ///
+    /// Values are masked, so that secrets are not shown in errors.
+    pub line_text: String,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, self.line_text)?;
+        write!(
+            f,
+            "{} | {}^",
+            gutter,
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
@@ -6,28 +6,44 @@ use std::{
 use crate::{Password, Username};
 
 /// Credentials to log into the application.
+///
+/// The username type defaults to [`Username`], which accepts any string. Use
+/// [`ValidatedUsername`] to normalize and validate usernames with a
+/// [`UsernamePolicy`].
+///
+/// [`ValidatedUsername`]: crate::ValidatedUsername
+/// [`UsernamePolicy`]: crate::UsernamePolicy
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Credentials {
+pub struct Credentials<U = Username> {
     /// Username to login.
-    pub username: Username,
+    pub username: U,
     /// Password to login.
     pub password: Password,
 }
 
-impl PartialOrd for Credentials {
-    fn partial_cmp(&self, other: &Credentials) -> Option<Ordering> {
+impl<U> PartialOrd for Credentials<U>
+where
+    U: Ord,
+{
+    fn partial_cmp(&self, other: &Credentials<U>) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for Credentials {
-    fn cmp(&self, other: &Credentials) -> Ordering {
+impl<U> Ord for Credentials<U>
+where
+    U: Ord,
+{
+    fn cmp(&self, other: &Credentials<U>) -> Ordering {
         self.username.cmp(&other.username)
     }
 }
 
-impl Display for Credentials {
+impl<U> Display for Credentials<U>
+where
+    U: Display,
+{
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}:{}", self.username, self.password)
     }
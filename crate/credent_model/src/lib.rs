@@ -7,8 +7,15 @@ mod password;
 mod profile;
 mod profiles;
 mod username;
+mod username_policy;
+mod validated_username;
 
 pub use crate::{
-    credentials::Credentials, password::Password, profile::Profile, profiles::Profiles,
+    credentials::Credentials,
+    password::Password,
+    profile::Profile,
+    profiles::Profiles,
     username::Username,
+    username_policy::{EmailUsernamePolicy, UsernameCheck, UsernameError, UsernamePolicy},
+    validated_username::ValidatedUsername,
 };
@@ -8,10 +8,17 @@ use std::{
 ///
 /// The `Debug` and `Display` implementations for this type mask the password.
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename = "credent_model::Password"))]
 #[derive(Clone, PartialEq, Eq)]
 pub struct PlainText(String);
 
 impl PlainText {
+    /// Name of the password newtype in serde's data model.
+    ///
+    /// Deserializers that read the plain text password, such as from a
+    /// prompt, match this name to encode it before it is deserialized.
+    pub const SERDE_NAME: &'static str = "credent_model::Password";
+
     /// Returns a new plain text password.
     pub fn new<S>(plain_text: S) -> Self
     where
@@ -0,0 +1,180 @@
+use std::fmt;
+
+/// Normalization and validation rules for a [`ValidatedUsername`].
+///
+/// Normalization happens in the following order, then the result is checked:
+///
+/// 1. Unicode NFC normalization, if [`NFC`] is `true`. This requires the
+///    `"unicode-normalization"` feature; without it, every username is
+///    rejected with [`UsernameError::NfcUnavailable`].
+/// 2. Trimming leading and trailing whitespace, if [`TRIM`] is `true`.
+/// 3. Lowercasing with [`str::to_lowercase`], if [`LOWERCASE`] is `true`.
+///    This is not full Unicode case folding, e.g. `"ß"` and `"ss"` remain
+///    different.
+///
+/// ```rust
+/// use credent_model::{Credentials, UsernameCheck, UsernameError, UsernamePolicy, ValidatedUsername};
+///
+/// #[derive(Debug)]
+/// struct Employee;
+///
+/// impl UsernamePolicy for Employee {
+///     const TRIM: bool = true;
+///     const LOWERCASE: bool = true;
+///
+///     fn check(username: &str) -> Result<(), UsernameError> {
+///         UsernameCheck::chars(username, |c| c.is_ascii_alphanumeric() || c == '.')
+///     }
+/// }
+///
+/// type EmployeeCredentials = Credentials<ValidatedUsername<Employee>>;
+/// ```
+///
+/// [`ValidatedUsername`]: crate::ValidatedUsername
+/// [`NFC`]: Self::NFC
+/// [`TRIM`]: Self::TRIM
+/// [`LOWERCASE`]: Self::LOWERCASE
+pub trait UsernamePolicy {
+    /// Whether to apply Unicode NFC normalization.
+    ///
+    /// Requires the `"unicode-normalization"` feature.
+    const NFC: bool = false;
+    /// Whether to trim leading and trailing whitespace.
+    const TRIM: bool = false;
+    /// Whether to lowercase the username with [`str::to_lowercase`].
+    const LOWERCASE: bool = false;
+
+    /// Checks that the normalized username is valid.
+    ///
+    /// The default implementation accepts any non-empty username.
+    fn check(_username: &str) -> Result<(), UsernameError> {
+        Ok(())
+    }
+
+    /// Returns the normalized username, or an error if it is not valid.
+    fn normalize(username: &str) -> Result<String, UsernameError> {
+        #[cfg(feature = "unicode-normalization")]
+        let username = if Self::NFC {
+            use unicode_normalization::UnicodeNormalization;
+            username.nfc().collect::<String>()
+        } else {
+            username.to_string()
+        };
+        #[cfg(not(feature = "unicode-normalization"))]
+        let username = if Self::NFC {
+            return Err(UsernameError::NfcUnavailable);
+        } else {
+            username.to_string()
+        };
+
+        let username = if Self::TRIM {
+            username.trim().to_string()
+        } else {
+            username
+        };
+        let username = if Self::LOWERCASE {
+            username.to_lowercase()
+        } else {
+            username
+        };
+
+        if username.is_empty() {
+            return Err(UsernameError::Empty);
+        }
+        Self::check(&username)?;
+
+        Ok(username)
+    }
+}
+
+/// Policy for usernames that are email addresses.
+///
+/// Usernames are trimmed, lowercased, and checked to be email addresses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmailUsernamePolicy;
+
+impl UsernamePolicy for EmailUsernamePolicy {
+    const TRIM: bool = true;
+    const LOWERCASE: bool = true;
+
+    fn check(username: &str) -> Result<(), UsernameError> {
+        UsernameCheck::email(username)
+    }
+}
+
+/// Common checks for use in [`UsernamePolicy::check`].
+#[derive(Debug)]
+pub struct UsernameCheck;
+
+impl UsernameCheck {
+    /// Checks that the username is shaped like an email address.
+    ///
+    /// This checks that there is exactly one `@`, with a non-empty local part
+    /// before it, a domain containing a `.` after it, and no whitespace.
+    pub fn email(username: &str) -> Result<(), UsernameError> {
+        let mut parts = username.split('@');
+        let local = parts.next().unwrap_or_default();
+        let domain = parts.next().unwrap_or_default();
+        let is_email = parts.next().is_none()
+            && !local.is_empty()
+            && domain.split('.').count() >= 2
+            && domain.split('.').all(|label| !label.is_empty())
+            && !username.chars().any(char::is_whitespace);
+
+        if is_email {
+            Ok(())
+        } else {
+            Err(UsernameError::EmailInvalid)
+        }
+    }
+
+    /// Checks that every character in the username is allowed.
+    ///
+    /// # Parameters
+    ///
+    /// * `username`: Username to check.
+    /// * `allowed`: Returns whether a character is allowed.
+    pub fn chars<F>(username: &str, allowed: F) -> Result<(), UsernameError>
+    where
+        F: Fn(char) -> bool,
+    {
+        match username.chars().find(|c| !allowed(*c)) {
+            Some(invalid_char) => Err(UsernameError::InvalidChar(invalid_char)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reasons a username does not satisfy a [`UsernamePolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UsernameError {
+    /// Username is empty after normalization.
+    Empty,
+    /// Username is not an email address.
+    EmailInvalid,
+    /// Username contains a character that is not allowed.
+    InvalidChar(char),
+    /// Policy requires NFC normalization, but the `"unicode-normalization"`
+    /// feature is not enabled.
+    NfcUnavailable,
+}
+
+impl fmt::Display for UsernameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Username must not be empty."),
+            Self::EmailInvalid => write!(f, "Username must be an email address."),
+            Self::InvalidChar(invalid_char) => write!(
+                f,
+                "Username must not contain `{}`.",
+                invalid_char.escape_default()
+            ),
+            Self::NfcUnavailable => write!(
+                f,
+                "Username policy requires NFC normalization, but the `unicode-normalization` feature is not enabled."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UsernameError {}
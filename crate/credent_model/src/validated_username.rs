@@ -0,0 +1,215 @@
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+    str::FromStr,
+};
+
+use crate::{Username, UsernameError, UsernamePolicy};
+
+/// Username that is normalized and validated by a [`UsernamePolicy`].
+///
+/// The username is normalized when it is constructed, including when it is
+/// deserialized, so comparisons and ordering use the normalized form.
+///
+/// Use this in place of [`Username`] with `Credentials<ValidatedUsername<P>>`.
+pub struct ValidatedUsername<P>(String, PhantomData<fn() -> P>);
+
+impl<P> ValidatedUsername<P>
+where
+    P: UsernamePolicy,
+{
+    /// Returns the normalized username, or an error if it does not satisfy
+    /// the policy.
+    pub fn new<S>(username: S) -> Result<Self, UsernameError>
+    where
+        S: AsRef<str>,
+    {
+        P::normalize(username.as_ref()).map(|username| Self(username, PhantomData))
+    }
+}
+
+impl<P> ValidatedUsername<P> {
+    /// Returns the username as a `&str`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the inner `String`.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<P> Clone for ValidatedUsername<P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<P> Debug for ValidatedUsername<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ValidatedUsername").field(&self.0).finish()
+    }
+}
+
+impl<P> Display for ValidatedUsername<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<P> PartialEq for ValidatedUsername<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<P> Eq for ValidatedUsername<P> {}
+
+impl<P> PartialOrd for ValidatedUsername<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P> Ord for ValidatedUsername<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<P> Hash for ValidatedUsername<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<P> Deref for ValidatedUsername<P> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<P> FromStr for ValidatedUsername<P>
+where
+    P: UsernamePolicy,
+{
+    type Err = UsernameError;
+
+    fn from_str(s: &str) -> Result<ValidatedUsername<P>, UsernameError> {
+        Self::new(s)
+    }
+}
+
+impl<P> TryFrom<Username> for ValidatedUsername<P>
+where
+    P: UsernamePolicy,
+{
+    type Error = UsernameError;
+
+    fn try_from(username: Username) -> Result<ValidatedUsername<P>, UsernameError> {
+        Self::new(username.0)
+    }
+}
+
+impl<P> From<ValidatedUsername<P>> for Username {
+    fn from(username: ValidatedUsername<P>) -> Self {
+        Username(username.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<P> serde::Serialize for ValidatedUsername<P> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, P> serde::Deserialize<'de> for ValidatedUsername<P>
+where
+    P: UsernamePolicy,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let username = String::deserialize(deserializer)?;
+        Self::new(username).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidatedUsername;
+    use crate::{EmailUsernamePolicy, UsernameError, UsernamePolicy};
+
+    #[derive(Debug)]
+    struct NfcPolicy;
+
+    impl UsernamePolicy for NfcPolicy {
+        const NFC: bool = true;
+    }
+
+    #[test]
+    fn email_policy_trims_and_lowercases() {
+        let username = ValidatedUsername::<EmailUsernamePolicy>::new("  Me@Example.COM \n");
+
+        assert_eq!(
+            Ok("me@example.com"),
+            username.as_ref().map(|username| username.as_str())
+        );
+    }
+
+    #[test]
+    fn email_policy_rejects_non_email() {
+        assert_eq!(
+            Err(UsernameError::EmailInvalid),
+            ValidatedUsername::<EmailUsernamePolicy>::new("me@localhost")
+        );
+        assert_eq!(
+            Err(UsernameError::Empty),
+            ValidatedUsername::<EmailUsernamePolicy>::new("   ")
+        );
+    }
+
+    #[test]
+    fn ordering_uses_normalized_username() -> Result<(), UsernameError> {
+        let upper = ValidatedUsername::<EmailUsernamePolicy>::new("ME@example.com")?;
+        let lower = ValidatedUsername::<EmailUsernamePolicy>::new("me@example.com")?;
+
+        assert_eq!(upper, lower);
+        assert_eq!(std::cmp::Ordering::Equal, upper.cmp(&lower));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn nfc_policy_composes_characters() {
+        let username = ValidatedUsername::<NfcPolicy>::new("e\u{301}");
+
+        assert_eq!(
+            Ok("\u{e9}"),
+            username.as_ref().map(|username| username.as_str())
+        );
+    }
+
+    #[cfg(not(feature = "unicode-normalization"))]
+    #[test]
+    fn nfc_policy_rejects_username_without_feature() {
+        assert_eq!(
+            Err(UsernameError::NfcUnavailable),
+            ValidatedUsername::<NfcPolicy>::new("e\u{301}")
+        );
+    }
+}
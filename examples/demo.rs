@@ -11,7 +11,7 @@
 
 use credent::{
     cli::CredentialsCliReader,
-    fs::{model::AppName, CredentialsFileLoader, CredentialsFileStorer},
+    fs::{model::AppName, CredentialsFile, CredentialsFileLoader, CredentialsFileStorer},
     model::{Credentials, Password, Profile},
 };
 
@@ -20,9 +20,7 @@ use demo_styles::{Colours, Logo, Prompt};
 mod demo_styles;
 
 /// Application name
-const CREDENT: AppName<'_> = AppName("credent");
-
-type CredentialsFile = credent::fs::CredentialsFile<Credentials>;
+const CREDENT: AppName<'_> = AppName::new("credent");
 
 #[cfg(feature = "backend-smol")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -33,7 +31,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Some(credentials) => credentials,
             None => prompt_and_save_credentials().await?,
         };
-        println!("");
+        println!();
 
         output_credentials(&credentials);
         output_password(&credentials.password);
@@ -53,7 +51,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Some(credentials) => credentials,
             None => prompt_and_save_credentials().await?,
         };
-        println!("");
+        println!();
 
         output_credentials(&credentials);
         output_password(&credentials.password);
@@ -84,7 +82,7 @@ async fn prompt_and_save_credentials() -> Result<Credentials, Box<dyn std::error
     };
 
     let credentials = credentials_cli_reader.prompt_from_tty().await?;
-    println!("");
+    println!();
 
     let profile = Profile::new_default(credentials);
     CredentialsFileStorer::store(CREDENT, &profile).await?;
@@ -110,7 +108,7 @@ fn output_credentials(credentials: &Credentials) {
         hint = Colours::output_hint().apply("debug"),
         value = credentials
     );
-    println!("");
+    println!();
 }
 
 fn output_password(password: &Password) {
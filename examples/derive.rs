@@ -0,0 +1,79 @@
+#![deny(missing_debug_implementations, missing_docs)]
+
+//! Reads custom credentials from file, or prompts for and stores them if they
+//! don't exist.
+
+use credent::{
+    cli::Credential,
+    fs::{model::AppName, CredentialsFile, CredentialsFileLoader, CredentialsFileStorer},
+    model::Profile,
+};
+use serde::{Deserialize, Serialize};
+
+/// Application name
+const CREDENT: AppName<'_> = AppName::new("credent");
+
+/// Credentials for an API.
+#[derive(Clone, PartialEq, Eq, Credential, Deserialize, Serialize)]
+struct ApiCredentials {
+    /// Account the API key belongs to.
+    #[credent(prompt = "Account: ", validate = "account_validate")]
+    account: String,
+    /// Key to access the API.
+    #[credent(secret)]
+    api_key: String,
+}
+
+/// Returns an error if the account is empty.
+fn account_validate(account: &str) -> Result<(), String> {
+    if account.is_empty() {
+        Err(String::from("Account must not be empty."))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the stored credentials, or prompts for and stores them.
+async fn api_credentials() -> Result<ApiCredentials, Box<dyn std::error::Error>> {
+    let profile =
+        CredentialsFileLoader::<ApiCredentials>::load_profile(CREDENT, Profile::<()>::DEFAULT_NAME)
+            .await?;
+    if let Some(profile) = profile {
+        profile.credentials.validate()?;
+        return Ok(profile.credentials);
+    }
+
+    let credentials = ApiCredentials::prompt_from_tty().await?;
+    let profile = Profile::new_default(credentials);
+    CredentialsFileStorer::store(CREDENT, &profile).await?;
+    println!(
+        "credentials written to: {}",
+        CredentialsFile::path(CREDENT)?.display()
+    );
+
+    Ok(profile.credentials)
+}
+
+#[cfg(feature = "backend-smol")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let credentials = api_credentials().await?;
+        println!("credentials: {}", credentials);
+        println!("credentials: {:?}", credentials);
+
+        Result::<(), Box<dyn std::error::Error>>::Ok(())
+    })
+}
+
+#[cfg(feature = "backend-tokio")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Runtime::new()?;
+
+    rt.block_on(async {
+        let credentials = api_credentials().await?;
+        println!("credentials: {}", credentials);
+        println!("credentials: {:?}", credentials);
+
+        Result::<(), Box<dyn std::error::Error>>::Ok(())
+    })
+}
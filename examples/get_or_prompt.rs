@@ -0,0 +1,49 @@
+#![deny(missing_debug_implementations, missing_docs)]
+
+//! Reads the default profile's credentials from file, or prompts for and stores
+//! them if they don't exist.
+
+use credent::{fs::model::AppName, Credent};
+
+/// Application name
+const CREDENT: AppName<'_> = AppName::new("credent");
+
+#[cfg(feature = "backend-smol")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let credentials = Credent::new(CREDENT)
+            .with_validator(|credentials| {
+                if credentials.username.is_empty() {
+                    Err(String::from("Username must not be empty."))
+                } else {
+                    Ok(())
+                }
+            })
+            .get_or_prompt()
+            .await?;
+        println!("credentials: {}", credentials);
+
+        Result::<(), Box<dyn std::error::Error>>::Ok(())
+    })
+}
+
+#[cfg(feature = "backend-tokio")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Runtime::new()?;
+
+    rt.block_on(async {
+        let credentials = Credent::new(CREDENT)
+            .with_validator(|credentials| {
+                if credentials.username.is_empty() {
+                    Err(String::from("Username must not be empty."))
+                } else {
+                    Ok(())
+                }
+            })
+            .get_or_prompt()
+            .await?;
+        println!("credentials: {}", credentials);
+
+        Result::<(), Box<dyn std::error::Error>>::Ok(())
+    })
+}
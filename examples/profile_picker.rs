@@ -0,0 +1,67 @@
+#![deny(missing_debug_implementations, missing_docs)]
+
+//! Lets the user choose a stored profile, or create a new one.
+//!
+//! With the `"raw-mode"` feature, profiles are chosen with the arrow keys.
+
+use credent::{
+    cli::{ProfilePicker, ProfileSelection},
+    fs::{model::AppName, CredentialsFileLoader},
+    model::{Credentials, Profiles},
+    Credent,
+};
+
+/// Application name
+const CREDENT: AppName<'_> = AppName::new("credent");
+
+/// Returns the chosen profile's credentials, prompting for and storing new
+/// ones if "Create new profile" is chosen.
+///
+/// The chosen profile is loaded by name, as `load_all` leaves the password
+/// empty for profiles with a secret reference.
+async fn credentials_picked() -> Result<(String, Credentials), Box<dyn std::error::Error>> {
+    let profiles = CredentialsFileLoader::<Credentials>::load_all(CREDENT)
+        .await?
+        .unwrap_or_else(Profiles::new);
+
+    match ProfilePicker::new(&profiles).pick().await? {
+        ProfileSelection::Existing(profile) => {
+            let credentials = Credent::new(CREDENT)
+                .with_profile_name(profile.name.clone())
+                .get_or_prompt()
+                .await?;
+            Ok((profile.name.clone(), credentials))
+        }
+        ProfileSelection::New(profile_name) => {
+            let credentials = Credent::new(CREDENT)
+                .with_profile_name(profile_name.clone())
+                .prompt_and_store()
+                .await?;
+            Ok((profile_name, credentials))
+        }
+    }
+}
+
+#[cfg(feature = "backend-smol")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    smol::block_on(async {
+        let (profile_name, credentials) = credentials_picked().await?;
+        println!("profile: {}", profile_name);
+        println!("credentials: {}", credentials);
+
+        Result::<(), Box<dyn std::error::Error>>::Ok(())
+    })
+}
+
+#[cfg(feature = "backend-tokio")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Runtime::new()?;
+
+    rt.block_on(async {
+        let (profile_name, credentials) = credentials_picked().await?;
+        println!("profile: {}", profile_name);
+        println!("credentials: {}", credentials);
+
+        Result::<(), Box<dyn std::error::Error>>::Ok(())
+    })
+}
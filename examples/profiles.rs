@@ -14,7 +14,7 @@ use std::{env, env::Args, ffi::OsStr, fmt::Write, path::PathBuf};
 
 use credent::{
     cli::CredentialsCliReader,
-    fs::{model::AppName, CredentialsFileLoader, CredentialsFileStorer},
+    fs::{model::AppName, CredentialsFile, CredentialsFileLoader, CredentialsFileStorer},
     model::{Credentials, Password},
 };
 
@@ -23,9 +23,8 @@ use demo_styles::{Colours, Logo, Prompt};
 mod demo_styles;
 
 /// Application name
-const CREDENT: AppName<'_> = AppName("credent");
+const CREDENT: AppName<'_> = AppName::new("credent");
 
-type CredentialsFile = credent::fs::CredentialsFile<Credentials>;
 type Profile = credent::model::Profile<Credentials>;
 
 #[cfg(feature = "backend-smol")]
@@ -38,7 +37,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Some(credentials) => credentials,
             None => prompt_and_save_credentials(profile_name.clone()).await?,
         };
-        println!("");
+        println!();
 
         output_profile_name(&profile_name);
         output_credentials(&credentials);
@@ -70,7 +69,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Some(credentials) => credentials,
             None => prompt_and_save_credentials(profile_name.clone()).await?,
         };
-        println!("");
+        println!();
 
         output_profile_name(&profile_name);
         output_credentials(&credentials);
@@ -96,10 +95,8 @@ fn get_profile_name() -> Result<String, String> {
     let full_path = args.next().map(PathBuf::from);
     let exe_name = full_path
         .as_ref()
-        .map(|full_path| full_path.file_name())
-        .flatten()
-        .map(OsStr::to_str)
-        .flatten()
+        .and_then(|full_path| full_path.file_name())
+        .and_then(OsStr::to_str)
         .unwrap_or("profiles");
 
     match args.next().as_deref() {
@@ -122,13 +119,12 @@ fn next_arg_as_profile(exe_name: &str, next_arg: Option<String>) -> Result<Strin
             "\
             Profile name must be specified.\n\
             \n\
-            {arrow}{exe_name} --profile {profile_placeholder}\n\
-            {indent}{highlight:>pad$}\n\
+            {arrow}{exe_name} --profile {profile_placeholder}\n  \
+            {highlight:>pad$}\n\
             ",
             arrow = Colours::prompt_label().apply("> "),
             exe_name = exe_name,
             profile_placeholder = Colours::error_label().apply(".."),
-            indent = "  ",
             highlight = Colours::error_label().apply("^^^^^^^^^^^^"),
             pad = exe_name.len() + " --profile ".len() + "^^".len()
         );
@@ -159,8 +155,7 @@ fn handle_unknown_arg(
 
     writeln!(
         buffer,
-        "{indent}{highlight:>pad$}",
-        indent = "  ",
+        "  {highlight:>pad$}",
         highlight = Colours::error_label().apply(highlight_str),
         pad = exe_name.len() + 1 + unknown_arg.len()
     )?;
@@ -194,7 +189,7 @@ async fn prompt_and_save_credentials(
     };
 
     let credentials = credentials_cli_reader.prompt_from_tty().await?;
-    println!("");
+    println!();
 
     let profile = Profile::new(profile_name, credentials);
     CredentialsFileStorer::store(CREDENT, &profile).await?;
@@ -229,7 +224,7 @@ fn output_credentials(credentials: &Credentials) {
         hint = Colours::output_hint().apply("debug"),
         value = credentials
     );
-    println!("");
+    println!();
 }
 
 fn output_password(password: &Password) {
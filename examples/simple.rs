@@ -4,15 +4,12 @@
 
 use credent::{
     cli::CredentialsCliReader,
-    fs::{model::AppName, CredentialsFileStorer},
+    fs::{model::AppName, CredentialsFile, CredentialsFileStorer},
     model::Profile,
 };
-use credent_model::Credentials;
 
 /// Application name
-const CREDENT: AppName<'_> = AppName("credent");
-
-type CredentialsFile = credent::fs::CredentialsFile<Credentials>;
+const CREDENT: AppName<'_> = AppName::new("credent");
 
 #[cfg(feature = "backend-smol")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -0,0 +1,25 @@
+use crate::Error;
+
+/// Value returned by an operation run with [`Credent::run_authenticated`].
+///
+/// Entered credentials are stored after the operation succeeds with them.
+/// Failing to store them does not undo the operation, so the failure is
+/// recorded in `store_error` alongside the operation's value.
+///
+/// [`Credent::run_authenticated`]: crate::Credent::run_authenticated
+#[derive(Debug)]
+pub struct Authenticated<T> {
+    /// Value returned by the operation.
+    pub value: T,
+    /// Error from storing the entered credentials, if they failed to be
+    /// stored.
+    pub store_error: Option<Error>,
+}
+
+impl<T> Authenticated<T> {
+    /// Returns the operation's value, discarding any error from storing the
+    /// entered credentials.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
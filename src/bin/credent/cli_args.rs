@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use credent::fs::model::AppNameBuf;
+
+/// Manages `~/.config/<app>/credentials`.
+#[derive(Debug, Parser)]
+#[command(name = "credent", version)]
+pub struct CliArgs {
+    /// Command to run.
+    #[command(subcommand)]
+    pub command: CliCommand,
+}
+
+/// Subcommands of the `credent` binary.
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Prompts for credentials and stores them in a profile.
+    Add {
+        #[command(flatten)]
+        app: AppArg,
+        /// Name of the profile to store the credentials in.
+        #[arg(long, default_value = "default")]
+        profile: String,
+        /// Replace the profile if it already exists, even if it is invalid.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Lists the profiles in the credentials file.
+    List {
+        #[command(flatten)]
+        app: AppArg,
+        #[command(flatten)]
+        reveal: RevealArg,
+    },
+    /// Shows the credentials in a profile.
+    Show {
+        #[command(flatten)]
+        app: AppArg,
+        /// Name of the profile to show.
+        #[arg(long, default_value = "default")]
+        profile: String,
+        #[command(flatten)]
+        reveal: RevealArg,
+    },
+    /// Removes a profile from the credentials file.
+    Remove {
+        #[command(flatten)]
+        app: AppArg,
+        /// Name of the profile to remove.
+        profile: String,
+    },
+    /// Renames a profile in the credentials file.
+    Rename {
+        #[command(flatten)]
+        app: AppArg,
+        /// Name of the profile to rename.
+        profile: String,
+        /// New name of the profile.
+        profile_new: String,
+    },
+    /// Makes a profile the default profile.
+    SetDefault {
+        #[command(flatten)]
+        app: AppArg,
+        /// Name of the profile to use as the default.
+        profile: String,
+    },
+    /// Writes the profiles in the credentials file to stdout.
+    Export {
+        #[command(flatten)]
+        app: AppArg,
+        #[command(flatten)]
+        reveal: RevealArg,
+    },
+    /// Upgrades the credentials file to the current version.
+    Migrate {
+        #[command(flatten)]
+        app: AppArg,
+        /// Show the changes without writing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Stores the profiles from another credentials file.
+    Import {
+        #[command(flatten)]
+        app: AppArg,
+        /// Credentials file to import profiles from.
+        path: PathBuf,
+    },
+    /// Outputs the path to the credentials file.
+    Path {
+        #[command(flatten)]
+        app: AppArg,
+    },
+}
+
+/// Name of the application whose credentials to manage.
+#[derive(Debug, Args)]
+pub struct AppArg {
+    /// Name of the application whose credentials to manage.
+    #[arg(long = "app")]
+    pub name: AppNameBuf,
+}
+
+/// Whether to output passwords in plain text.
+#[derive(Debug, Args)]
+pub struct RevealArg {
+    /// Outputs passwords in plain text instead of masking them.
+    #[arg(long = "reveal")]
+    pub enabled: bool,
+}
@@ -0,0 +1,758 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use credent::{
+    cli::CredentialsCliReader,
+    fs::{
+        model::LenientProfiles, CredentialsFileLoader, CredentialsFileMigrator,
+        CredentialsFileStorer, CredentialsTarget,
+    },
+    model::{Credentials, Profile},
+};
+use toml::{value::Table, Value};
+
+use crate::cli_args::{AppArg, CliArgs, CliCommand};
+
+/// Text shown in place of passwords when they are not revealed.
+const PASSWORD_MASK: &str = "******";
+/// Text shown in place of credentials whose secret reference is not resolved.
+const SECRET_REF_DISPLAY: &str = "(password from secret reference, use `--reveal` to resolve)";
+
+/// Runs the command specified on the command line.
+pub async fn run(cli_args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let out = &mut io::stdout();
+    match cli_args.command {
+        CliCommand::Add {
+            app,
+            profile,
+            force,
+        } => add(&target(&app), profile, force).await,
+        CliCommand::List { app, reveal } => list(&target(&app), reveal.enabled, out).await,
+        CliCommand::Show {
+            app,
+            profile,
+            reveal,
+        } => show(&target(&app), &profile, reveal.enabled, out).await,
+        CliCommand::Remove { app, profile } => remove(&target(&app), &profile).await,
+        CliCommand::Rename {
+            app,
+            profile,
+            profile_new,
+        } => rename(&target(&app), &profile, &profile_new).await,
+        CliCommand::SetDefault { app, profile } => set_default(&target(&app), &profile).await,
+        CliCommand::Export { app, reveal } => export(&target(&app), reveal.enabled, out).await,
+        CliCommand::Migrate { app, dry_run } => migrate(&target(&app), dry_run).await,
+        CliCommand::Import { app, path } => import(&target(&app), &path).await,
+        CliCommand::Path { app } => self::path(&target(&app), out),
+    }
+}
+
+fn target(app: &AppArg) -> CredentialsTarget<'_> {
+    CredentialsTarget::new(&app.name)
+}
+
+async fn add(
+    target: &CredentialsTarget<'_>,
+    profile_name: String,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Existence is checked by name, so that invalid profiles are not loaded
+    // and are also only replaced with `--force`.
+    if !force && profile_name_exists(target, &profile_name).await? {
+        return Err(format!(
+            "Profile `{profile_name}` already exists. Use `--force` to replace it."
+        )
+        .into());
+    }
+
+    let credentials = CredentialsCliReader::read_from_tty().await?;
+    let profile = Profile::new(profile_name, credentials);
+    if force {
+        CredentialsFileStorer::store_force(target.clone(), &profile).await?;
+    } else {
+        CredentialsFileStorer::store(target.clone(), &profile).await?;
+    }
+
+    eprintln!(
+        "Stored profile `{profile_name}` in `{path}`.",
+        profile_name = profile.name,
+        path = target.path()?.display()
+    );
+
+    Ok(())
+}
+
+async fn list<W>(
+    target: &CredentialsTarget<'_>,
+    reveal: bool,
+    out: &mut W,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    W: Write,
+{
+    // Invalid profiles are reported rather than hiding the valid ones.
+    let (lenient_profiles, profile_names_unresolved) = profiles_lenient(target, reveal).await?;
+    lenient_profiles
+        .errors
+        .iter()
+        .for_each(|error| eprintln!("Warning: {error}"));
+    let meta = CredentialsFileLoader::<Credentials>::load_meta(target.clone())
+        .await?
+        .unwrap_or_default();
+    let profile_name_default = meta.profile_name_resolve(Profile::<Credentials>::DEFAULT_NAME)?;
+    let default_marker = |profile_name: &str| {
+        if profile_name == profile_name_default {
+            " (default)"
+        } else {
+            ""
+        }
+    };
+
+    let profile_lines = lenient_profiles
+        .profiles
+        .iter()
+        .map(|profile| {
+            let profile_line = format!(
+                "{profile_name}{default_marker}: {username}:{password}",
+                profile_name = profile.name,
+                default_marker = default_marker(&profile.name),
+                username = profile.credentials.username,
+                password = password_display(&profile.credentials, reveal)
+            );
+            (profile.name.as_str(), profile_line)
+        })
+        .chain(profile_names_unresolved.iter().map(|profile_name| {
+            let profile_line = format!(
+                "{profile_name}{default_marker}: {SECRET_REF_DISPLAY}",
+                default_marker = default_marker(profile_name),
+            );
+            (profile_name.as_str(), profile_line)
+        }))
+        .collect::<BTreeMap<_, _>>();
+    profile_lines
+        .values()
+        .try_for_each(|profile_line| writeln!(out, "{profile_line}"))?;
+
+    Ok(())
+}
+
+async fn show<W>(
+    target: &CredentialsTarget<'_>,
+    profile_name: &str,
+    reveal: bool,
+    out: &mut W,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    W: Write,
+{
+    let profile = profile_existing(target, profile_name).await?;
+
+    writeln!(out, "profile: {}", profile.name)?;
+    writeln!(out, "username: {}", profile.credentials.username)?;
+    writeln!(
+        out,
+        "password: {}",
+        password_display(&profile.credentials, reveal)
+    )?;
+
+    Ok(())
+}
+
+async fn remove(
+    target: &CredentialsTarget<'_>,
+    profile_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Profiles that fail to load are removed as well, so existence is checked
+    // by name.
+    if !profile_name_exists(target, profile_name).await? {
+        return Err(format!("Profile `{profile_name}` does not exist.").into());
+    }
+
+    CredentialsFileStorer::<Credentials>::remove(target.clone(), profile_name).await?;
+    eprintln!("Removed profile `{profile_name}`.");
+    Ok(())
+}
+
+async fn rename(
+    target: &CredentialsTarget<'_>,
+    profile_name: &str,
+    profile_name_new: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    CredentialsFileStorer::<Credentials>::rename(target.clone(), profile_name, profile_name_new)
+        .await?;
+    if profile_name == profile_name_new {
+        eprintln!("Profile `{profile_name}` already has that name.");
+    } else {
+        eprintln!("Renamed profile `{profile_name}` to `{profile_name_new}`.");
+    }
+
+    Ok(())
+}
+
+async fn set_default(
+    target: &CredentialsTarget<'_>,
+    profile_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    CredentialsFileStorer::<Credentials>::set_default(target.clone(), profile_name).await?;
+    eprintln!("Profile `{profile_name}` is now the default profile.");
+
+    Ok(())
+}
+
+async fn export<W>(
+    target: &CredentialsTarget<'_>,
+    reveal: bool,
+    out: &mut W,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    W: Write,
+{
+    let (lenient_profiles, profile_names_unresolved) = profiles_lenient(target, reveal).await?;
+    if let Some(error) = lenient_profiles.errors.into_iter().next() {
+        return Err(error.into());
+    }
+    profile_names_unresolved.iter().for_each(|profile_name| {
+        eprintln!(
+            "Skipped profile `{profile_name}`, as its password is read from a secret reference. Use `--reveal` to include it."
+        )
+    });
+    let profiles = lenient_profiles.profiles;
+    let profiles_contents = if reveal {
+        toml::ser::to_string_pretty(&profiles)?
+    } else {
+        let profiles_masked = profiles
+            .iter()
+            .map(|profile| {
+                let mut credentials = Table::new();
+                credentials.insert(
+                    String::from("username"),
+                    Value::String(profile.credentials.username.to_string()),
+                );
+                credentials.insert(
+                    String::from("password"),
+                    Value::String(String::from(PASSWORD_MASK)),
+                );
+
+                (profile.name.clone(), Value::Table(credentials))
+            })
+            .collect::<Table>();
+        toml::ser::to_string_pretty(&profiles_masked)?
+    };
+    write!(out, "{profiles_contents}")?;
+
+    Ok(())
+}
+
+async fn migrate(
+    target: &CredentialsTarget<'_>,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let migration = if dry_run {
+        CredentialsFileMigrator::<Credentials>::migrate_dry_run(target.clone()).await?
+    } else {
+        CredentialsFileMigrator::<Credentials>::migrate(target.clone()).await?
+    };
+    let migration = match migration {
+        Some(migration) if migration.is_needed() => migration,
+        Some(migration) => {
+            eprintln!(
+                "Credentials file is already at version {}.",
+                migration.version_to
+            );
+            return Ok(());
+        }
+        None => return Err("Credentials file does not exist.".into()),
+    };
+
+    // The migrated contents are not shown, as they contain passwords.
+    if dry_run {
+        eprintln!(
+            "Credentials file would be migrated from version {} to {}:",
+            migration.version_from, migration.version_to
+        );
+    } else {
+        eprintln!(
+            "Migrated credentials file from version {} to {}:",
+            migration.version_from, migration.version_to
+        );
+    }
+    migration
+        .steps
+        .iter()
+        .for_each(|step| eprintln!("* {step}"));
+
+    Ok(())
+}
+
+async fn import(
+    target: &CredentialsTarget<'_>,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let LenientProfiles {
+        mut profiles,
+        errors,
+        secret_referenced,
+    } = CredentialsFileLoader::<Credentials>::load_file_lenient(
+        path,
+        None,
+        target.options().audit_log(),
+    )
+    .await?;
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error.into());
+    }
+    // Secret references are not resolved, so that importing a file does not
+    // run its `password_command`s.
+    profiles.retain(|profile| !secret_referenced.contains(&profile.name));
+    secret_referenced.iter().for_each(|profile_name| {
+        eprintln!(
+            "Skipped profile `{profile_name}`, as its password is read from a secret reference."
+        )
+    });
+    let profile_count = profiles.len();
+    CredentialsFileStorer::store_many(target.clone(), profiles).await?;
+    eprintln!(
+        "Imported {profile_count} profile(s) into `{path}`.",
+        path = target.path()?.display()
+    );
+
+    Ok(())
+}
+
+fn path<W>(target: &CredentialsTarget<'_>, out: &mut W) -> Result<(), Box<dyn std::error::Error>>
+where
+    W: Write,
+{
+    writeln!(out, "{}", target.path()?.display())?;
+
+    Ok(())
+}
+
+/// Returns the profiles that load successfully, and the names of profiles
+/// whose secret references were not resolved.
+///
+/// Secret references are only resolved when passwords are revealed, so that
+/// listing profiles does not run their `password_command`s.
+async fn profiles_lenient(
+    target: &CredentialsTarget<'_>,
+    reveal: bool,
+) -> Result<(LenientProfiles<Credentials>, Vec<String>), Box<dyn std::error::Error>> {
+    let mut lenient_profiles =
+        CredentialsFileLoader::<Credentials>::load_all_lenient(target.clone())
+            .await?
+            .unwrap_or_default();
+
+    let mut profile_names_unresolved = Vec::new();
+    let secret_referenced = std::mem::take(&mut lenient_profiles.secret_referenced);
+    for profile_name in secret_referenced {
+        // Listed profiles have an empty password in place of the secret.
+        lenient_profiles
+            .profiles
+            .retain(|profile| profile.name != profile_name);
+        if reveal {
+            match CredentialsFileLoader::<Credentials>::load_profile(target.clone(), &profile_name)
+                .await
+            {
+                Ok(profile) => lenient_profiles.profiles.extend(profile),
+                Err(error) => lenient_profiles.errors.push(error),
+            }
+        } else {
+            profile_names_unresolved.push(profile_name);
+        }
+    }
+
+    Ok((lenient_profiles, profile_names_unresolved))
+}
+
+async fn profile_name_exists(
+    target: &CredentialsTarget<'_>,
+    profile_name: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let profile_names = CredentialsFileLoader::<Credentials>::load_profile_names(target.clone())
+        .await?
+        .unwrap_or_default();
+    Ok(profile_names.contains(profile_name))
+}
+
+async fn profile_existing(
+    target: &CredentialsTarget<'_>,
+    profile_name: &str,
+) -> Result<Profile<Credentials>, Box<dyn std::error::Error>> {
+    CredentialsFileLoader::<Credentials>::load_profile(target.clone(), profile_name)
+        .await?
+        .ok_or_else(|| format!("Profile `{profile_name}` does not exist.").into())
+}
+
+fn password_display(credentials: &Credentials, reveal: bool) -> String {
+    if reveal {
+        credentials.password.plain_text().to_string()
+    } else {
+        String::from(PASSWORD_MASK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use credent::{
+        fs::{
+            model::AppName, CredentialsDir, CredentialsFileLoader, CredentialsFileOptions,
+            CredentialsFileStorer, CredentialsPathResolver, CredentialsTarget,
+        },
+        model::{Credentials, Password, Profile, Profiles, Username},
+    };
+
+    use super::{add, export, import, list, path, remove, rename, set_default, show};
+
+    #[test]
+    fn list_marks_default_profile_and_masks_passwords() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+            set_default(&target, "work").await?;
+
+            let mut out = Vec::new();
+            list(&target, false, &mut out).await?;
+
+            assert_eq!(
+                "default: me:******\nwork (default): you:******\n",
+                String::from_utf8(out)?
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn list_does_not_run_password_command_unless_revealed() -> Result<(), Box<dyn std::error::Error>>
+    {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            let marker_path = tempdir.path().join("marker");
+            let credentials_path = target.path()?;
+            fs::write(
+                &credentials_path,
+                format!(
+                    "[ci]\nusername = 'me'\npassword_command = \"touch '{}' && echo secret\"\n",
+                    marker_path.display()
+                ),
+            )?;
+
+            let mut out = Vec::new();
+            list(&target, false, &mut out).await?;
+
+            assert_eq!(
+                "ci: (password from secret reference, use `--reveal` to resolve)\n",
+                String::from_utf8(out)?
+            );
+            assert!(!marker_path.exists());
+
+            let target = target
+                .with_options(CredentialsFileOptions::new().with_secret_commands_allowed(true));
+            let mut out = Vec::new();
+            list(&target, true, &mut out).await?;
+
+            assert_eq!("ci: me:secret\n", String::from_utf8(out)?);
+            assert!(marker_path.exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn show_reveals_password_when_requested() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+
+            let mut out = Vec::new();
+            show(&target, "work", true, &mut out).await?;
+
+            assert_eq!(
+                "profile: work\nusername: you\npassword: code\n",
+                String::from_utf8(out)?
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn show_returns_err_when_profile_missing() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+
+            let mut out = Vec::new();
+            let show_result = show(&target, "missing", false, &mut out).await;
+
+            assert_eq!(
+                Some(String::from("Profile `missing` does not exist.")),
+                show_result.err().map(|error| error.to_string())
+            );
+            assert!(out.is_empty());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn remove_returns_err_when_profile_missing() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+
+            remove(&target, "work").await?;
+            let remove_result = remove(&target, "work").await;
+
+            assert_eq!(
+                Some(String::from("Profile `work` does not exist.")),
+                remove_result.err().map(|error| error.to_string())
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn remove_removes_profile_that_fails_to_load() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+            let credentials_path = target.path()?;
+            let contents = fs::read_to_string(&credentials_path)?;
+            fs::write(
+                &credentials_path,
+                format!("{contents}\n[broken]\nusername = 'them'\n"),
+            )?;
+
+            remove(&target, "broken").await?;
+
+            assert!(!fs::read_to_string(&credentials_path)?.contains("broken"));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn remove_removes_profile_with_missing_parent() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+            let credentials_path = target.path()?;
+            let contents = fs::read_to_string(&credentials_path)?;
+            fs::write(
+                &credentials_path,
+                format!("{contents}\n[orphan]\ninherits = 'missing'\n"),
+            )?;
+
+            remove(&target, "orphan").await?;
+
+            assert!(!fs::read_to_string(&credentials_path)?.contains("orphan"));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn add_returns_err_when_profile_that_fails_to_load_exists(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+            let credentials_path = target.path()?;
+            let contents = fs::read_to_string(&credentials_path)?;
+            fs::write(
+                &credentials_path,
+                format!("{contents}\n[broken]\nusername = 'them'\npassword_command = 'false'\n"),
+            )?;
+
+            let add_result = add(&target, String::from("broken"), false).await;
+
+            assert_eq!(
+                Some(String::from(
+                    "Profile `broken` already exists. Use `--force` to replace it."
+                )),
+                add_result.err().map(|error| error.to_string())
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn rename_to_same_name_leaves_profile_unchanged() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+            let contents = fs::read_to_string(target.path()?)?;
+
+            rename(&target, "work", "work").await?;
+
+            assert_eq!(contents, fs::read_to_string(target.path()?)?);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn rename_moves_profile_to_new_name() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+
+            rename(&target, "work", "job").await?;
+
+            let profiles = CredentialsFileLoader::<Credentials>::load_all(target.clone())
+                .await?
+                .unwrap_or_else(Profiles::new);
+            let profile_names = profiles
+                .iter()
+                .map(|profile| profile.name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(vec!["default", "job"], profile_names);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn export_masks_passwords_unless_revealed() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            profiles_store(&target).await?;
+
+            let mut out_masked = Vec::new();
+            export(&target, false, &mut out_masked).await?;
+            let mut out_revealed = Vec::new();
+            export(&target, true, &mut out_revealed).await?;
+
+            let out_masked = String::from_utf8(out_masked)?;
+            let out_revealed = String::from_utf8(out_revealed)?;
+            assert_eq!(
+                "[default]\n\
+                username = 'me'\n\
+                password = '******'\n\
+                \n\
+                [work]\n\
+                username = 'you'\n\
+                password = '******'\n",
+                out_masked
+            );
+            assert!(out_revealed.contains("username = 'you'"));
+            assert!(!out_revealed.contains("******"));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn import_stores_profiles_from_other_file() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            let tempdir_other = tempfile::tempdir()?;
+            let target_other = self::target(&tempdir_other);
+            profiles_store(&target_other).await?;
+
+            import(&target, &target_other.path()?).await?;
+
+            let profile = CredentialsFileLoader::<Credentials>::load_profile(target, "work")
+                .await?
+                .map(|profile| profile.credentials.username.0);
+            assert_eq!(Some(String::from("you")), profile);
+
+            Ok(())
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn import_skips_profiles_with_secret_ref() -> Result<(), Box<dyn std::error::Error>> {
+        smol::block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let target = target(&tempdir);
+            let import_path = tempdir.path().join("import");
+            let marker_path = tempdir.path().join("marker");
+            std::fs::write(
+                &import_path,
+                format!(
+                    "[default]\nusername = 'me'\npassword = 'secret'\n\n\
+                    [ci]\nusername = 'you'\npassword_command = \"touch '{}' && echo code\"\n",
+                    marker_path.display()
+                ),
+            )?;
+
+            import(&target, &import_path).await?;
+
+            let profile_names =
+                CredentialsFileLoader::<Credentials>::load_profile_names(target).await?;
+            assert_eq!(
+                Some(vec![String::from("default")]),
+                profile_names.map(|profile_names| profile_names.into_iter().collect::<Vec<_>>())
+            );
+            assert!(!marker_path.exists());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn path_outputs_resolved_path() -> Result<(), Box<dyn std::error::Error>> {
+        let tempdir = tempfile::tempdir()?;
+        let target = target(&tempdir);
+
+        let mut out = Vec::new();
+        path(&target, &mut out)?;
+
+        assert_eq!(
+            format!("{}\n", tempdir.path().join("credentials").display()),
+            String::from_utf8(out)?
+        );
+
+        Ok(())
+    }
+
+    fn target(tempdir: &tempfile::TempDir) -> CredentialsTarget<'static> {
+        CredentialsTarget::new(
+            CredentialsPathResolver::new(AppName::new("credent"))
+                .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+                .with_env_var(None::<String>),
+        )
+    }
+
+    async fn profiles_store(
+        target: &CredentialsTarget<'_>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut profiles = Profiles::new();
+        profiles.insert(Profile::new_default(Credentials {
+            username: Username(String::from("me")),
+            password: Password::new("secret"),
+        }));
+        profiles.insert(Profile::new(
+            String::from("work"),
+            Credentials {
+                username: Username(String::from("you")),
+                password: Password::new("code"),
+            },
+        ));
+        CredentialsFileStorer::store_many(target.clone(), profiles).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+#![deny(missing_debug_implementations, missing_docs)]
+
+//! Manages `~/.config/<app>/credentials` from the command line.
+//!
+//! ```bash
+//! cargo install credent --features "cli-bin backend-smol"
+//!
+//! credent add --app my_app --profile development
+//! credent list --app my_app
+//! credent show --app my_app --profile development --reveal
+//! ```
+
+use clap::Parser;
+
+use crate::cli_args::CliArgs;
+
+mod cli_args;
+mod commands;
+
+#[cfg(feature = "backend-smol")]
+fn main() {
+    let cli_args = CliArgs::parse();
+    let result = smol::block_on(commands::run(cli_args));
+
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "backend-tokio")]
+fn main() {
+    let cli_args = CliArgs::parse();
+    let result = tokio::runtime::Runtime::new()
+        .map_err(Box::<dyn std::error::Error>::from)
+        .and_then(|rt| rt.block_on(commands::run(cli_args)));
+
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,898 @@
+use std::{
+    fmt::{self, Display},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+
+use credent_cli::{Credential, CredentialsCliReader};
+use credent_fs::{CredentialsFileLoader, CredentialsFileStorer, CredentialsTarget};
+use credent_model::{Credentials, Profile};
+use serde::{Deserialize, Serialize};
+
+use crate::{Authenticated, Error};
+
+/// Async function that reads credentials from the user.
+type Prompter<'s, C> = Box<
+    dyn Fn() -> Pin<Box<dyn Future<Output = Result<C, credent_cli::model::Error>> + Send + 's>>
+        + Send
+        + Sync
+        + 's,
+>;
+
+/// Function that checks entered or stored credentials.
+///
+/// Returns a message describing the problem when the credentials are invalid.
+type Validator<'s, C> = Box<dyn Fn(&C) -> Result<(), String> + Send + Sync + 's>;
+
+/// Async function that checks entered credentials against the service they
+/// are for, such as by logging in.
+///
+/// Resolves to a message describing the problem when the credentials are
+/// rejected.
+type Verifier<'s, C> = Box<
+    dyn Fn(&C) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + 's>> + Send + Sync + 's,
+>;
+
+/// Reads credentials for a profile from file, or prompts the user for them.
+///
+/// This combines [`CredentialsFileLoader`], [`CredentialsCliReader`] and
+/// [`CredentialsFileStorer`] for the common case of an application that needs
+/// credentials to run:
+///
+/// ```rust,ignore
+/// use credent::{fs::model::AppName, Credent};
+///
+/// /// Application name
+/// const CREDENT: AppName<'_> = AppName::new("credent");
+///
+/// let credentials = Credent::new(CREDENT)
+///     .with_profile_name("dev")
+///     .with_validator(|credentials| {
+///         if credentials.username.contains('@') {
+///             Ok(())
+///         } else {
+///             Err(String::from("Username must be an email address."))
+///         }
+///     })
+///     .get_or_prompt()
+///     .await?;
+/// ```
+///
+/// Custom credentials types that implement [`Credential`] are read with
+/// [`new_with`](Self::new_with):
+///
+/// ```rust,ignore
+/// let credentials = Credent::<ApiCredentials>::new_with(CREDENT)
+///     .get_or_prompt()
+///     .await?;
+/// ```
+pub struct Credent<'s, C = Credentials> {
+    /// Credentials file to read from and store to.
+    target: CredentialsTarget<'s>,
+    /// Name of the profile to read.
+    profile_name: String,
+    /// Function that reads the credentials when they need to be entered.
+    prompter: Prompter<'s, C>,
+    /// Functions that check the credentials.
+    validators: Vec<Validator<'s, C>>,
+    /// Function that checks entered credentials before they are stored.
+    verifier: Option<Verifier<'s, C>>,
+    /// Number of times the user may enter credentials before giving up.
+    prompt_attempts: usize,
+    /// Whether entered credentials are stored in the credentials file.
+    store: bool,
+    /// Whether an invalid stored profile is overwritten.
+    store_force: bool,
+}
+
+impl<'s> Credent<'s> {
+    /// Default number of times the user may enter credentials.
+    pub const PROMPT_ATTEMPTS_DEFAULT: usize = 3;
+
+    /// Returns a new `Credent` for the default profile.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to read, or
+    ///   [`CredentialsTarget`].
+    pub fn new<R>(target: R) -> Self
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        let prompter = Self::prompter_cli("Username: ", "Password (input is hidden): ");
+        Self::new_with_prompter(target.into(), prompter)
+    }
+
+    /// Sets the prompts shown when the credentials need to be entered.
+    ///
+    /// This replaces the function set with [`with_prompter`].
+    ///
+    /// [`with_prompter`]: Self::with_prompter
+    ///
+    /// # Parameters
+    ///
+    /// * `username_prompt`: Prompt text for the username.
+    /// * `password_prompt`: Prompt text for the password.
+    pub fn with_prompts<UsernamePrompt, PasswordPrompt>(
+        mut self,
+        username_prompt: UsernamePrompt,
+        password_prompt: PasswordPrompt,
+    ) -> Self
+    where
+        UsernamePrompt: Display + Send + Sync + 's,
+        PasswordPrompt: Display + Send + Sync + 's,
+    {
+        self.prompter = Self::prompter_cli(username_prompt, password_prompt);
+        self
+    }
+
+    /// Returns a function that reads the credentials from the terminal with
+    /// the given prompts.
+    fn prompter_cli<UsernamePrompt, PasswordPrompt>(
+        username_prompt: UsernamePrompt,
+        password_prompt: PasswordPrompt,
+    ) -> Prompter<'s, Credentials>
+    where
+        UsernamePrompt: Display + Send + Sync + 's,
+        PasswordPrompt: Display + Send + Sync + 's,
+    {
+        let credentials_cli_reader = Arc::new(CredentialsCliReader {
+            username_prompt,
+            password_prompt,
+        });
+        Box::new(move || {
+            let credentials_cli_reader = Arc::clone(&credentials_cli_reader);
+            Box::pin(async move { credentials_cli_reader.prompt_from_tty().await })
+        })
+    }
+}
+
+impl<'s, C> Credent<'s, C>
+where
+    C: Credential + Send + 's,
+{
+    /// Returns a new `Credent` for the default profile of a custom credentials
+    /// type.
+    ///
+    /// Credentials are entered with [`Credential::prompt_from_tty`], and stored
+    /// credentials that fail [`Credential::validate`] are treated as missing.
+    ///
+    /// # Parameters
+    ///
+    /// * `target`: Name of the application whose credentials to read, or
+    ///   [`CredentialsTarget`].
+    pub fn new_with<R>(target: R) -> Self
+    where
+        R: Into<CredentialsTarget<'s>>,
+    {
+        Self::new_with_prompter(target.into(), Box::new(|| Box::pin(C::prompt_from_tty())))
+            .with_validator(|credentials| credentials.validate().map_err(|error| error.to_string()))
+    }
+}
+
+impl<'s, C> Credent<'s, C> {
+    /// Returns a new `Credent` for the default profile, which reads entered
+    /// credentials with the given function.
+    fn new_with_prompter(target: CredentialsTarget<'s>, prompter: Prompter<'s, C>) -> Self {
+        Self {
+            target,
+            profile_name: String::from(Profile::<C>::DEFAULT_NAME),
+            prompter,
+            validators: Vec::new(),
+            verifier: None,
+            prompt_attempts: Credent::<Credentials>::PROMPT_ATTEMPTS_DEFAULT,
+            store: true,
+            store_force: false,
+        }
+    }
+
+    /// Sets the name of the profile to read.
+    ///
+    /// Aliases are resolved when the profile is read.
+    pub fn with_profile_name<N>(mut self, profile_name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        self.profile_name = profile_name.into();
+        self
+    }
+
+    /// Sets the async function that reads the credentials when they need to
+    /// be entered, such as from a custom prompt or a test.
+    ///
+    /// ```rust,ignore
+    /// let credent = Credent::new(CREDENT).with_prompter(|| async {
+    ///     CredentialsCliReader::read_from_tty().await
+    /// });
+    /// ```
+    pub fn with_prompter<F, Fut>(mut self, prompter: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 's,
+        Fut: Future<Output = Result<C, credent_cli::model::Error>> + Send + 's,
+    {
+        self.prompter = Box::new(move || Box::pin(prompter()));
+        self
+    }
+
+    /// Adds a function that checks the credentials.
+    ///
+    /// Stored credentials that fail validation are treated as missing, and
+    /// the user is prompted for new ones. Entered credentials that fail
+    /// validation are rejected with the returned message, and the user is
+    /// prompted again.
+    pub fn with_validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&C) -> Result<(), String> + Send + Sync + 's,
+    {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Sets the async function that checks entered credentials, such as by
+    /// logging in to the service they are for.
+    ///
+    /// Entered credentials are only stored after the verifier accepts them.
+    /// When the verifier rejects them, its message is shown and the user is
+    /// prompted again. Stored credentials are not verified.
+    ///
+    /// ```rust,ignore
+    /// let credent = Credent::new(CREDENT).with_verifier(|credentials| {
+    ///     let credentials = credentials.clone();
+    ///     async move { login(&credentials).await.map_err(|e| e.to_string()) }
+    /// });
+    /// ```
+    pub fn with_verifier<F, Fut>(mut self, verifier: F) -> Self
+    where
+        F: Fn(&C) -> Fut + Send + Sync + 's,
+        Fut: Future<Output = Result<(), String>> + Send + 's,
+    {
+        self.verifier = Some(Box::new(move |credentials| Box::pin(verifier(credentials))));
+        self
+    }
+
+    /// Sets the number of times the user may enter credentials before
+    /// [`Error::CredentialsInvalid`] or [`Error::CredentialsUnverified`] is
+    /// returned.
+    ///
+    /// Values less than 1 are treated as 1.
+    pub fn with_prompt_attempts(mut self, prompt_attempts: usize) -> Self {
+        self.prompt_attempts = prompt_attempts.max(1);
+        self
+    }
+
+    /// Sets whether entered credentials are stored in the credentials file.
+    ///
+    /// Defaults to `true`.
+    pub fn with_store(mut self, store: bool) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Sets whether a stored profile that fails to be loaded is treated as
+    /// missing, and overwritten with the entered credentials.
+    ///
+    /// Defaults to `false`. See [`CredentialsFileStorer::store_force`].
+    pub fn with_store_force(mut self, store_force: bool) -> Self {
+        self.store_force = store_force;
+        self
+    }
+
+    /// Returns the credentials file to read from and store to.
+    pub fn target(&self) -> &CredentialsTarget<'s> {
+        &self.target
+    }
+
+    /// Returns the name of the profile to read.
+    pub fn profile_name(&self) -> &str {
+        &self.profile_name
+    }
+
+    /// Checks the credentials with every validator, returning the first
+    /// validation message.
+    fn validate(&self, credentials: &C) -> Result<(), String> {
+        self.validators
+            .iter()
+            .try_for_each(|validator| validator(credentials))
+    }
+}
+
+impl<'s, C> Credent<'s, C>
+where
+    C: Clone + Eq + for<'de> Deserialize<'de> + Serialize,
+{
+    /// Returns the stored credentials, if they exist and pass validation.
+    async fn load(&self) -> Result<Option<C>, Error> {
+        let profile =
+            CredentialsFileLoader::<C>::load_profile(self.target.clone(), &self.profile_name).await;
+        let profile = match profile {
+            // The invalid profile is replaced with the entered credentials.
+            Err(credent_fs::model::Error::CredentialsFileDeserialize {
+                profile_name: Some(_),
+                ..
+            }) if self.store_force => None,
+            profile => profile?,
+        };
+
+        Ok(profile
+            .map(|profile| profile.credentials)
+            .filter(|credentials| self.validate(credentials).is_ok()))
+    }
+
+    /// Stores the credentials under the profile name, unless storing is
+    /// disabled.
+    async fn store(&self, credentials: C) -> Result<C, Error> {
+        if !self.store {
+            return Ok(credentials);
+        }
+
+        let profile = Profile::new(self.profile_name.clone(), credentials);
+        if self.store_force {
+            CredentialsFileStorer::store_force(self.target.clone(), &profile).await?;
+        } else {
+            CredentialsFileStorer::store(self.target.clone(), &profile).await?;
+        }
+
+        Ok(profile.credentials)
+    }
+
+    /// Returns the profile's credentials, prompting the user if they are not
+    /// stored or fail validation.
+    ///
+    /// Entered credentials are stored unless [`with_store`] is `false`.
+    ///
+    /// [`with_store`]: Self::with_store
+    pub async fn get_or_prompt(&self) -> Result<C, Error> {
+        match self.load().await? {
+            Some(credentials) => Ok(credentials),
+            None => self.prompt_and_store().await,
+        }
+    }
+
+    /// Prompts the user for the profile's credentials, without reading stored
+    /// credentials.
+    ///
+    /// The credentials are stored once they pass validation and the verifier,
+    /// unless [`with_store`] is `false`.
+    ///
+    /// [`with_store`]: Self::with_store
+    pub async fn prompt_and_store(&self) -> Result<C, Error> {
+        let credentials = self.prompt().await?;
+        self.store(credentials).await
+    }
+
+    /// Runs an operation with the profile's credentials, prompting for new
+    /// credentials and retrying when the operation fails to authenticate.
+    ///
+    /// Stored credentials are used if they exist, otherwise the user is
+    /// prompted. When `is_auth_error` returns `true` for the operation's
+    /// error, such as for an HTTP 401 response, the user is prompted again and
+    /// the operation is retried. The user is prompted at most
+    /// [`with_prompt_attempts`] times in total, including prompts for
+    /// credentials that fail validation or the verifier.
+    ///
+    /// Entered credentials are only stored after the operation succeeds with
+    /// them, replacing the stored profile, unless [`with_store`] is `false`.
+    /// If they fail to be stored, the operation's value is still returned,
+    /// with the error in [`Authenticated::store_error`].
+    ///
+    /// ```rust,ignore
+    /// let response = credent
+    ///     .run_authenticated(
+    ///         |credentials| async move { client.get(&url, &credentials).await },
+    ///         |error: &ClientError| error.status() == Some(401),
+    ///     )
+    ///     .await?;
+    /// if let Some(error) = response.store_error.as_ref() {
+    ///     eprintln!("Credentials were not saved: {error}");
+    /// }
+    /// ```
+    ///
+    /// # Parameters
+    ///
+    /// * `operation`: Async function to run with the credentials.
+    /// * `is_auth_error`: Returns whether the operation's error means the
+    ///   credentials were rejected.
+    ///
+    /// [`with_prompt_attempts`]: Self::with_prompt_attempts
+    /// [`with_store`]: Self::with_store
+    pub async fn run_authenticated<T, E, F, Fut, A>(
+        &self,
+        operation: F,
+        is_auth_error: A,
+    ) -> Result<Authenticated<T>, E>
+    where
+        F: Fn(C) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        A: Fn(&E) -> bool,
+        E: From<Error>,
+    {
+        let mut prompt_count = 0;
+        let (mut credentials, mut prompted) = match self.load().await? {
+            Some(credentials) => (credentials, false),
+            None => (self.prompt_counted(&mut prompt_count).await?, true),
+        };
+
+        loop {
+            match operation(credentials.clone()).await {
+                Ok(value) => {
+                    let store_error = if prompted {
+                        self.store(credentials).await.err()
+                    } else {
+                        None
+                    };
+                    return Ok(Authenticated { value, store_error });
+                }
+                Err(error) if is_auth_error(&error) && prompt_count < self.prompt_attempts => {
+                    let message = format!(
+                        "Credentials for profile `{}` were rejected.",
+                        self.profile_name
+                    );
+                    CredentialsCliReader::write_message(&message)
+                        .await
+                        .map_err(Error::from)?;
+                    credentials = self.prompt_counted(&mut prompt_count).await?;
+                    prompted = true;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Prompts the user for credentials until they pass validation and the
+    /// verifier, or the attempts run out.
+    async fn prompt(&self) -> Result<C, Error> {
+        let mut prompt_count = 0;
+        self.prompt_counted(&mut prompt_count).await
+    }
+
+    /// Prompts the user for credentials until they pass validation and the
+    /// verifier, or the attempts run out.
+    ///
+    /// `prompt_count` is the number of times the user has already been
+    /// prompted, and is incremented for each prompt, so that repeated calls
+    /// share the attempts.
+    async fn prompt_counted(&self, prompt_count: &mut usize) -> Result<C, Error> {
+        loop {
+            *prompt_count += 1;
+            let attempts_remain = *prompt_count < self.prompt_attempts;
+            let credentials = (self.prompter)().await?;
+            if let Err(message) = self.validate(&credentials) {
+                if attempts_remain {
+                    CredentialsCliReader::write_message(&message).await?;
+                    continue;
+                }
+                return Err(Error::CredentialsInvalid {
+                    profile_name: self.profile_name.clone(),
+                    message,
+                });
+            }
+
+            let verification = match self.verifier.as_ref() {
+                Some(verifier) => verifier(&credentials).await,
+                None => Ok(()),
+            };
+            match verification {
+                Ok(()) => return Ok(credentials),
+                Err(message) if attempts_remain => {
+                    CredentialsCliReader::write_message(&message).await?
+                }
+                Err(message) => {
+                    return Err(Error::CredentialsUnverified {
+                        profile_name: self.profile_name.clone(),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<'s, C> fmt::Debug for Credent<'s, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credent")
+            .field("target", &self.target)
+            .field("profile_name", &self.profile_name)
+            .field("validators", &self.validators.len())
+            .field("verifier", &self.verifier.is_some())
+            .field("prompt_attempts", &self.prompt_attempts)
+            .field("store", &self.store)
+            .field("store_force", &self.store_force)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use credent_cli::Credential;
+    use credent_fs::{
+        model::{AppName, IntegrityKey},
+        CredentialsDir, CredentialsFileLoader, CredentialsFileOptions, CredentialsFileStorer,
+        CredentialsPathResolver, CredentialsTarget,
+    };
+    use credent_model::{Credentials, Password, Profile, Username};
+    use serde::{Deserialize, Serialize};
+
+    use super::Credent;
+    use crate::Error;
+
+    /// Custom credentials type, whose account must not be empty.
+    #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+    struct ApiCredentials {
+        account: String,
+        api_key: String,
+    }
+
+    impl Credential for ApiCredentials {
+        async fn prompt_from_tty() -> Result<Self, credent_cli::model::Error> {
+            unreachable!("Tests enter credentials with `with_prompter`.")
+        }
+
+        fn validate(&self) -> Result<(), credent_cli::model::Error> {
+            if self.account.is_empty() {
+                Err(credent_cli::model::Error::ValueInvalid {
+                    field: String::from("account"),
+                    message: String::from("Account must not be empty."),
+                })
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn resolver(tempdir: &tempfile::TempDir) -> CredentialsPathResolver<'static> {
+        CredentialsPathResolver::new(AppName::new("credent"))
+            .with_dir(CredentialsDir::Custom(tempdir.path().to_path_buf()))
+            .with_env_var(None::<String>)
+    }
+
+    fn credentials_of(username: &str) -> Credentials {
+        Credentials {
+            username: Username(String::from(username)),
+            password: Password::new("secret"),
+        }
+    }
+
+    /// Returns a `Credent` that enters the given usernames in order, and the
+    /// number of times it prompted.
+    fn credent_prompting(
+        tempdir: &tempfile::TempDir,
+        usernames: &'static [&'static str],
+    ) -> (Credent<'static>, Arc<AtomicUsize>) {
+        let prompt_count = Arc::new(AtomicUsize::new(0));
+        let credent = Credent::new(resolver(tempdir)).with_prompter({
+            let prompt_count = Arc::clone(&prompt_count);
+            move || {
+                let prompt_index = prompt_count.fetch_add(1, Ordering::SeqCst);
+                let credentials = credentials_of(usernames[prompt_index]);
+                async move { Ok(credentials) }
+            }
+        });
+
+        (credent, prompt_count)
+    }
+
+    fn email_validate(credentials: &Credentials) -> Result<(), String> {
+        if credentials.username.contains('@') {
+            Ok(())
+        } else {
+            Err(String::from("Username must be an email address."))
+        }
+    }
+
+    #[test]
+    fn get_or_prompt_returns_stored_credentials_that_pass_validation(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let profile = Profile::new_default(credentials_of("me@example.com"));
+            CredentialsFileStorer::store(resolver(&tempdir), &profile).await?;
+            let (credent, prompt_count) = credent_prompting(&tempdir, &[]);
+
+            let credentials = credent
+                .with_validator(email_validate)
+                .get_or_prompt()
+                .await?;
+
+            assert_eq!(profile.credentials, credentials);
+            assert_eq!(0, prompt_count.load(Ordering::SeqCst));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn get_or_prompt_prompts_when_stored_credentials_fail_validation(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let profile = Profile::new_default(credentials_of("me"));
+            CredentialsFileStorer::store(resolver(&tempdir), &profile).await?;
+            let (credent, prompt_count) = credent_prompting(&tempdir, &["me@example.com"]);
+
+            let credentials = credent
+                .with_validator(email_validate)
+                .get_or_prompt()
+                .await?;
+
+            assert_eq!(credentials_of("me@example.com"), credentials);
+            assert_eq!(1, prompt_count.load(Ordering::SeqCst));
+            let profile_stored =
+                CredentialsFileLoader::<Credentials>::load(resolver(&tempdir)).await?;
+            assert_eq!(
+                Some(credentials),
+                profile_stored.map(|profile| profile.credentials)
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn get_or_prompt_overwrites_invalid_profile_only_with_store_force(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let profile = Profile::new_default(credentials_of("me"));
+            CredentialsFileStorer::store(resolver(&tempdir), &profile).await?;
+            let credentials_path = resolver(&tempdir).resolve()?;
+            let contents = std::fs::read_to_string(&credentials_path)?;
+            std::fs::write(
+                &credentials_path,
+                format!("{contents}\n[broken]\nusername = 'me'\n"),
+            )?;
+
+            let (credent, prompt_count) = credent_prompting(&tempdir, &["ma"]);
+            let credent = credent.with_profile_name("broken");
+            let get_result = credent.get_or_prompt().await;
+            assert!(
+                matches!(
+                    get_result,
+                    Err(Error::Fs(
+                        credent_fs::model::Error::CredentialsFileDeserialize { .. }
+                    ))
+                ),
+                "Expected `get_result` to return `CredentialsFileDeserialize` error, but got `{:?}`.",
+                get_result
+            );
+            assert_eq!(0, prompt_count.load(Ordering::SeqCst));
+
+            let credentials = credent.with_store_force(true).get_or_prompt().await?;
+            assert_eq!(credentials_of("ma"), credentials);
+            let profile_stored =
+                CredentialsFileLoader::<Credentials>::load_profile(resolver(&tempdir), "broken")
+                    .await?;
+            assert_eq!(
+                Some(credentials),
+                profile_stored.map(|profile| profile.credentials)
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn prompt_and_store_returns_first_failing_validator_message(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let (credent, _prompt_count) = credent_prompting(&tempdir, &["me"]);
+
+            let prompt_result = credent
+                .with_prompt_attempts(1)
+                .with_validator(|_| Ok(()))
+                .with_validator(|_| Err(String::from("second")))
+                .with_validator(|_| Err(String::from("third")))
+                .prompt_and_store()
+                .await;
+
+            match prompt_result {
+                Err(Error::CredentialsInvalid { message, .. }) => assert_eq!("second", message),
+                prompt_result => panic!(
+                    "Expected `prompt_result` to return `CredentialsInvalid` error, but got `{:?}`.",
+                    prompt_result
+                ),
+            }
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn prompt_and_store_prompts_up_to_prompt_attempts() -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+
+            let (credent, prompt_count) =
+                credent_prompting(&tempdir, &["me", "ma", "me@example.com"]);
+            let prompt_result = credent
+                .with_store(false)
+                .with_prompt_attempts(2)
+                .with_validator(email_validate)
+                .prompt_and_store()
+                .await;
+            assert!(matches!(
+                prompt_result,
+                Err(Error::CredentialsInvalid { .. })
+            ));
+            assert_eq!(2, prompt_count.load(Ordering::SeqCst));
+
+            let (credent, prompt_count) =
+                credent_prompting(&tempdir, &["me", "ma", "me@example.com"]);
+            let credentials = credent
+                .with_store(false)
+                .with_validator(email_validate)
+                .prompt_and_store()
+                .await?;
+            assert_eq!(credentials_of("me@example.com"), credentials);
+            assert_eq!(3, prompt_count.load(Ordering::SeqCst));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn prompt_and_store_counts_verifier_rejections_as_attempts(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let (credent, prompt_count) = credent_prompting(&tempdir, &["me", "ma", "mine"]);
+
+            let prompt_result = credent
+                .with_store(false)
+                .with_prompt_attempts(2)
+                .with_validator(|credentials| {
+                    if credentials.username.as_str() == "me" {
+                        Err(String::from("Username is taken."))
+                    } else {
+                        Ok(())
+                    }
+                })
+                .with_verifier(|_| async { Err(String::from("Login failed.")) })
+                .prompt_and_store()
+                .await;
+
+            match prompt_result {
+                Err(Error::CredentialsUnverified { message, .. }) => {
+                    assert_eq!("Login failed.", message)
+                }
+                prompt_result => panic!(
+                    "Expected `prompt_result` to return `CredentialsUnverified` error, but got `{:?}`.",
+                    prompt_result
+                ),
+            }
+            assert_eq!(2, prompt_count.load(Ordering::SeqCst));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn get_or_prompt_validates_custom_credentials_type() -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let credentials_invalid = ApiCredentials {
+                account: String::new(),
+                api_key: String::from("secret"),
+            };
+            let profile = Profile::new_default(credentials_invalid);
+            CredentialsFileStorer::store(resolver(&tempdir), &profile).await?;
+
+            let credentials_entered = ApiCredentials {
+                account: String::from("me"),
+                api_key: String::from("secret"),
+            };
+            let credentials = Credent::<ApiCredentials>::new_with(resolver(&tempdir))
+                .with_prompter({
+                    let credentials_entered = credentials_entered.clone();
+                    move || {
+                        let credentials_entered = credentials_entered.clone();
+                        async move { Ok(credentials_entered) }
+                    }
+                })
+                .get_or_prompt()
+                .await?;
+
+            assert_eq!(credentials_entered, credentials);
+            let profile_stored =
+                CredentialsFileLoader::<ApiCredentials>::load(resolver(&tempdir)).await?;
+            assert_eq!(
+                Some(credentials_entered),
+                profile_stored.map(|profile| profile.credentials)
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn run_authenticated_prompts_at_most_prompt_attempts_times(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            let profile = Profile::new_default(credentials_of("me@example.com"));
+            CredentialsFileStorer::store(resolver(&tempdir), &profile).await?;
+            let (credent, prompt_count) =
+                credent_prompting(&tempdir, &["me", "ma@example.com", "mine@example.com"]);
+            let credent = credent
+                .with_prompt_attempts(3)
+                .with_validator(email_validate);
+
+            let run_result = credent
+                .run_authenticated(
+                    |_credentials| async {
+                        Result::<(), Error>::Err(Error::CredentialsUnverified {
+                            profile_name: String::from("default"),
+                            message: String::from("Unauthorized."),
+                        })
+                    },
+                    |error| matches!(error, Error::CredentialsUnverified { .. }),
+                )
+                .await;
+
+            assert!(matches!(
+                run_result,
+                Err(Error::CredentialsUnverified { .. })
+            ));
+            assert_eq!(3, prompt_count.load(Ordering::SeqCst));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn run_authenticated_returns_value_when_store_fails() -> Result<(), Box<dyn std::error::Error>>
+    {
+        block_on(async {
+            let tempdir = tempfile::tempdir()?;
+            // A signed file cannot be written without its integrity key.
+            let target_signed = CredentialsTarget::new(resolver(&tempdir)).with_options(
+                CredentialsFileOptions::new()
+                    .with_integrity_key(IntegrityKey::Secret(b"integrity key".to_vec())),
+            );
+            let profile = Profile::new(String::from("other"), credentials_of("other"));
+            CredentialsFileStorer::store(target_signed, &profile).await?;
+            let (credent, _prompt_count) = credent_prompting(&tempdir, &["me"]);
+
+            let authenticated = credent
+                .run_authenticated(
+                    |credentials| async move { Result::<_, Error>::Ok(credentials.username) },
+                    |_error| false,
+                )
+                .await?;
+
+            assert_eq!(Username(String::from("me")), authenticated.value);
+            assert!(matches!(
+                authenticated.store_error,
+                Some(Error::Fs(
+                    credent_fs::model::Error::CredentialsFileIntegrityKeyRequired { .. }
+                ))
+            ));
+
+            Ok(())
+        })
+    }
+
+    /// Runs the future on the backend's runtime, which writing rejection
+    /// messages to stderr needs.
+    fn block_on<F>(future: F) -> F::Output
+    where
+        F: std::future::Future,
+    {
+        #[cfg(feature = "backend-tokio")]
+        {
+            tokio::runtime::Runtime::new()
+                .expect("Expected tokio runtime to be created.")
+                .block_on(future)
+        }
+        #[cfg(not(feature = "backend-tokio"))]
+        {
+            smol::block_on(future)
+        }
+    }
+}
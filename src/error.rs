@@ -0,0 +1,72 @@
+use std::fmt;
+
+/// Errors when using [`Credent`].
+///
+/// [`Credent`]: crate::Credent
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Failed to load or store credentials.
+    Fs(credent_fs::model::Error),
+    /// Failed to read credentials from the command line.
+    Cli(credent_cli::model::Error),
+    /// Entered credentials were rejected by a validator on every attempt.
+    CredentialsInvalid {
+        /// Name of the profile whose credentials were being entered.
+        profile_name: String,
+        /// Message from the validator for the last attempt.
+        message: String,
+    },
+    /// Entered credentials were rejected by the verifier on every attempt.
+    CredentialsUnverified {
+        /// Name of the profile whose credentials were being entered.
+        profile_name: String,
+        /// Message from the verifier for the last attempt.
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fs(error) => write!(f, "{error}"),
+            Self::Cli(error) => write!(f, "{error}"),
+            Self::CredentialsInvalid {
+                profile_name,
+                message,
+            } => write!(
+                f,
+                "Credentials for profile `{profile_name}` are invalid. {message}"
+            ),
+            Self::CredentialsUnverified {
+                profile_name,
+                message,
+            } => write!(
+                f,
+                "Credentials for profile `{profile_name}` were rejected. {message}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Fs(error) => Some(error),
+            Self::Cli(error) => Some(error),
+            Self::CredentialsInvalid { .. } | Self::CredentialsUnverified { .. } => None,
+        }
+    }
+}
+
+impl From<credent_fs::model::Error> for Error {
+    fn from(error: credent_fs::model::Error) -> Self {
+        Self::Fs(error)
+    }
+}
+
+impl From<credent_cli::model::Error> for Error {
+    fn from(error: credent_cli::model::Error) -> Self {
+        Self::Cli(error)
+    }
+}
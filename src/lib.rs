@@ -20,7 +20,7 @@
 //! };
 //!
 //! /// Application name
-//! const CREDENT: AppName<'_> = AppName("credent");
+//! const CREDENT: AppName<'_> = AppName::new("credent");
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     smol::run(async {
@@ -31,7 +31,7 @@
 //!
 //!         println!(
 //!             "credentials written to: {}",
-//!             CredentialsFile::<Credentials>::path(CREDENT)?.display()
+//!             CredentialsFile::path(CREDENT)?.display()
 //!         );
 //!
 //!         Result::<(), Box<dyn std::error::Error>>::Ok(())
@@ -43,6 +43,12 @@
 //!
 //! [examples]: https://github.com/azriel91/credent/tree/main/examples
 
+pub use crate::{authenticated::Authenticated, credent::Credent, error::Error};
+
 pub use credent_cli as cli;
 pub use credent_fs as fs;
 pub use credent_model as model;
+
+mod authenticated;
+mod credent;
+mod error;
@@ -0,0 +1,139 @@
+#![deny(missing_debug_implementations, missing_docs)]
+
+//! Checks the behaviour of `#[derive(Credential)]`.
+
+use credent::cli::{model::Error, Credential};
+
+/// Credentials with a plain, a parsed, and a secret field.
+#[derive(Clone, PartialEq, Eq, Credential)]
+struct ApiCredentials {
+    /// Account the API key belongs to.
+    #[credent(validate = "account_validate", validate = "account_ascii_validate")]
+    account: String,
+    /// Port of the API.
+    #[credent(validate = "port_validate")]
+    port: u16,
+    /// Key to access the API.
+    #[credent(secret)]
+    api_key: String,
+}
+
+/// Credentials whose account type is chosen by the user.
+#[derive(Clone, PartialEq, Eq, Credential)]
+struct AccountCredentials<A> {
+    /// Account the API key belongs to.
+    account: A,
+    /// Key to access the API.
+    #[credent(secret)]
+    api_key: String,
+}
+
+/// Returns an error if the account is empty.
+fn account_validate(account: &str) -> Result<(), String> {
+    if account.is_empty() {
+        Err(String::from("Account must not be empty."))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns an error if the account is not ASCII.
+fn account_ascii_validate(account: &str) -> Result<(), String> {
+    if account.is_ascii() {
+        Ok(())
+    } else {
+        Err(String::from("Account must be ASCII."))
+    }
+}
+
+/// Returns an error if the port is 0.
+fn port_validate(port: &u16) -> Result<(), String> {
+    if *port == 0 {
+        Err(String::from("Port must not be 0."))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns credentials with the given account and port.
+fn api_credentials(account: &str, port: u16) -> ApiCredentials {
+    ApiCredentials {
+        account: String::from(account),
+        port,
+        api_key: String::from("secret"),
+    }
+}
+
+#[test]
+fn debug_masks_secret_fields() {
+    assert_eq!(
+        r#"ApiCredentials { account: "me", port: 443, api_key: "******" }"#,
+        format!("{:?}", api_credentials("me", 443))
+    );
+}
+
+#[test]
+fn display_masks_secret_fields() {
+    assert_eq!(
+        "account: me, port: 443, api_key: ******",
+        api_credentials("me", 443).to_string()
+    );
+}
+
+#[test]
+fn validate_accepts_valid_fields() -> Result<(), Error> {
+    api_credentials("me", 443).validate()
+}
+
+#[test]
+fn validate_returns_first_failing_validator_of_first_invalid_field() {
+    let validate_result = api_credentials("", 0).validate();
+    match validate_result {
+        Err(Error::ValueInvalid { field, message }) => {
+            assert_eq!("account", field);
+            assert_eq!("Account must not be empty.", message);
+        }
+        validate_result => panic!(
+            "Expected `validate_result` to return `ValueInvalid` error, but got `{:?}`.",
+            validate_result
+        ),
+    }
+
+    let validate_result = api_credentials("mé", 0).validate();
+    assert!(
+        matches!(
+            &validate_result,
+            Err(Error::ValueInvalid { field, message })
+                if field == "account" && message == "Account must be ASCII."
+        ),
+        "Expected `validate_result` to return `ValueInvalid` error for `account`, but got `{:?}`.",
+        validate_result
+    );
+
+    let validate_result = api_credentials("me", 0).validate();
+    assert!(
+        matches!(
+            &validate_result,
+            Err(Error::ValueInvalid { field, .. }) if field == "port"
+        ),
+        "Expected `validate_result` to return `ValueInvalid` error for `port`, but got `{:?}`.",
+        validate_result
+    );
+}
+
+#[test]
+fn debug_and_display_format_generic_fields() {
+    let account_credentials = AccountCredentials {
+        account: 42u32,
+        api_key: String::from("secret"),
+    };
+
+    assert_eq!(
+        r#"AccountCredentials { account: 42, api_key: "******" }"#,
+        format!("{:?}", account_credentials)
+    );
+    assert_eq!(
+        "account: 42, api_key: ******",
+        account_credentials.to_string()
+    );
+}